@@ -1,79 +1,513 @@
+use crate::audio::{InputLevel, RecordingMonitor};
 use crate::config::{Config, WaybarConfig};
+use crate::error::ChezWizperError;
+use crate::history::TranscriptionHistory;
+use crate::version;
+use crate::whisper;
 use anyhow::Result;
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
+    extract::{Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::sse::{Event, KeepAlive, Sse},
     response::Json,
+    response::Response,
     routing::{get, post},
     Router,
 };
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
 use tower::ServiceBuilder;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 #[derive(Clone)]
 pub enum ApiCommand {
-    ToggleRecording,
+    /// Start or stop recording; the overrides apply to a newly started recording
+    ToggleRecording(RecordingOverrides),
+    /// Stop recording if one is in progress, ignored otherwise
+    StopRecording,
+    /// Discard the current recording without transcribing it
+    CancelRecording,
+    /// Stop capturing but keep the recording open
+    PauseRecording,
+    ResumeRecording,
+    /// Re-read the config file and apply the settings that can change live
+    ReloadConfig,
 }
 
+/// Per-recording settings passed as `/toggle` query parameters, replacing
+/// `whisper.language` and `whisper.model` for that recording only
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RecordingOverrides {
+    pub language: Option<String>,
+    pub model: Option<String>,
+}
+
+impl RecordingOverrides {
+    /// Drop empty values and unknown language codes, warning about the latter
+    fn validated(self) -> Self {
+        let language = self
+            .language
+            .map(|language| language.trim().to_ascii_lowercase())
+            .filter(|language| !language.is_empty())
+            .filter(|language| {
+                let known = whisper::is_known_language(language);
+                if !known {
+                    warn!("Ignoring unknown language '{}'", language);
+                }
+                known
+            });
+        let model = self
+            .model
+            .map(|model| model.trim().to_string())
+            .filter(|model| !model.is_empty());
+
+        Self { language, model }
+    }
+}
+
+/// State transitions published by the main loop and streamed from `GET /events`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ApiEvent {
+    RecordingStarted,
+    RecordingPaused,
+    RecordingResumed,
+    RecordingStopped,
+    RecordingCancelled,
+    Transcribing,
+    TranscriptionComplete {
+        text: String,
+    },
+    /// `category` is a [`ChezWizperError::category`], or `no_speech` /
+    /// `low_confidence` when a transcription was discarded
+    Error {
+        message: String,
+        category: &'static str,
+    },
+    /// `POST /enabled` turned starting new recordings back on
+    Enabled,
+    /// `POST /enabled` turned starting new recordings off
+    Disabled,
+}
+
+impl ApiEvent {
+    /// SSE event name, matching the serialized `event` tag
+    pub fn name(&self) -> &'static str {
+        match self {
+            ApiEvent::RecordingStarted => "recording_started",
+            ApiEvent::RecordingPaused => "recording_paused",
+            ApiEvent::RecordingResumed => "recording_resumed",
+            ApiEvent::RecordingStopped => "recording_stopped",
+            ApiEvent::RecordingCancelled => "recording_cancelled",
+            ApiEvent::Transcribing => "transcribing",
+            ApiEvent::TranscriptionComplete { .. } => "transcription_complete",
+            ApiEvent::Error { .. } => "error",
+            ApiEvent::Enabled => "enabled",
+            ApiEvent::Disabled => "disabled",
+        }
+    }
+
+    /// Error event carrying the indicator message and category of `error`
+    pub fn error(error: &ChezWizperError) -> Self {
+        ApiEvent::Error {
+            message: error.user_message(),
+            category: error.category(),
+        }
+    }
+}
+
+/// Coarse application state reported by `GET /status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppStatus {
+    Idle,
+    Recording,
+    Paused,
+    Processing,
+    Error,
+    /// Idle with `/toggle` turned off by `POST /enabled`
+    Disabled,
+}
+
+impl AppStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            AppStatus::Idle => "idle",
+            AppStatus::Recording => "recording",
+            AppStatus::Paused => "paused",
+            AppStatus::Processing => "processing",
+            AppStatus::Error => "error",
+            AppStatus::Disabled => "disabled",
+        }
+    }
+}
+
+/// Follows `ApiEvent`s to know whether we're idle, recording, processing or
+/// showing a recent error
+#[derive(Debug)]
+pub struct StatusTracker {
+    status: AppStatus,
+    error: Option<String>,
+    changed_at: Instant,
+}
+
+impl Default for StatusTracker {
+    fn default() -> Self {
+        Self {
+            status: AppStatus::Idle,
+            error: None,
+            changed_at: Instant::now(),
+        }
+    }
+}
+
+impl StatusTracker {
+    pub fn apply(&mut self, event: &ApiEvent) {
+        let (status, error) = match event {
+            ApiEvent::RecordingStarted | ApiEvent::RecordingResumed => (AppStatus::Recording, None),
+            ApiEvent::RecordingPaused => (AppStatus::Paused, None),
+            ApiEvent::RecordingStopped | ApiEvent::Transcribing => (AppStatus::Processing, None),
+            ApiEvent::RecordingCancelled | ApiEvent::TranscriptionComplete { .. } => {
+                (AppStatus::Idle, None)
+            }
+            ApiEvent::Error { message, .. } => (AppStatus::Error, Some(message.clone())),
+            // Reported next to the status rather than replacing it
+            ApiEvent::Enabled | ApiEvent::Disabled => return,
+        };
+        self.status = status;
+        self.error = error;
+        self.changed_at = Instant::now();
+    }
+
+    /// Current status and error message; an error older than `error_timeout` reads as idle
+    pub fn current(&self, error_timeout: Duration) -> (AppStatus, Option<&str>) {
+        if self.status == AppStatus::Error && self.changed_at.elapsed() >= error_timeout {
+            return (AppStatus::Idle, None);
+        }
+        (self.status, self.error.as_deref())
+    }
+}
+
+/// Outcome of one readiness check reported by `/health`
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthCheck {
+    pub name: &'static str,
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl HealthCheck {
+    pub fn new(name: &'static str, ok: bool, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            ok,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Runs the `/health` checks. Called on a blocking thread, so it may enumerate
+/// devices and search PATH.
+pub type HealthProbe = Arc<dyn Fn() -> Vec<HealthCheck> + Send + Sync>;
+
+/// Collects the text injection details behind `GET /diagnostics`, also on a
+/// blocking thread
+pub type DiagnosticsProbe = Arc<dyn Fn() -> Value + Send + Sync>;
+
 #[derive(Clone)]
 pub struct AppState {
     tx: mpsc::Sender<ApiCommand>,
     recording: Arc<Mutex<bool>>,
+    /// Cleared by `POST /enabled` so `/toggle` doesn't start new recordings
+    enabled: Arc<Mutex<bool>>,
+    status: Arc<Mutex<StatusTracker>>,
+    history: Arc<Mutex<TranscriptionHistory>>,
+    events: broadcast::Sender<ApiEvent>,
     waybar_config: WaybarConfig,
+    health: Option<HealthProbe>,
+    diagnostics: Option<DiagnosticsProbe>,
+    /// Live input level behind `GET /level`
+    monitor: Option<RecordingMonitor>,
+    /// Resolved provider name, which may differ from the config when auto-detected
+    provider: Option<&'static str>,
+    /// Configured model, replaced by the provider's default once it is known
+    model: Option<String>,
 }
 
 pub struct ApiServer {
     port: u16,
     state: AppState,
+    auth_token: Option<Arc<str>>,
+    protect_status: bool,
 }
 
 impl ApiServer {
-    pub fn new(tx: mpsc::Sender<ApiCommand>, recording: Arc<Mutex<bool>>, config: &Config) -> Self {
+    pub fn new(
+        tx: mpsc::Sender<ApiCommand>,
+        recording: Arc<Mutex<bool>>,
+        status: Arc<Mutex<StatusTracker>>,
+        history: Arc<Mutex<TranscriptionHistory>>,
+        events: broadcast::Sender<ApiEvent>,
+        config: &Config,
+    ) -> Self {
         Self {
             port: 3737, // WHSP in numbers
             state: AppState {
                 tx,
                 recording,
+                enabled: Arc::new(Mutex::new(true)),
+                status,
+                history,
+                events,
                 waybar_config: config.ui.waybar.clone(),
+                health: None,
+                diagnostics: None,
+                monitor: None,
+                provider: None,
+                model: config.whisper.model.clone(),
             },
+            // Taken from [api] auth_token only; there is intentionally no env override
+            auth_token: config.api.auth_token.as_deref().map(Arc::from),
+            protect_status: config.api.protect_status,
+        }
+    }
+
+    /// Checks run by `GET /health`; without a probe it always reports healthy
+    pub fn with_health_probe(mut self, probe: HealthProbe) -> Self {
+        self.state.health = Some(probe);
+        self
+    }
+
+    /// Details reported by `GET /diagnostics`; without a probe it reports none
+    pub fn with_diagnostics_probe(mut self, probe: DiagnosticsProbe) -> Self {
+        self.state.diagnostics = Some(probe);
+        self
+    }
+
+    /// Recording monitor read by `GET /level`; without one the level is always zero
+    pub fn with_level_monitor(mut self, monitor: RecordingMonitor) -> Self {
+        self.state.monitor = Some(monitor);
+        self
+    }
+
+    /// Name of the provider in use, reported by `GET /`
+    pub fn with_provider_name(mut self, provider: &'static str) -> Self {
+        self.state.provider = Some(provider);
+        self
+    }
+
+    /// Model the provider resolved; keeps the configured one when it has none
+    pub fn with_model(mut self, model: Option<&str>) -> Self {
+        if let Some(model) = model {
+            self.state.model = Some(model.to_string());
         }
+        self
     }
 
     pub async fn start(self) -> Result<()> {
-        let app = Router::new()
-            .route("/", get(status))
-            .route("/toggle", post(toggle_recording))
-            .route("/status", get(recording_status))
-            .layer(ServiceBuilder::new())
-            .with_state(self.state);
+        let port = self.port;
+        let authenticated = self.auth_token.is_some();
+        let app = self.router();
 
-        let listener = tokio::net::TcpListener::bind(&format!("127.0.0.1:{}", self.port)).await?;
+        let listener = tokio::net::TcpListener::bind(&format!("127.0.0.1:{}", port)).await?;
 
-        info!("API server listening on http://127.0.0.1:{}", self.port);
+        info!("API server listening on http://127.0.0.1:{}", port);
+        if authenticated {
+            info!("API requests require an Authorization: Bearer token");
+        }
         info!("Endpoints:");
-        info!("  POST /toggle - Toggle recording");
+        info!("  POST /toggle - Toggle recording (?language=es&model=... for this recording only)");
+        info!("  POST /cancel - Discard the current recording");
+        info!("  POST /pause  - Pause the current recording");
+        info!("  POST /resume - Continue a paused recording");
+        info!("  POST /enabled - Stop or resume starting recordings ({{\"enabled\": false}})");
         info!("  GET /status  - Get recording status");
+        info!("  GET /health  - Check provider, audio input and text injection");
+        info!("  GET /diagnostics - Show detected injection and clipboard tools");
+        info!("  GET /level   - Get the live input level while recording");
+        info!("  GET /info    - Get version and build info");
+        info!("  GET /history - Get recent transcriptions");
+        info!("  GET /last    - Get latest transcription (?format=segments for timestamps)");
+        info!("  GET /events  - Stream state changes (Server-Sent Events)");
 
         axum::serve(listener, app).await?;
 
         Ok(())
     }
+
+    /// Routes, with the token required where `auth_token` and `protect_status` ask for it
+    fn router(self) -> Router {
+        let control = Router::new()
+            .route("/toggle", post(toggle_recording))
+            .route("/cancel", post(cancel_recording))
+            .route("/pause", post(pause_recording))
+            .route("/resume", post(resume_recording))
+            .route("/enabled", post(set_enabled));
+
+        // Endpoints that return dictated text
+        let transcripts = Router::new()
+            .route("/history", get(history))
+            .route("/last", get(last_transcription))
+            .route("/events", get(events));
+
+        let reads = Router::new()
+            .route("/", get(status))
+            .route("/info", get(info))
+            .route("/status", get(recording_status))
+            .route("/health", get(health))
+            .route("/diagnostics", get(diagnostics))
+            .route("/level", get(level));
+
+        // Recording controls and transcripts always need the token when one is
+        // configured; the other read-only endpoints only when protect_status is set
+        let (control, transcripts, reads) = match &self.auth_token {
+            Some(token) => {
+                let auth = middleware::from_fn_with_state(token.clone(), require_token);
+                let reads = if self.protect_status {
+                    reads.route_layer(auth.clone())
+                } else {
+                    reads
+                };
+                (
+                    control.route_layer(auth.clone()),
+                    transcripts.route_layer(auth),
+                    reads,
+                )
+            }
+            None => (control, transcripts, reads),
+        };
+
+        Router::new()
+            .merge(control)
+            .merge(transcripts)
+            .merge(reads)
+            .layer(ServiceBuilder::new())
+            .with_state(self.state)
+    }
+}
+
+async fn require_token(
+    State(token): State<Arc<str>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    if is_authorized(request.headers(), &token) {
+        Ok(next.run(request).await)
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+fn is_authorized(headers: &HeaderMap, token: &str) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|provided| provided == token)
 }
 
-async fn status() -> Json<Value> {
+async fn status(State(state): State<AppState>) -> Json<Value> {
     Json(json!({
         "service": "chezwizper",
-        "version": "0.1.0",
+        "version": version::VERSION,
+        "git_hash": version::GIT_HASH,
+        "build_timestamp": version::BUILD_TIMESTAMP,
+        "provider": state.provider,
+        "model": state.model,
         "status": "running"
     }))
 }
 
-async fn toggle_recording(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
-    match state.tx.send(ApiCommand::ToggleRecording).await {
+async fn health(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let checks = match state.health {
+        Some(probe) => tokio::task::spawn_blocking(move || probe())
+            .await
+            .unwrap_or_else(|e| vec![HealthCheck::new("health", false, e.to_string())]),
+        None => Vec::new(),
+    };
+    health_response(&checks)
+}
+
+async fn diagnostics(State(state): State<AppState>) -> Json<Value> {
+    let injection = match state.diagnostics {
+        Some(probe) => tokio::task::spawn_blocking(move || probe())
+            .await
+            .unwrap_or_else(|e| json!({ "error": e.to_string() })),
+        None => Value::Null,
+    };
+
+    Json(json!({
+        "version": version::VERSION,
+        "git_hash": version::GIT_HASH,
+        "provider": state.provider,
+        "model": state.model,
+        "injection": injection,
+    }))
+}
+
+/// Latest input level, or zero when no recording is running
+async fn level(State(state): State<AppState>) -> Json<Value> {
+    let recording = *state.recording.lock().await;
+    let level = match &state.monitor {
+        Some(monitor) if recording => monitor.level(),
+        _ => InputLevel::default(),
+    };
+
+    Json(json!({
+        "recording": recording,
+        "rms": level.rms,
+        "peak": level.peak,
+    }))
+}
+
+/// 200 when every check passes, otherwise 503 listing the failing checks
+fn health_response(checks: &[HealthCheck]) -> (StatusCode, Json<Value>) {
+    let failing: Vec<&str> = checks
+        .iter()
+        .filter(|check| !check.ok)
+        .map(|check| check.name)
+        .collect();
+    let code = if failing.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        code,
+        Json(json!({
+            "healthy": failing.is_empty(),
+            "failing": failing,
+            "checks": checks,
+        })),
+    )
+}
+
+async fn info() -> Json<Value> {
+    Json(version::info_json())
+}
+
+async fn toggle_recording(
+    State(state): State<AppState>,
+    Query(overrides): Query<RecordingOverrides>,
+) -> Result<Json<Value>, StatusCode> {
+    // A recording started before disabling can still be stopped
+    if !*state.enabled.lock().await && !*state.recording.lock().await {
+        info!("Ignoring toggle, ChezWizper is disabled");
+        return Ok(Json(json!({
+            "success": false,
+            "disabled": true
+        })));
+    }
+
+    let overrides = overrides.validated();
+    match state.tx.send(ApiCommand::ToggleRecording(overrides)).await {
         Ok(_) => {
             info!("Toggle recording command received via API");
             Ok(Json(json!({
@@ -88,32 +522,394 @@ async fn toggle_recording(State(state): State<AppState>) -> Result<Json<Value>,
     }
 }
 
+async fn cancel_recording(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    match state.tx.send(ApiCommand::CancelRecording).await {
+        Ok(_) => {
+            info!("Cancel recording command received via API");
+            Ok(Json(json!({
+                "success": true,
+                "message": "Recording cancelled"
+            })))
+        }
+        Err(e) => {
+            error!("Failed to send cancel command: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct EnabledRequest {
+    enabled: bool,
+}
+
+async fn set_enabled(
+    State(state): State<AppState>,
+    Json(request): Json<EnabledRequest>,
+) -> Json<Value> {
+    let was_enabled = std::mem::replace(&mut *state.enabled.lock().await, request.enabled);
+    if was_enabled != request.enabled {
+        let event = if request.enabled {
+            ApiEvent::Enabled
+        } else {
+            ApiEvent::Disabled
+        };
+        let _ = state.events.send(event);
+    }
+    info!(
+        "ChezWizper {} via API",
+        if request.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    Json(json!({
+        "success": true,
+        "enabled": request.enabled
+    }))
+}
+
+async fn pause_recording(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    match state.tx.send(ApiCommand::PauseRecording).await {
+        Ok(_) => {
+            info!("Pause recording command received via API");
+            Ok(Json(json!({
+                "success": true,
+                "message": "Recording paused"
+            })))
+        }
+        Err(e) => {
+            error!("Failed to send pause command: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn resume_recording(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    match state.tx.send(ApiCommand::ResumeRecording).await {
+        Ok(_) => {
+            info!("Resume recording command received via API");
+            Ok(Json(json!({
+                "success": true,
+                "message": "Recording resumed"
+            })))
+        }
+        Err(e) => {
+            error!("Failed to send resume command: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 async fn recording_status(
     Query(params): Query<HashMap<String, String>>,
     State(state): State<AppState>,
 ) -> Json<Value> {
     let recording = *state.recording.lock().await;
+    let enabled = *state.enabled.lock().await;
+    let tracker = state.status.lock().await;
+    let error_timeout = Duration::from_secs(state.waybar_config.error_timeout_secs);
+    let (status, error) = match tracker.current(error_timeout) {
+        // The flag flips before the recording actually starts or stops
+        (AppStatus::Idle | AppStatus::Error, _) if recording => (AppStatus::Recording, None),
+        (AppStatus::Recording | AppStatus::Paused, _) if !recording => {
+            (AppStatus::Processing, None)
+        }
+        (AppStatus::Idle, _) if !enabled => (AppStatus::Disabled, None),
+        current => current,
+    };
 
     // Check if waybar style is requested
     if params.get("style") == Some(&"waybar".to_string()) {
-        return Json(generate_waybar_response(recording, &state.waybar_config));
+        return Json(generate_waybar_response(
+            status,
+            error,
+            &state.waybar_config,
+        ));
     }
 
     // Default JSON response
-    Json(json!({
+    let mut response = json!({
         "recording": recording,
-        "status": if recording { "recording" } else { "idle" }
-    }))
+        "enabled": enabled,
+        "status": status.as_str(),
+        "version": version::VERSION
+    });
+    if let Some(error) = error {
+        response["error"] = json!(error);
+    }
+    Json(response)
+}
+
+async fn history(State(state): State<AppState>) -> Json<Value> {
+    let entries = state.history.lock().await.entries();
+    Json(json!(entries))
 }
 
-fn generate_waybar_response(recording: bool, config: &WaybarConfig) -> Value {
+async fn last_transcription(
+    Query(params): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, StatusCode> {
+    let entry = state
+        .history
+        .lock()
+        .await
+        .last()
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if params.get("format").map(String::as_str) == Some("segments") {
+        return Ok(Json(json!({
+            "text": entry.text,
+            "timestamp": entry.timestamp,
+            "provider": entry.provider,
+            "segments": entry.segments,
+        })));
+    }
+
+    Ok(Json(json!(entry)))
+}
+
+async fn events(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    // Subscribers that fall behind skip the events they missed
+    let stream = BroadcastStream::new(state.events.subscribe())
+        .filter_map(|event| event.ok())
+        .map(|event| Event::default().event(event.name()).json_data(&event));
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+fn generate_waybar_response(
+    status: AppStatus,
+    error: Option<&str>,
+    config: &WaybarConfig,
+) -> Value {
+    let (text, tooltip) = match status {
+        AppStatus::Idle => (&config.idle_text, config.idle_tooltip.clone()),
+        AppStatus::Recording => (&config.recording_text, config.recording_tooltip.clone()),
+        AppStatus::Paused => (&config.paused_text, config.paused_tooltip.clone()),
+        AppStatus::Processing => (&config.processing_text, config.processing_tooltip.clone()),
+        AppStatus::Disabled => (&config.disabled_text, config.disabled_tooltip.clone()),
+        AppStatus::Error => (
+            &config.error_text,
+            match error {
+                Some(message) => format!("{}\n{}", config.error_tooltip, message),
+                None => config.error_tooltip.clone(),
+            },
+        ),
+    };
+
     json!({
-        "text": if recording { &config.recording_text } else { &config.idle_text },
-        "class": if recording { "chezwizper-recording" } else { "chezwizper-idle" },
-        "tooltip": if recording {
-            &config.recording_tooltip
-        } else {
-            &config.idle_tooltip
-        }
+        "text": text,
+        "class": format!("chezwizper-{}", status.as_str()),
+        "tooltip": tooltip
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_authorized() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_authorized(&headers, "secret"));
+
+        headers.insert(header::AUTHORIZATION, "Bearer wrong".parse().unwrap());
+        assert!(!is_authorized(&headers, "secret"));
+
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert!(is_authorized(&headers, "secret"));
+    }
+
+    #[test]
+    fn test_event_name_matches_tag() {
+        let event = ApiEvent::TranscriptionComplete {
+            text: "hello".to_string(),
+        };
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["event"], event.name());
+        assert_eq!(value["text"], "hello");
+        assert_eq!(
+            serde_json::to_value(ApiEvent::RecordingStarted).unwrap()["event"],
+            ApiEvent::RecordingStarted.name()
+        );
+    }
+
+    #[test]
+    fn test_status_tracker_follows_events() {
+        let mut tracker = StatusTracker::default();
+        let timeout = Duration::from_secs(5);
+        assert_eq!(tracker.current(timeout), (AppStatus::Idle, None));
+
+        tracker.apply(&ApiEvent::RecordingStarted);
+        assert_eq!(tracker.current(timeout).0, AppStatus::Recording);
+        tracker.apply(&ApiEvent::RecordingPaused);
+        assert_eq!(tracker.current(timeout).0, AppStatus::Paused);
+        tracker.apply(&ApiEvent::RecordingResumed);
+        assert_eq!(tracker.current(timeout).0, AppStatus::Recording);
+        tracker.apply(&ApiEvent::Transcribing);
+        assert_eq!(tracker.current(timeout).0, AppStatus::Processing);
+
+        tracker.apply(&ApiEvent::Error {
+            message: "No speech detected".to_string(),
+            category: "no_speech",
+        });
+        assert_eq!(
+            tracker.current(timeout),
+            (AppStatus::Error, Some("No speech detected"))
+        );
+        // Expired errors read as idle
+        assert_eq!(tracker.current(Duration::ZERO), (AppStatus::Idle, None));
+    }
+
+    #[test]
+    fn test_waybar_response_classes() {
+        let config = WaybarConfig::default();
+
+        let processing = generate_waybar_response(AppStatus::Processing, None, &config);
+        assert_eq!(processing["class"], "chezwizper-processing");
+        assert_eq!(processing["text"], config.processing_text.as_str());
+
+        let error = generate_waybar_response(AppStatus::Error, Some("boom"), &config);
+        assert_eq!(error["class"], "chezwizper-error");
+        assert_eq!(error["tooltip"], "Transcription failed\nboom");
+    }
+
+    #[tokio::test]
+    async fn test_toggle_ignored_while_disabled() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let (events, mut event_rx) = broadcast::channel(4);
+        let server = ApiServer::new(
+            tx,
+            Arc::new(Mutex::new(false)),
+            Arc::new(Mutex::new(StatusTracker::default())),
+            Arc::new(Mutex::new(TranscriptionHistory::new(1))),
+            events,
+            &Config::default(),
+        );
+        let state = server.state;
+
+        let Json(response) = set_enabled(
+            State(state.clone()),
+            Json(EnabledRequest { enabled: false }),
+        )
+        .await;
+        assert_eq!(response["enabled"], false);
+
+        let Json(response) =
+            toggle_recording(State(state.clone()), Query(RecordingOverrides::default()))
+                .await
+                .unwrap();
+        assert_eq!(response["disabled"], true);
+        assert!(rx.try_recv().is_err());
+        assert!(matches!(event_rx.try_recv(), Ok(ApiEvent::Disabled)));
+        let Json(response) = recording_status(Query(HashMap::new()), State(state.clone())).await;
+        assert_eq!(response["status"], "disabled");
+
+        // Disabling mid-recording still lets the recording be stopped
+        *state.recording.lock().await = true;
+        let Json(response) =
+            toggle_recording(State(state.clone()), Query(RecordingOverrides::default()))
+                .await
+                .unwrap();
+        assert_eq!(response["success"], true);
+        assert!(rx.try_recv().is_ok());
+        *state.recording.lock().await = false;
+
+        let _ = set_enabled(State(state.clone()), Json(EnabledRequest { enabled: true })).await;
+        assert!(matches!(event_rx.try_recv(), Ok(ApiEvent::Enabled)));
+        let query = RecordingOverrides {
+            language: Some("es".to_string()),
+            model: Some("whisper-large-v3".to_string()),
+        };
+        let Json(response) = toggle_recording(State(state.clone()), Query(query))
+            .await
+            .unwrap();
+        assert_eq!(response["success"], true);
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(ApiCommand::ToggleRecording(RecordingOverrides {
+                language: Some(language),
+                model: Some(model),
+            })) if language == "es" && model == "whisper-large-v3"
+        ));
+
+        let query = RecordingOverrides {
+            language: Some("klingon".to_string()),
+            model: Some(" ".to_string()),
+        };
+        let _ = toggle_recording(State(state), Query(query)).await;
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(ApiCommand::ToggleRecording(RecordingOverrides {
+                language: None,
+                model: None
+            }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_token_guards_transcripts() {
+        // Serves the routes on a local port and returns its base URL
+        async fn serve(protect_status: bool) -> String {
+            let mut config = Config::default();
+            config.api.auth_token = Some("secret".to_string());
+            config.api.protect_status = protect_status;
+            let router = ApiServer::new(
+                mpsc::channel(1).0,
+                Arc::new(Mutex::new(false)),
+                Arc::new(Mutex::new(StatusTracker::default())),
+                Arc::new(Mutex::new(TranscriptionHistory::new(1))),
+                broadcast::channel(1).0,
+                &config,
+            )
+            .router();
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, router).await });
+            url
+        }
+        let client = reqwest::Client::new();
+        let get = |url: String, token: Option<&str>| {
+            let mut request = client.get(url);
+            if let Some(token) = token {
+                request = request.bearer_auth(token);
+            }
+            async move { request.send().await.unwrap().status().as_u16() }
+        };
+
+        let open = serve(false).await;
+        for path in ["/history", "/last", "/events"] {
+            assert_eq!(get(format!("{open}{path}"), None).await, 401, "{path}");
+        }
+        assert_eq!(get(format!("{open}/"), None).await, 200);
+        assert_eq!(get(format!("{open}/status"), None).await, 200);
+        // No transcription yet
+        assert_eq!(get(format!("{open}/last"), Some("secret")).await, 404);
+
+        let protected = serve(true).await;
+        for path in ["/", "/status", "/info"] {
+            assert_eq!(get(format!("{protected}{path}"), None).await, 401, "{path}");
+        }
+        assert_eq!(get(format!("{protected}/"), Some("secret")).await, 200);
+    }
+
+    #[test]
+    fn test_health_response_lists_failures() {
+        let (code, Json(body)) = health_response(&[]);
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(body["healthy"], true);
+
+        let checks = [
+            HealthCheck::new("provider", true, "Groq API"),
+            HealthCheck::new("text_injection", false, "wtype"),
+        ];
+        let (code, Json(body)) = health_response(&checks);
+        assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["failing"], json!(["text_injection"]));
+        assert_eq!(body["checks"][0]["detail"], "Groq API");
+    }
+}
@@ -1,65 +1,924 @@
 #![allow(clippy::arc_with_non_send_sync)]
 
+use crate::config::{AudioConfig, Config};
+use crate::error::{Categorize, ChezWizperError};
 use anyhow::{Context, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use hound::{WavSpec, WavWriter};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
-use tracing::{debug, error, info};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
 
 /// State of the audio recording session
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RecordingState {
     Idle,
     Recording,
+    /// The stream stays open but captured samples are dropped until resumed
+    Paused,
     Stopping,
 }
 
+/// Root-mean-square amplitude of a block of samples (0.0 - 1.0 for normalized audio)
+pub fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+
+    let sum: f32 = samples.iter().map(|s| s * s).sum();
+    (sum / samples.len() as f32).sqrt()
+}
+
+/// Sample rate of saved recordings, the rate Whisper models work at
+pub const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Peak amplitude used as the normalization target (~-1 dBFS)
+const NORMALIZE_TARGET_PEAK: f32 = 0.891;
+
+/// Recordings quieter than this peak are treated as silence and never amplified
+const NORMALIZE_MIN_PEAK: f32 = 0.01;
+
+/// Samples at or above this magnitude are counted as clipped
+const CLIP_LEVEL: f32 = 0.999;
+
+/// Fraction of clipped samples above which a recording is reported as clipping
+const CLIP_RATIO_WARN: f32 = 0.001;
+
+/// Largest absolute sample value
+pub fn peak(samples: &[f32]) -> f32 {
+    samples.iter().fold(0.0f32, |max, s| max.max(s.abs()))
+}
+
+/// Whether enough samples sit at full scale for the recording to sound clipped
+pub fn is_clipping(samples: &[f32]) -> bool {
+    if samples.is_empty() {
+        return false;
+    }
+
+    let clipped = samples.iter().filter(|s| s.abs() >= CLIP_LEVEL).count();
+    clipped as f32 / samples.len() as f32 > CLIP_RATIO_WARN
+}
+
+/// Length in milliseconds of `sample_count` interleaved samples
+fn duration_ms(sample_count: usize, channels: u16, sample_rate: u32) -> u64 {
+    let frames = sample_count as u64 / channels.max(1) as u64;
+    frames * 1000 / sample_rate.max(1) as u64
+}
+
+/// Average interleaved frames down to a single channel
+pub fn downmix(samples: Vec<f32>, channels: u16) -> Vec<f32> {
+    if channels <= 1 {
+        return samples;
+    }
+
+    samples
+        .chunks_exact(channels as usize)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// Linearly resample interleaved samples from `from_rate` to `to_rate`, keeping
+/// the channel layout. Speech has little energy above 8 kHz, so the aliasing a
+/// plain linear interpolation lets through doesn't affect transcription.
+pub fn resample(samples: Vec<f32>, channels: u16, from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || samples.is_empty() {
+        return samples;
+    }
+
+    let channels = channels.max(1) as usize;
+    let frames = samples.len() / channels;
+    let out_frames = (frames as u64 * to_rate as u64 / from_rate as u64) as usize;
+    let step = from_rate as f64 / to_rate as f64;
+
+    let mut out = Vec::with_capacity(out_frames * channels);
+    for frame in 0..out_frames {
+        let position = frame as f64 * step;
+        let index = position as usize;
+        let next = (index + 1).min(frames - 1);
+        let fraction = (position - index as f64) as f32;
+        for channel in 0..channels {
+            let a = samples[index * channels + channel];
+            let b = samples[next * channels + channel];
+            out.push(a + (b - a) * fraction);
+        }
+    }
+    out
+}
+
+/// Multiply samples by `gain`, clamping to full scale so a boost never wraps
+pub fn apply_gain(samples: &mut [f32], gain: f32) {
+    for sample in samples.iter_mut() {
+        *sample = (*sample * gain).clamp(-1.0, 1.0);
+    }
+}
+
+/// Length of the blocks the noise gate measures the level of
+const GATE_WINDOW_MS: u32 = 10;
+
+/// Silences stretches of a recording that stay below a level threshold, such
+/// as fan hum or keyboard noise between words
+#[derive(Debug, Clone, Copy)]
+pub struct NoiseGate {
+    /// RMS level (0.0 - 1.0) below which a block is gated
+    pub threshold: f32,
+    /// Time for the gate to fully open once the level rises above the threshold
+    pub attack_ms: u32,
+    /// Time for the gate to fully close once the level drops, so word endings fade out
+    pub release_ms: u32,
+}
+
+impl NoiseGate {
+    /// Gate interleaved samples in place, ramping the gain per frame so words aren't chopped
+    pub fn apply(&self, samples: &mut [f32], sample_rate: u32, channels: u16) {
+        let channels = channels.max(1) as usize;
+        let frames_per_ms = sample_rate as f32 / 1000.0;
+        let window = ((frames_per_ms * GATE_WINDOW_MS as f32) as usize).max(1) * channels;
+        let attack_step = 1.0 / (frames_per_ms * self.attack_ms as f32).max(1.0);
+        let release_step = 1.0 / (frames_per_ms * self.release_ms as f32).max(1.0);
+
+        let mut gain = 0.0f32;
+        for block in samples.chunks_mut(window) {
+            let open = rms(block) >= self.threshold;
+            for frame in block.chunks_mut(channels) {
+                gain = if open {
+                    (gain + attack_step).min(1.0)
+                } else {
+                    (gain - release_step).max(0.0)
+                };
+                for sample in frame {
+                    *sample *= gain;
+                }
+            }
+        }
+    }
+}
+
+/// Scale samples so the peak sits at ~-1 dBFS. Returns the gain applied,
+/// or `None` when the recording is essentially silent and was left untouched.
+pub fn normalize(samples: &mut [f32]) -> Option<f32> {
+    let peak = peak(samples);
+    if peak < NORMALIZE_MIN_PEAK {
+        return None;
+    }
+
+    let gain = NORMALIZE_TARGET_PEAK / peak;
+    for sample in samples.iter_mut() {
+        *sample *= gain;
+    }
+
+    Some(gain)
+}
+
+#[derive(Debug)]
+struct MonitorState {
+    threshold: f32,
+    last_sound: Option<Instant>,
+    limit_reached: bool,
+    clipping: bool,
+    paused: bool,
+}
+
+/// Input level of the latest captured block, for VU meters
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InputLevel {
+    pub rms: f32,
+    pub peak: f32,
+}
+
+/// Thread-safe handle tracking the active recording, used for silence detection,
+/// level metering and the maximum duration limit
+#[derive(Debug, Clone)]
+pub struct RecordingMonitor {
+    state: Arc<Mutex<MonitorState>>,
+    /// `f32` bits of the latest RMS and peak, written from the stream callback
+    level: Arc<[AtomicU32; 2]>,
+}
+
+impl RecordingMonitor {
+    fn new(threshold: f32) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MonitorState {
+                threshold,
+                last_sound: None,
+                limit_reached: false,
+                clipping: false,
+                paused: false,
+            })),
+            level: Arc::new([AtomicU32::new(0), AtomicU32::new(0)]),
+        }
+    }
+
+    fn set_threshold(&self, threshold: f32) {
+        self.state.lock().unwrap().threshold = threshold.clamp(0.0, 1.0);
+    }
+
+    fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.last_sound = None;
+        state.limit_reached = false;
+        state.clipping = false;
+        state.paused = false;
+        self.set_level(0.0, 0.0);
+    }
+
+    /// While paused, input is neither recorded nor counted as silence. On
+    /// resume the silence timer restarts, so the pause itself never stops a
+    /// recording.
+    fn set_paused(&self, paused: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.paused = paused;
+        if paused {
+            self.set_level(0.0, 0.0);
+        }
+        if !paused && state.last_sound.is_some() {
+            state.last_sound = Some(Instant::now());
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.state.lock().unwrap().paused
+    }
+
+    fn set_clipping(&self) {
+        self.state.lock().unwrap().clipping = true;
+    }
+
+    /// Whether the last saved recording showed signs of clipping
+    pub fn clipping_detected(&self) -> bool {
+        self.state.lock().unwrap().clipping
+    }
+
+    fn set_limit_reached(&self) {
+        self.state.lock().unwrap().limit_reached = true;
+    }
+
+    /// Whether the current recording hit the maximum duration and stopped capturing
+    pub fn limit_reached(&self) -> bool {
+        self.state.lock().unwrap().limit_reached
+    }
+
+    fn set_level(&self, rms: f32, peak: f32) {
+        self.level[0].store(rms.to_bits(), Ordering::Relaxed);
+        self.level[1].store(peak.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Level of the most recent block, zero before recording starts and while paused
+    pub fn level(&self) -> InputLevel {
+        InputLevel {
+            rms: f32::from_bits(self.level[0].load(Ordering::Relaxed)),
+            peak: f32::from_bits(self.level[1].load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Feed a block of captured samples
+    fn update(&self, samples: &[f32]) {
+        let level = rms(samples);
+        self.set_level(level, peak(samples));
+        let mut state = self.state.lock().unwrap();
+        if level > state.threshold {
+            state.last_sound = Some(Instant::now());
+        }
+    }
+
+    /// Time since the input was last above the silence threshold.
+    /// Returns `None` until sound has been heard, so leading silence never counts.
+    pub fn silence_duration(&self) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        if state.paused {
+            return None;
+        }
+        state.last_sound.map(|at| at.elapsed())
+    }
+}
+
+/// Write samples to a WAV file as 32-bit float or 16-bit PCM
+pub fn write_wav(
+    path: &Path,
+    samples: &[f32],
+    channels: u16,
+    sample_rate: u32,
+    bit_depth: u16,
+) -> Result<()> {
+    let sample_format = match bit_depth {
+        16 => hound::SampleFormat::Int,
+        32 => hound::SampleFormat::Float,
+        other => return Err(anyhow::anyhow!("Unsupported WAV bit depth: {}", other)),
+    };
+
+    let spec = WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: bit_depth,
+        sample_format,
+    };
+
+    let mut writer = WavWriter::create(path, spec).context("Failed to create WAV file")?;
+    for &sample in samples {
+        if bit_depth == 16 {
+            let pcm = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            writer.write_sample(pcm)?;
+        } else {
+            writer.write_sample(sample)?;
+        }
+    }
+    writer.finalize()?;
+
+    Ok(())
+}
+
+/// Length of a WAV file in seconds
+pub fn wav_duration_secs(path: &Path) -> Result<f64> {
+    let reader = hound::WavReader::open(path).context("Failed to open WAV file")?;
+    Ok(reader.duration() as f64 / reader.spec().sample_rate as f64)
+}
+
+/// Temporary recording on disk, removed when dropped if deletion is enabled.
+/// Keeps audio files from piling up when any step after recording fails.
+#[derive(Debug)]
+pub struct TempAudioFile {
+    path: PathBuf,
+    delete_on_drop: bool,
+}
+
+impl TempAudioFile {
+    pub fn new(path: PathBuf, delete_on_drop: bool) -> Self {
+        Self {
+            path,
+            delete_on_drop,
+        }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Move the recording to `dest` and keep it there. A numeric suffix is added
+    /// when `dest` already exists. Returns the final path.
+    pub fn persist_to(mut self, dest: &Path) -> Result<PathBuf> {
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {parent:?}"))?;
+        }
+
+        let mut target = dest.to_path_buf();
+        let stem = dest.file_stem().unwrap_or_default().to_string_lossy();
+        let mut n = 2;
+        while target.exists() {
+            target = dest.with_file_name(format!("{stem}-{n}.wav"));
+            n += 1;
+        }
+
+        // rename fails across filesystems, e.g. from a tmpfs /tmp to $HOME
+        if std::fs::rename(&self.path, &target).is_err() {
+            std::fs::copy(&self.path, &target)
+                .with_context(|| format!("Failed to copy recording to {target:?}"))?;
+            let _ = std::fs::remove_file(&self.path);
+        }
+
+        self.delete_on_drop = false;
+        self.path = target.clone();
+        Ok(target)
+    }
+}
+
+impl Drop for TempAudioFile {
+    fn drop(&mut self) {
+        if self.delete_on_drop && self.path.exists() {
+            match std::fs::remove_file(&self.path) {
+                Ok(_) => debug!("Removed temp audio file {:?}", self.path),
+                Err(e) => warn!("Failed to remove temp audio file {:?}: {}", self.path, e),
+            }
+        }
+    }
+}
+
+/// Length of the blocks compared when looking for a quiet place to split a recording
+const SPLIT_WINDOW_MS: u64 = 100;
+
+/// Room left for the WAV header when sizing chunks (hound may write an extended one)
+const WAV_HEADER_MARGIN: u64 = 1024;
+
+/// Piece of a longer recording, written next to it and removed when dropped
+#[derive(Debug)]
+pub struct AudioChunk {
+    pub file: TempAudioFile,
+    /// Offset of the chunk from the start of the original recording
+    pub start_secs: f64,
+}
+
+/// Format recordings are converted to before uploading to an API provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadFormat {
+    Wav,
+    Mp3,
+    /// Opus in an Ogg container
+    Opus,
+}
+
+impl UploadFormat {
+    pub fn parse(format: &str) -> Result<Self> {
+        match format {
+            "wav" => Ok(UploadFormat::Wav),
+            "mp3" => Ok(UploadFormat::Mp3),
+            "opus" => Ok(UploadFormat::Opus),
+            other => Err(anyhow::anyhow!(
+                "Unknown upload_format '{}', expected \"wav\", \"mp3\" or \"opus\"",
+                other
+            )),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            UploadFormat::Wav => "wav",
+            UploadFormat::Mp3 => "mp3",
+            UploadFormat::Opus => "ogg",
+        }
+    }
+
+    /// ffmpeg codec arguments; bitrates are plenty for 16 kHz speech
+    fn codec_args(self) -> &'static [&'static str] {
+        match self {
+            UploadFormat::Wav => &["-c:a", "pcm_s16le"],
+            UploadFormat::Mp3 => &["-c:a", "libmp3lame", "-b:a", "48k"],
+            UploadFormat::Opus => &["-c:a", "libopus", "-b:a", "24k"],
+        }
+    }
+}
+
+/// Convert a WAV recording with ffmpeg, writing the result next to it. The
+/// returned file is removed when dropped.
+pub async fn encode(path: &Path, format: UploadFormat) -> Result<TempAudioFile> {
+    let output_path = path.with_extension(format.extension());
+    let output = TempAudioFile::new(output_path.clone(), true);
+
+    let result = tokio::process::Command::new("ffmpeg")
+        .args(["-nostdin", "-y", "-loglevel", "error", "-i"])
+        .arg(path)
+        .args(format.codec_args())
+        .arg(&output_path)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await
+        .context("Failed to run ffmpeg, is it installed?")?;
+
+    if !result.status.success() {
+        return Err(anyhow::anyhow!(
+            "ffmpeg failed to encode {}: {}",
+            format.extension(),
+            String::from_utf8_lossy(&result.stderr).trim()
+        ));
+    }
+
+    Ok(output)
+}
+
+/// Split a WAV file into chunks no longer than `max_secs` and no larger than `max_bytes`.
+/// Each cut is made in the quietest part of the last fifth of a chunk so words stay whole.
+pub fn split_wav(path: &Path, max_secs: u64, max_bytes: u64) -> Result<Vec<AudioChunk>> {
+    let mut reader = hound::WavReader::open(path).context("Failed to open WAV file")?;
+    let spec = reader.spec();
+    let frame_bytes = spec.channels as u64 * (spec.bits_per_sample as u64 / 8);
+    let max_frames = (max_secs * spec.sample_rate as u64)
+        .min(max_bytes.saturating_sub(WAV_HEADER_MARGIN) / frame_bytes.max(1))
+        as usize;
+    if max_frames == 0 {
+        anyhow::bail!("Chunk limits of {max_secs}s and {max_bytes} bytes leave no room for audio");
+    }
+
+    match spec.sample_format {
+        hound::SampleFormat::Float => {
+            let samples = reader
+                .samples::<f32>()
+                .collect::<Result<Vec<_>, _>>()
+                .context("Failed to read WAV samples")?;
+            write_chunks(path, spec, &samples, &samples, max_frames)
+        }
+        hound::SampleFormat::Int => {
+            let samples = reader
+                .samples::<i32>()
+                .collect::<Result<Vec<_>, _>>()
+                .context("Failed to read WAV samples")?;
+            let scale = (1u32 << (spec.bits_per_sample - 1)) as f32;
+            let levels: Vec<f32> = samples.iter().map(|&s| s as f32 / scale).collect();
+            write_chunks(path, spec, &samples, &levels, max_frames)
+        }
+    }
+}
+
+fn write_chunks<S: hound::Sample + Copy>(
+    path: &Path,
+    spec: WavSpec,
+    samples: &[S],
+    levels: &[f32],
+    max_frames: usize,
+) -> Result<Vec<AudioChunk>> {
+    let channels = spec.channels.max(1) as usize;
+    let total_frames = samples.len() / channels;
+    let window = (spec.sample_rate as u64 * SPLIT_WINDOW_MS / 1000).max(1) as usize;
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    let mut bounds = split_points(levels, channels, max_frames, window);
+    bounds.push(total_frames);
+
+    let mut chunks = Vec::with_capacity(bounds.len());
+    let mut start = 0;
+    for (index, end) in bounds.into_iter().enumerate() {
+        let chunk_path = path.with_file_name(format!("{stem}_part{}.wav", index + 1));
+        // Wrapped before writing so a failed write doesn't leave the file behind
+        let file = TempAudioFile::new(chunk_path, true);
+
+        let mut writer =
+            WavWriter::create(file.path(), spec).context("Failed to create WAV chunk")?;
+        for &sample in &samples[start * channels..end * channels] {
+            writer.write_sample(sample)?;
+        }
+        writer.finalize()?;
+
+        chunks.push(AudioChunk {
+            file,
+            start_secs: start as f64 / spec.sample_rate as f64,
+        });
+        start = end;
+    }
+
+    Ok(chunks)
+}
+
+/// Frames to cut at so no chunk exceeds `max_frames`, each placed in the middle of the
+/// quietest `window` in the last fifth of its chunk
+fn split_points(levels: &[f32], channels: usize, max_frames: usize, window: usize) -> Vec<usize> {
+    let total_frames = levels.len() / channels;
+    let mut cuts = Vec::new();
+    let mut start = 0;
+
+    while total_frames - start > max_frames {
+        let end = start + max_frames;
+        let mut cut = end;
+        let mut quietest = f32::MAX;
+
+        let mut at = end - max_frames / 5;
+        while at + window <= end {
+            let level = rms(&levels[at * channels..(at + window) * channels]);
+            if level < quietest {
+                quietest = level;
+                cut = at + window / 2;
+            }
+            at += window;
+        }
+
+        cuts.push(cut);
+        start = cut;
+    }
+
+    cuts
+}
+
+/// Words of the transcription used to name an archived recording
+const ARCHIVE_SLUG_WORDS: usize = 6;
+
+/// Longest transcription part of an archived recording's file name
+const ARCHIVE_SLUG_MAX_CHARS: usize = 48;
+
+/// File name for an archived recording: the UTC time plus the first few words of
+/// its transcription, e.g. `2026-10-16_14-30-22_send-the-report-to-anna.wav`
+pub fn archive_file_name(unix_secs: u64, transcript: Option<&str>) -> String {
+    let (year, month, day) = civil_date(unix_secs / 86400);
+    let time = unix_secs % 86400;
+    let stamp = format!(
+        "{year:04}-{month:02}-{day:02}_{:02}-{:02}-{:02}",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    );
+
+    let slug = transcript.map(filename_slug).unwrap_or_default();
+    if slug.is_empty() {
+        format!("{stamp}.wav")
+    } else {
+        format!("{stamp}_{slug}.wav")
+    }
+}
+
+/// Lowercase alphanumeric words joined by dashes, safe in any file name
+fn filename_slug(text: &str) -> String {
+    let mut slug = String::new();
+    let words = text
+        .split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .take(ARCHIVE_SLUG_WORDS);
+
+    for word in words {
+        let sep = usize::from(!slug.is_empty());
+        if slug.chars().count() + sep + word.chars().count() > ARCHIVE_SLUG_MAX_CHARS {
+            // Always keep something from the first word
+            if slug.is_empty() {
+                slug = word.chars().take(ARCHIVE_SLUG_MAX_CHARS).collect();
+            }
+            break;
+        }
+        if sep == 1 {
+            slug.push('-');
+        }
+        slug.push_str(&word);
+    }
+
+    slug
+}
+
+/// (year, month, day) for a count of days since 1970-01-01
+fn civil_date(days: u64) -> (i64, u64, u64) {
+    let days = days as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u64;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u64;
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// Description of an available audio input device
+#[derive(Debug, Clone)]
+pub struct InputDeviceInfo {
+    pub name: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub is_default: bool,
+}
+
 /// Manages the lifecycle of audio streams and recordings
 pub struct AudioStreamManager {
-    device: cpal::Device,
+    /// Configured device name, resolved again at the start of every recording
+    device_name: String,
+    /// Last device that resolved, used when resolving fails
+    device: Mutex<cpal::Device>,
     config: cpal::StreamConfig,
     samples: Arc<Mutex<Vec<f32>>>,
     active_stream: Arc<Mutex<Option<cpal::Stream>>>,
     state: Arc<Mutex<RecordingState>>,
+    monitor: RecordingMonitor,
+    max_samples: usize,
+    min_duration_ms: u64,
+    normalize: bool,
+    gain: f32,
+    noise_gate: Option<NoiseGate>,
+    bit_depth: u16,
 }
 
 impl AudioStreamManager {
-    /// Create a new audio stream manager
-    pub fn new() -> Result<Self> {
+    /// Create a new audio stream manager using the configured device, sample rate and channels
+    pub fn new(audio_config: &AudioConfig) -> Result<Self, ChezWizperError> {
+        Self::with_device_name(&audio_config.device, audio_config)
+    }
+
+    /// Create a new audio stream manager with the `[audio]` settings and the
+    /// recording limits from `[behavior]`
+    pub fn from_config(config: &Config) -> Result<Self, ChezWizperError> {
+        Ok(Self::new(&config.audio)?
+            .with_silence_threshold(config.behavior.silence_threshold)
+            .with_max_duration(config.behavior.max_recording_secs)
+            .with_min_duration(config.behavior.min_recording_ms))
+    }
+
+    /// Create a new audio stream manager on the input device with the given name.
+    /// `"default"` (or an unknown name) selects the system default input device.
+    pub fn with_device_name(
+        name: &str,
+        audio_config: &AudioConfig,
+    ) -> Result<Self, ChezWizperError> {
+        if !matches!(audio_config.wav_bit_depth, 16 | 32) {
+            return Err(ChezWizperError::Recording(format!(
+                "Unsupported wav_bit_depth {}; use 16 (PCM) or 32 (float)",
+                audio_config.wav_bit_depth
+            )));
+        }
+
         let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .context("No input device available")?;
+        let device =
+            Self::find_input_device(&host, name).categorize(ChezWizperError::AudioDevice)?;
 
-        info!("Using audio device: {}", device.name()?);
+        info!(
+            "Using audio device: {}",
+            device.name().unwrap_or_else(|_| "unknown".to_string())
+        );
 
-        let _config = device.default_input_config()?;
-        let config = cpal::StreamConfig {
-            channels: 1,
-            sample_rate: cpal::SampleRate(16000), // Whisper optimal
-            buffer_size: cpal::BufferSize::Default,
-        };
+        let config = Self::negotiate_channels(
+            &device,
+            cpal::StreamConfig {
+                channels: audio_config.channels,
+                sample_rate: cpal::SampleRate(audio_config.sample_rate),
+                buffer_size: cpal::BufferSize::Default,
+            },
+        )
+        .categorize(ChezWizperError::AudioDevice)?;
+
+        info!(
+            "Recording at {} Hz, {} channel(s)",
+            audio_config.sample_rate, config.channels
+        );
 
         Ok(Self {
-            device,
+            device_name: name.to_string(),
+            device: Mutex::new(device),
             config,
             samples: Arc::new(Mutex::new(Vec::new())),
             active_stream: Arc::new(Mutex::new(None)),
             state: Arc::new(Mutex::new(RecordingState::Idle)),
+            monitor: RecordingMonitor::new(0.01),
+            max_samples: 0,
+            min_duration_ms: 0,
+            normalize: audio_config.normalize_audio,
+            gain: audio_config.gain,
+            noise_gate: (audio_config.noise_gate_threshold > 0.0).then_some(NoiseGate {
+                threshold: audio_config.noise_gate_threshold,
+                attack_ms: audio_config.noise_gate_attack_ms,
+                release_ms: audio_config.noise_gate_release_ms,
+            }),
+            bit_depth: audio_config.wav_bit_depth,
         })
     }
 
+    /// Stop capturing once a recording reaches `secs` seconds (0 = unlimited)
+    pub fn with_max_duration(mut self, secs: u64) -> Self {
+        self.max_samples =
+            self.config.sample_rate.0 as usize * self.config.channels as usize * secs as usize;
+        self
+    }
+
+    /// Refuse to save recordings shorter than `ms` milliseconds (0 = no minimum)
+    pub fn with_min_duration(mut self, ms: u64) -> Self {
+        self.min_duration_ms = ms;
+        self
+    }
+
+    /// Set the RMS level (0.0 - 1.0) below which input is treated as silence
+    pub fn with_silence_threshold(self, threshold: f32) -> Self {
+        self.monitor.set_threshold(threshold);
+        self
+    }
+
+    /// Handle for observing input levels from other tasks
+    pub fn monitor(&self) -> RecordingMonitor {
+        self.monitor.clone()
+    }
+
+    /// Enumerate input devices with their default sample rate and channel count
+    pub fn list_input_devices() -> Result<Vec<InputDeviceInfo>, ChezWizperError> {
+        let host = cpal::default_host();
+        let default_name = host
+            .default_input_device()
+            .and_then(|device| device.name().ok());
+
+        let mut devices = Vec::new();
+        for device in host
+            .input_devices()
+            .context("Failed to enumerate input devices")
+            .categorize(ChezWizperError::AudioDevice)?
+        {
+            let Ok(name) = device.name() else {
+                continue;
+            };
+
+            let (sample_rate, channels) = match device.default_input_config() {
+                Ok(config) => (config.sample_rate().0, config.channels()),
+                Err(e) => {
+                    debug!("Skipping device {}: {}", name, e);
+                    continue;
+                }
+            };
+
+            devices.push(InputDeviceInfo {
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                name,
+                sample_rate,
+                channels,
+            });
+        }
+
+        Ok(devices)
+    }
+
+    /// Look up an input device by name, falling back to the default device
+    fn find_input_device(host: &cpal::Host, name: &str) -> Result<cpal::Device> {
+        if name != "default" {
+            let found = host
+                .input_devices()
+                .context("Failed to enumerate input devices")?
+                .find(|device| device.name().map(|n| n == name).unwrap_or(false));
+
+            match found {
+                Some(device) => return Ok(device),
+                None => warn!(
+                    "Audio device '{}' not found, falling back to default input device",
+                    name
+                ),
+            }
+        }
+
+        host.default_input_device()
+            .context("No input device available")
+    }
+
+    /// Resolve the configured device again so changes to the system default
+    /// (e.g. in pavucontrol) apply to the next recording without a restart
+    fn current_device(&self) -> cpal::Device {
+        let mut cached = self.device.lock().unwrap();
+        let resolved =
+            Self::find_input_device(&cpal::default_host(), &self.device_name).and_then(|device| {
+                Self::check_supported(&device, &self.config)?;
+                Ok(device)
+            });
+
+        match resolved {
+            Ok(device) => *cached = device,
+            Err(e) => warn!(
+                "Failed to resolve audio device, reusing the previous one: {:#}",
+                e
+            ),
+        }
+        cached.clone()
+    }
+
+    /// Use the requested channel count when the device supports it, otherwise
+    /// the device's own default (e.g. stereo-only interfaces). Recordings are
+    /// downmixed to mono before saving either way.
+    fn negotiate_channels(
+        device: &cpal::Device,
+        config: cpal::StreamConfig,
+    ) -> Result<cpal::StreamConfig> {
+        let err = match Self::check_supported(device, &config) {
+            Ok(()) => return Ok(config),
+            Err(e) => e,
+        };
+
+        let default_channels = device.default_input_config()?.channels();
+        if default_channels == config.channels {
+            return Err(err);
+        }
+
+        let fallback = cpal::StreamConfig {
+            channels: default_channels,
+            ..config
+        };
+        if Self::check_supported(device, &fallback).is_err() {
+            return Err(err);
+        }
+        warn!(
+            "Audio device can't capture {} channel(s), capturing {} and downmixing to mono",
+            config.channels, default_channels
+        );
+        Ok(fallback)
+    }
+
+    /// Ensure the device can capture at the requested sample rate and channel count
+    fn check_supported(device: &cpal::Device, config: &cpal::StreamConfig) -> Result<()> {
+        let rate = config.sample_rate;
+        let mut supported = device
+            .supported_input_configs()
+            .context("Failed to query supported input configs")?;
+
+        if supported.any(|range| {
+            range.channels() == config.channels
+                && range.min_sample_rate() <= rate
+                && range.max_sample_rate() >= rate
+        }) {
+            return Ok(());
+        }
+
+        let default = device.default_input_config()?;
+        Err(anyhow::anyhow!(
+            "Audio device does not support {} Hz with {} channel(s); its default is {} Hz with {} channel(s). Update [audio] sample_rate/channels in your config",
+            config.sample_rate.0,
+            config.channels,
+            default.sample_rate().0,
+            default.channels()
+        ))
+    }
+
     /// Start recording audio, properly managing stream lifecycle
-    pub async fn start_recording(&self) -> Result<()> {
+    pub async fn start_recording(&self) -> Result<(), ChezWizperError> {
         let mut state = self.state.lock().unwrap();
 
         match *state {
-            RecordingState::Recording => {
-                return Err(anyhow::anyhow!("Recording already in progress"));
+            RecordingState::Recording | RecordingState::Paused => {
+                return Err(ChezWizperError::Recording(
+                    "Recording already in progress".to_string(),
+                ));
             }
             RecordingState::Stopping => {
-                return Err(anyhow::anyhow!("Previous recording still stopping"));
+                return Err(ChezWizperError::Recording(
+                    "Previous recording still stopping".to_string(),
+                ));
             }
             RecordingState::Idle => {}
         }
@@ -73,24 +932,58 @@ impl AudioStreamManager {
             samples.clear();
             samples.shrink_to_fit(); // Free memory from previous recordings
         }
+        self.monitor.reset();
 
-        debug!("Creating new audio stream");
+        let device = self.current_device();
+        info!(
+            "Recording from audio device: {}",
+            device.name().unwrap_or_else(|_| "unknown".to_string())
+        );
 
         let samples_clone = self.samples.clone();
+        let monitor = self.monitor.clone();
+        let max_samples = self.max_samples;
+        let gain = self.gain;
         let err_fn = |err| error!("Audio stream error: {}", err);
 
-        let stream = self.device.build_input_stream(
-            &self.config,
-            move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                if let Ok(mut samples) = samples_clone.lock() {
-                    samples.extend_from_slice(data);
-                }
-            },
-            err_fn,
-            None,
-        )?;
+        let stream = device
+            .build_input_stream(
+                &self.config,
+                move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                    if monitor.is_paused() {
+                        return;
+                    }
+                    // Boosted before metering so silence detection sees the same level
+                    let boosted;
+                    let data = if gain == 1.0 {
+                        data
+                    } else {
+                        let mut samples = data.to_vec();
+                        apply_gain(&mut samples, gain);
+                        boosted = samples;
+                        &boosted
+                    };
+                    if let Ok(mut samples) = samples_clone.lock() {
+                        if max_samples > 0 && samples.len() + data.len() > max_samples {
+                            let remaining = max_samples.saturating_sub(samples.len());
+                            samples.extend_from_slice(&data[..remaining]);
+                            monitor.set_limit_reached();
+                            return;
+                        }
+                        samples.extend_from_slice(data);
+                    }
+                    monitor.update(data);
+                },
+                err_fn,
+                None,
+            )
+            .map_err(|e| {
+                ChezWizperError::AudioDevice(format!("Failed to open input stream: {e}"))
+            })?;
 
-        stream.play()?;
+        stream.play().map_err(|e| {
+            ChezWizperError::AudioDevice(format!("Failed to start input stream: {e}"))
+        })?;
         info!("Started audio recording");
 
         // Store stream for proper cleanup
@@ -100,18 +993,55 @@ impl AudioStreamManager {
         Ok(())
     }
 
+    /// Stop capturing without ending the recording; `resume_recording` continues it
+    pub async fn pause_recording(&self) -> Result<(), ChezWizperError> {
+        let mut state = self.state.lock().unwrap();
+        if *state != RecordingState::Recording {
+            return Err(ChezWizperError::Recording(
+                "No active recording to pause".to_string(),
+            ));
+        }
+
+        self.monitor.set_paused(true);
+        *state = RecordingState::Paused;
+        info!(
+            "Paused recording, {} samples so far",
+            self.samples.lock().unwrap().len()
+        );
+        Ok(())
+    }
+
+    /// Continue a paused recording, appending to the audio captured so far
+    pub async fn resume_recording(&self) -> Result<(), ChezWizperError> {
+        let mut state = self.state.lock().unwrap();
+        if *state != RecordingState::Paused {
+            return Err(ChezWizperError::Recording(
+                "Recording is not paused".to_string(),
+            ));
+        }
+
+        self.monitor.set_paused(false);
+        *state = RecordingState::Recording;
+        info!("Resumed recording");
+        Ok(())
+    }
+
     /// Stop recording and save audio to file
-    pub async fn stop_recording(&self, output_path: PathBuf) -> Result<PathBuf> {
+    pub async fn stop_recording(&self, output_path: PathBuf) -> Result<PathBuf, ChezWizperError> {
         let mut state = self.state.lock().unwrap();
 
         match *state {
             RecordingState::Idle => {
-                return Err(anyhow::anyhow!("No recording in progress"));
+                return Err(ChezWizperError::Recording(
+                    "No recording in progress".to_string(),
+                ));
             }
             RecordingState::Stopping => {
-                return Err(anyhow::anyhow!("Recording already stopping"));
+                return Err(ChezWizperError::Recording(
+                    "Recording already stopping".to_string(),
+                ));
             }
-            RecordingState::Recording => {}
+            RecordingState::Recording | RecordingState::Paused => {}
         }
 
         *state = RecordingState::Stopping;
@@ -126,26 +1056,66 @@ impl AudioStreamManager {
             samples_guard.clone()
         };
 
+        // Accidental taps aren't worth transcribing
+        let duration = duration_ms(
+            samples.len(),
+            self.config.channels,
+            self.config.sample_rate.0,
+        );
+        if duration < self.min_duration_ms {
+            self.samples.lock().unwrap().clear();
+            *self.state.lock().unwrap() = RecordingState::Idle;
+            return Err(ChezWizperError::TooShort(duration));
+        }
+
         if samples.is_empty() {
             *self.state.lock().unwrap() = RecordingState::Idle;
-            return Err(anyhow::anyhow!("No audio samples recorded"));
+            return Err(ChezWizperError::Recording(
+                "No audio samples recorded".to_string(),
+            ));
         }
 
         info!("Stopping recording, {} samples captured", samples.len());
 
-        // Write WAV file
-        let spec = WavSpec {
-            channels: 1,
-            sample_rate: 16000,
-            bits_per_sample: 32,
-            sample_format: hound::SampleFormat::Float,
-        };
+        if is_clipping(&samples) {
+            warn!("Recording appears clipped, consider lowering your input volume");
+            self.monitor.set_clipping();
+        }
 
-        let mut writer = WavWriter::create(&output_path, spec)?;
-        for sample in samples {
-            writer.write_sample(sample)?;
+        // Whisper wants 16 kHz mono whatever the device captured
+        let samples = downmix(samples, self.config.channels);
+        let capture_rate = self.config.sample_rate.0;
+        if capture_rate != WHISPER_SAMPLE_RATE {
+            debug!(
+                "Resampling recording from {} Hz to {} Hz",
+                capture_rate, WHISPER_SAMPLE_RATE
+            );
+        }
+        let mut samples = resample(samples, 1, capture_rate, WHISPER_SAMPLE_RATE);
+
+        // Gated first so normalization doesn't also boost the background noise
+        if let Some(gate) = &self.noise_gate {
+            gate.apply(&mut samples, WHISPER_SAMPLE_RATE, 1);
+        }
+
+        if self.normalize {
+            match normalize(&mut samples) {
+                Some(gain) => debug!("Normalized recording with gain {:.2}", gain),
+                None => debug!("Recording is near silent, skipping normalization"),
+            }
+        }
+
+        if let Err(e) = write_wav(
+            &output_path,
+            &samples,
+            1,
+            WHISPER_SAMPLE_RATE,
+            self.bit_depth,
+        ) {
+            self.samples.lock().unwrap().clear();
+            *self.state.lock().unwrap() = RecordingState::Idle;
+            return Err(e).categorize(ChezWizperError::Recording);
         }
-        writer.finalize()?;
 
         // Clear samples and reset state
         {
@@ -160,6 +1130,29 @@ impl AudioStreamManager {
         Ok(output_path)
     }
 
+    /// Stop recording and discard the captured audio without writing a file
+    pub async fn cancel_recording(&self) -> Result<(), ChezWizperError> {
+        let mut state = self.state.lock().unwrap();
+
+        if !matches!(*state, RecordingState::Recording | RecordingState::Paused) {
+            return Err(ChezWizperError::Recording(
+                "No recording in progress".to_string(),
+            ));
+        }
+
+        self.cleanup_stream();
+
+        {
+            let mut samples = self.samples.lock().unwrap();
+            info!("Cancelling recording, discarding {} samples", samples.len());
+            samples.clear();
+            samples.shrink_to_fit();
+        }
+
+        *state = RecordingState::Idle;
+        Ok(())
+    }
+
     /// Cleanup any active stream
     fn cleanup_stream(&self) {
         let mut active_stream = self.active_stream.lock().unwrap();
@@ -174,6 +1167,13 @@ impl AudioStreamManager {
 impl Drop for AudioStreamManager {
     fn drop(&mut self) {
         debug!("Dropping AudioStreamManager, cleaning up resources");
+        // Shutdown stops and saves recordings before dropping the manager, so
+        // anything still recording here is discarded rather than half-written
+        if let Ok(state) = self.state.lock() {
+            if matches!(*state, RecordingState::Recording | RecordingState::Paused) {
+                warn!("AudioStreamManager dropped mid-recording, discarding unsaved audio");
+            }
+        }
         self.cleanup_stream();
     }
 }
@@ -189,6 +1189,286 @@ mod tests {
             || std::env::var("TRAVIS").is_ok()
     }
 
+    #[test]
+    fn test_rms() {
+        assert_eq!(rms(&[]), 0.0);
+        assert_eq!(rms(&[0.5, -0.5, 0.5, -0.5]), 0.5);
+    }
+
+    #[test]
+    fn test_clipping_detection() {
+        let mut samples = vec![0.2; 1000];
+        assert!(!is_clipping(&samples));
+
+        samples[..10].fill(1.0);
+        assert!(is_clipping(&samples));
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut samples = vec![0.1, -0.2, 0.05];
+        assert!(normalize(&mut samples).is_some());
+        assert!((peak(&samples) - NORMALIZE_TARGET_PEAK).abs() < 1e-6);
+
+        let mut silent = vec![0.001, -0.002];
+        assert!(normalize(&mut silent).is_none());
+        assert_eq!(silent, vec![0.001, -0.002]);
+    }
+
+    #[test]
+    fn test_noise_gate_silences_hum_between_words() {
+        let rate = 16000;
+        let hum = |n: usize| (0..n).map(|i| 0.005 * (i as f32 * 0.3).sin());
+        let voice = |n: usize| (0..n).map(|i| 0.5 * (i as f32 * 0.05).sin());
+        let mut samples: Vec<f32> = hum(8000).chain(voice(3200)).chain(hum(8000)).collect();
+
+        let gate = NoiseGate {
+            threshold: 0.02,
+            attack_ms: 5,
+            release_ms: 50,
+        };
+        gate.apply(&mut samples, rate, 1);
+
+        // Leading hum is gone and the voice passes untouched once the gate is open
+        assert_eq!(peak(&samples[..7900]), 0.0);
+        let original: Vec<f32> = voice(3200).collect();
+        assert_eq!(&samples[8400..11200], &original[400..3200]);
+        // The tail fades out over the release time, then the hum is gone again
+        assert!(peak(&samples[11200..11400]) > 0.0);
+        assert_eq!(peak(&samples[12400..]), 0.0);
+    }
+
+    #[test]
+    fn test_downmix_averages_channels() {
+        let stereo = vec![0.5, 0.1, -0.2, 0.4, 1.0, 0.0];
+        assert_eq!(downmix(stereo, 2), vec![0.3, 0.1, 0.5]);
+        assert_eq!(downmix(vec![0.1, 0.2], 1), vec![0.1, 0.2]);
+    }
+
+    #[test]
+    fn test_resample_sample_counts() {
+        // One second of 48 kHz stereo becomes one second of 16 kHz stereo
+        let stereo = vec![0.25; 48000 * 2];
+        let out = resample(stereo, 2, 48000, WHISPER_SAMPLE_RATE);
+        assert_eq!(out.len(), 16000 * 2);
+        assert!(out.iter().all(|&s| (s - 0.25).abs() < 1e-6));
+
+        assert_eq!(resample(vec![0.0; 44100], 1, 44100, 16000).len(), 16000);
+        assert_eq!(resample(vec![0.0; 8000], 1, 8000, 16000).len(), 16000);
+        assert_eq!(resample(vec![0.1, 0.2], 1, 16000, 16000), vec![0.1, 0.2]);
+    }
+
+    #[test]
+    fn test_resample_interpolates() {
+        let out = resample(vec![0.0, 1.0], 1, 8000, 16000);
+        assert_eq!(out, vec![0.0, 0.5, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_apply_gain_clamps() {
+        let mut samples = vec![0.1, -0.2, 0.6, -0.9];
+        apply_gain(&mut samples, 2.0);
+        assert_eq!(samples, vec![0.2, -0.4, 1.0, -1.0]);
+    }
+
+    #[test]
+    fn test_write_wav_16_bit() {
+        let path = std::env::temp_dir().join("chezwizper_test_16bit.wav");
+        let samples = [0.0, 0.5, -0.5, 1.0, -1.5];
+
+        write_wav(&path, &samples, 1, 16000, 16).unwrap();
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        let spec = reader.spec();
+        assert_eq!(spec.bits_per_sample, 16);
+        assert_eq!(spec.sample_format, hound::SampleFormat::Int);
+        assert_eq!(spec.sample_rate, 16000);
+        assert_eq!(spec.channels, 1);
+
+        let read: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(read, vec![0, 16383, -16383, i16::MAX, -i16::MAX]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_write_wav_32_bit_float() {
+        let path = std::env::temp_dir().join("chezwizper_test_32bit.wav");
+        let samples = [0.0, 0.25, -0.75];
+
+        write_wav(&path, &samples, 1, 48000, 32).unwrap();
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().sample_format, hound::SampleFormat::Float);
+        let read: Vec<f32> = reader.samples::<f32>().map(|s| s.unwrap()).collect();
+        assert_eq!(read, samples);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_temp_audio_file_removed_on_error_path() {
+        fn failing_pipeline(file: &TempAudioFile) -> Result<()> {
+            write_wav(file.path(), &[0.1, 0.2], 1, 16000, 16)?;
+            Err(anyhow::anyhow!("transcription failed"))
+        }
+
+        let path = std::env::temp_dir().join("chezwizper_test_guard.wav");
+        {
+            let file = TempAudioFile::new(path.clone(), true);
+            assert!(failing_pipeline(&file).is_err());
+            assert!(path.exists());
+        }
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_temp_audio_file_kept_when_disabled() {
+        let path = std::env::temp_dir().join("chezwizper_test_guard_keep.wav");
+        {
+            let file = TempAudioFile::new(path.clone(), false);
+            write_wav(file.path(), &[0.1], 1, 16000, 32).unwrap();
+        }
+        assert!(path.exists());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_upload_format_parse() {
+        assert_eq!(UploadFormat::parse("opus").unwrap(), UploadFormat::Opus);
+        assert_eq!(UploadFormat::Opus.extension(), "ogg");
+        assert!(UploadFormat::parse("aac").is_err());
+    }
+
+    #[test]
+    fn test_split_wav_cuts_in_silence() {
+        let path = std::env::temp_dir().join("chezwizper_test_split.wav");
+        // 3s of tone with a pause between 1.7s and 1.9s
+        let samples: Vec<f32> = (0..48000)
+            .map(|i| match i {
+                27200..=30400 => 0.0,
+                _ if i % 2 == 0 => 0.5,
+                _ => -0.5,
+            })
+            .collect();
+        write_wav(&path, &samples, 1, 16000, 16).unwrap();
+
+        let chunks = split_wav(&path, 2, u64::MAX).unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].start_secs, 0.0);
+        assert!((1.7..=1.9).contains(&chunks[1].start_secs));
+
+        let frames: u32 = chunks
+            .iter()
+            .map(|c| hound::WavReader::open(c.file.path()).unwrap().duration())
+            .sum();
+        assert_eq!(frames, 48000);
+
+        let chunk_paths: Vec<PathBuf> = chunks.iter().map(|c| c.file.path().clone()).collect();
+        drop(chunks);
+        assert!(chunk_paths.iter().all(|p| !p.exists()));
+
+        // The byte limit caps chunks too
+        for chunk in split_wav(&path, 60, 32000 + WAV_HEADER_MARGIN).unwrap() {
+            assert!(
+                hound::WavReader::open(chunk.file.path())
+                    .unwrap()
+                    .duration()
+                    <= 16000
+            );
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_archive_file_name() {
+        // 2024-02-29 13:05:09 UTC
+        assert_eq!(
+            archive_file_name(
+                1709211909,
+                Some("Send the report to Anna, by Friday please!")
+            ),
+            "2024-02-29_13-05-09_send-the-report-to-anna-by.wav"
+        );
+        assert_eq!(archive_file_name(0, None), "1970-01-01_00-00-00.wav");
+        assert_eq!(
+            archive_file_name(0, Some("../ ?!")),
+            "1970-01-01_00-00-00.wav"
+        );
+
+        let long = archive_file_name(0, Some(&"a".repeat(200)));
+        assert_eq!(long.len(), "1970-01-01_00-00-00_".len() + 48 + ".wav".len());
+    }
+
+    #[test]
+    fn test_persist_to_keeps_file() {
+        let dir = std::env::temp_dir().join(format!("chezwizper_archive_{}", std::process::id()));
+        let source = std::env::temp_dir().join("chezwizper_test_persist.wav");
+        write_wav(&source, &[0.1], 1, 16000, 16).unwrap();
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("take.wav"), b"existing").unwrap();
+
+        let kept = TempAudioFile::new(source.clone(), true)
+            .persist_to(&dir.join("take.wav"))
+            .unwrap();
+        assert_eq!(kept, dir.join("take-2.wav"));
+        assert!(kept.exists());
+        assert!(!source.exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_monitor_ignores_leading_silence() {
+        let monitor = RecordingMonitor::new(0.1);
+
+        monitor.update(&[0.0; 160]);
+        assert!(monitor.silence_duration().is_none());
+
+        monitor.update(&[0.5; 160]);
+        monitor.update(&[0.0; 160]);
+        assert!(monitor.silence_duration().is_some());
+
+        monitor.reset();
+        assert!(monitor.silence_duration().is_none());
+    }
+
+    #[test]
+    fn test_monitor_pause_restarts_silence_timer() {
+        let monitor = RecordingMonitor::new(0.1);
+        monitor.update(&[0.5; 160]);
+        std::thread::sleep(Duration::from_millis(30));
+
+        monitor.set_paused(true);
+        assert!(monitor.is_paused());
+        assert!(monitor.silence_duration().is_none());
+
+        monitor.set_paused(false);
+        assert!(monitor.silence_duration().unwrap() < Duration::from_millis(30));
+    }
+
+    #[test]
+    fn test_monitor_level() {
+        let monitor = RecordingMonitor::new(0.1);
+        assert_eq!(monitor.level(), InputLevel::default());
+
+        monitor.update(&[0.5, -0.5, 0.5, -0.25]);
+        let level = monitor.level();
+        assert_eq!(level.peak, 0.5);
+        assert!(level.rms > 0.4 && level.rms < 0.5);
+
+        monitor.set_paused(true);
+        assert_eq!(monitor.level(), InputLevel::default());
+    }
+
+    #[test]
+    fn test_duration_ms() {
+        assert_eq!(duration_ms(16000, 1, 16000), 1000);
+        assert_eq!(duration_ms(44100, 2, 44100), 500);
+        assert_eq!(duration_ms(0, 2, 48000), 0);
+    }
+
     #[tokio::test]
     async fn test_audio_stream_manager_creation() {
         if is_ci() {
@@ -197,6 +1477,6 @@ mod tests {
         }
 
         // This test may fail in CI without audio devices
-        let _manager = AudioStreamManager::new();
+        let _manager = AudioStreamManager::new(&AudioConfig::default());
     }
 }
@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use chezwizper::config::NormalizerConfig;
+use chezwizper::transcription::TranscriptionService;
+use chezwizper::whisper::{ProviderConfig, WhisperTranscriber};
+use clap::Parser;
+use std::path::PathBuf;
+use tracing_subscriber::EnvFilter;
+
+/// Transcribe an audio file with any provider and print the text
+#[derive(Parser)]
+#[command(name = "transcribe_file")]
+struct Args {
+    /// Audio file to transcribe
+    file: PathBuf,
+
+    /// Provider name (openai-api, openai-compatible, groq, deepgram, azure-openai, openai-cli,
+    /// whisper-cpp, whisper-cpp-server); auto-detects when unset
+    #[arg(long)]
+    provider: Option<String>,
+
+    /// Model name, defaults to the provider's default
+    #[arg(long)]
+    model: Option<String>,
+
+    /// Language code, or "auto"
+    #[arg(long, default_value = "en")]
+    language: String,
+
+    /// Custom API endpoint (API providers only)
+    #[arg(long)]
+    endpoint: Option<String>,
+
+    /// API key; API providers otherwise read OPENAI_API_KEY, GROQ_API_KEY or DEEPGRAM_API_KEY
+    #[arg(long)]
+    api_key: Option<String>,
+
+    /// Log at debug level to stderr
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    // Logs go to stderr so stdout only carries the transcription
+    let log_level = if args.verbose { "debug" } else { "warn" };
+    let env_filter = EnvFilter::try_new(log_level).unwrap_or_else(|_| EnvFilter::new("warn"));
+    tracing_subscriber::fmt()
+        .with_env_filter(env_filter)
+        .with_writer(std::io::stderr)
+        .init();
+
+    if !args.file.exists() {
+        anyhow::bail!("Audio file not found: {:?}", args.file);
+    }
+
+    let whisper = WhisperTranscriber::new(ProviderConfig {
+        provider: args.provider,
+        model: args.model,
+        language: Some(args.language),
+        api_endpoint: args.endpoint,
+        api_key: args.api_key,
+        ..Default::default()
+    })?;
+
+    let service = TranscriptionService::new(whisper, &NormalizerConfig::default())?;
+    let transcription = service
+        .transcribe(&args.file)
+        .await
+        .with_context(|| format!("Failed to transcribe {:?}", args.file))?;
+
+    println!("{}", transcription.text);
+    Ok(())
+}
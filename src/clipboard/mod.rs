@@ -1,19 +1,90 @@
-use anyhow::Result;
-use arboard::Clipboard;
+use anyhow::{Context, Result};
+use arboard::{Clipboard, ImageData};
+use serde::Serialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::Duration;
 use tracing::{debug, error, info};
+use which::which;
+
+/// Time the target app gets to read the pasted text before the clipboard is restored
+const RESTORE_DELAY: Duration = Duration::from_millis(500);
+
+/// Which tool owns the clipboard, shared by `ClipboardManager` and the
+/// paste fallback in `TextInjector`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClipboardTool {
+    /// wl-clipboard on Wayland, arboard elsewhere
+    #[default]
+    Auto,
+    Arboard,
+    /// wl-copy/wl-paste; wl-copy keeps serving the text after we exit,
+    /// where arboard can silently lose ownership on Wayland
+    WlClipboard,
+}
+
+impl ClipboardTool {
+    pub fn parse(tool: &str) -> Result<Self> {
+        match tool {
+            "auto" => Ok(Self::Auto),
+            "arboard" => Ok(Self::Arboard),
+            "wl-clipboard" => Ok(Self::WlClipboard),
+            other => Err(anyhow::anyhow!(
+                "Unknown clipboard_tool '{}', expected \"auto\", \"arboard\" or \"wl-clipboard\"",
+                other
+            )),
+        }
+    }
+
+    /// Pick a concrete tool for `Auto`
+    pub fn resolve(self) -> Self {
+        match self {
+            Self::Auto if std::env::var("WAYLAND_DISPLAY").is_ok() && which("wl-copy").is_ok() => {
+                Self::WlClipboard
+            }
+            Self::Auto => Self::Arboard,
+            tool => tool,
+        }
+    }
+}
+
+/// Clipboard content captured before copying, so it can be put back
+enum SavedContent {
+    Text(String),
+    Image(ImageData<'static>),
+    /// Raw content of one MIME type, as saved through wl-paste
+    Typed {
+        mime: String,
+        data: Vec<u8>,
+    },
+    Empty,
+}
 
 pub struct ClipboardManager {
-    clipboard: Clipboard,
+    /// None when wl-clipboard handles the clipboard
+    clipboard: Option<Clipboard>,
     preserve_previous: bool,
+    saved: Option<SavedContent>,
 }
 
 impl ClipboardManager {
-    pub fn new() -> Result<Self> {
-        let clipboard = Clipboard::new()?;
+    pub fn new(tool: ClipboardTool) -> Result<Self> {
+        let clipboard = match tool.resolve() {
+            ClipboardTool::WlClipboard => {
+                info!("Using wl-clipboard for the clipboard");
+                None
+            }
+            _ => {
+                info!("Using arboard for the clipboard");
+                Some(Clipboard::new()?)
+            }
+        };
 
         Ok(Self {
             clipboard,
             preserve_previous: false,
+            saved: None,
         })
     }
 
@@ -27,45 +98,202 @@ impl ClipboardManager {
             return Ok(());
         }
 
-        let previous = if self.preserve_previous {
-            self.clipboard.get_text().ok()
-        } else {
-            None
-        };
+        if self.preserve_previous {
+            self.saved = Some(self.snapshot());
+        }
 
         info!("Copying {} chars to clipboard", text.len());
         debug!("Text to copy: {}", text);
 
-        self.clipboard.set_text(text)?;
+        match &mut self.clipboard {
+            Some(clipboard) => clipboard.set_text(text)?,
+            None => wl_copy(text.as_bytes(), None)?,
+        }
 
-        if let Some(prev) = previous {
-            debug!("Previous clipboard content preserved: {} chars", prev.len());
+        Ok(())
+    }
+
+    /// Put back whatever was on the clipboard before the last `copy_text`,
+    /// after giving the paste time to complete. No-op unless preserving.
+    pub async fn restore_previous(&mut self) -> Result<()> {
+        let Some(saved) = self.saved.take() else {
+            return Ok(());
+        };
+
+        tokio::time::sleep(RESTORE_DELAY).await;
+
+        match (saved, &mut self.clipboard) {
+            (SavedContent::Text(text), Some(clipboard)) => {
+                debug!("Restoring previous clipboard text: {} chars", text.len());
+                clipboard.set_text(text)?;
+            }
+            (SavedContent::Image(image), Some(clipboard)) => {
+                debug!(
+                    "Restoring previous clipboard image: {}x{}",
+                    image.width, image.height
+                );
+                clipboard.set_image(image)?;
+            }
+            (SavedContent::Typed { mime, data }, _) => {
+                debug!(
+                    "Restoring previous clipboard {}: {} bytes",
+                    mime,
+                    data.len()
+                );
+                wl_copy(&data, Some(&mime))?;
+            }
+            (SavedContent::Empty, Some(clipboard)) => {
+                debug!("Previous clipboard was empty, clearing");
+                clipboard.clear()?;
+            }
+            (SavedContent::Empty, None) => {
+                debug!("Previous clipboard was empty, clearing");
+                run_wl_copy(&["--clear"], None)?;
+            }
+            // Only arboard saves Text and Image
+            (SavedContent::Text(_) | SavedContent::Image(_), None) => {}
         }
 
+        info!("Restored previous clipboard content");
         Ok(())
     }
 
+    fn snapshot(&mut self) -> SavedContent {
+        let Some(clipboard) = &mut self.clipboard else {
+            return wl_snapshot();
+        };
+
+        match clipboard.get_text() {
+            Ok(text) if !text.is_empty() => return SavedContent::Text(text),
+            Ok(_) => {}
+            Err(e) => debug!("No text on clipboard to preserve: {}", e),
+        }
+
+        match clipboard.get_image() {
+            Ok(image) => SavedContent::Image(image),
+            Err(e) => {
+                debug!("No image on clipboard to preserve: {}", e);
+                SavedContent::Empty
+            }
+        }
+    }
+
     pub async fn copy_with_wayland_fallback(&mut self, text: &str) -> Result<()> {
-        // Try arboard first
+        // Try the configured tool first
         if let Err(e) = self.copy_text(text) {
+            if self.clipboard.is_none() {
+                return Err(e);
+            }
             error!("Arboard clipboard failed: {}, trying wl-copy", e);
 
             // Fallback to wl-copy command
-            use std::io::Write;
-            use std::process::Command;
+            wl_copy(text.as_bytes(), None)?;
+            info!("Copied text using wl-copy fallback");
+        }
 
-            let mut child = Command::new("wl-copy")
-                .stdin(std::process::Stdio::piped())
-                .spawn()?;
+        Ok(())
+    }
+}
 
-            if let Some(mut stdin) = child.stdin.take() {
-                stdin.write_all(text.as_bytes())?;
-            }
+/// Save the clipboard through wl-paste, keeping one type we can put back
+fn wl_snapshot() -> SavedContent {
+    let types = match wl_paste(&["--list-types"]) {
+        Ok(types) => String::from_utf8_lossy(&types).into_owned(),
+        Err(e) => {
+            debug!("Nothing on clipboard to preserve: {}", e);
+            return SavedContent::Empty;
+        }
+    };
+    let Some(mime) = restorable_type(&types) else {
+        debug!("No text or image on clipboard to preserve");
+        return SavedContent::Empty;
+    };
 
-            child.wait()?;
-            info!("Copied text using wl-copy fallback");
+    match wl_paste(&["--no-newline", "--type", mime]) {
+        Ok(data) => SavedContent::Typed {
+            mime: mime.to_string(),
+            data,
+        },
+        Err(e) => {
+            debug!("Failed to read clipboard {}: {}", mime, e);
+            SavedContent::Empty
         }
+    }
+}
 
-        Ok(())
+/// The type worth restoring from `wl-paste --list-types` output: plain
+/// text first, then PNG, then any other image
+fn restorable_type(types: &str) -> Option<&str> {
+    let types: Vec<&str> = types.lines().map(str::trim).collect();
+    ["text/plain;charset=utf-8", "text/plain", "image/png"]
+        .into_iter()
+        .find_map(|wanted| types.iter().copied().find(|t| *t == wanted))
+        .or_else(|| types.iter().copied().find(|t| t.starts_with("image/")))
+}
+
+fn wl_copy(data: &[u8], mime: Option<&str>) -> Result<()> {
+    match mime {
+        Some(mime) => run_wl_copy(&["--type", mime], Some(data)),
+        None => run_wl_copy(&[], Some(data)),
+    }
+}
+
+fn run_wl_copy(args: &[&str], data: Option<&[u8]>) -> Result<()> {
+    let mut child = Command::new("wl-copy")
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("Failed to run wl-copy")?;
+
+    if let (Some(mut stdin), Some(data)) = (child.stdin.take(), data) {
+        stdin.write_all(data)?;
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("wl-copy exited with {}", status));
+    }
+    Ok(())
+}
+
+fn wl_paste(args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("wl-paste")
+        .args(args)
+        .output()
+        .context("Failed to run wl-paste")?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("wl-paste exited with {}", output.status));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clipboard_tool_parse() {
+        assert_eq!(
+            ClipboardTool::parse("wl-clipboard").unwrap(),
+            ClipboardTool::WlClipboard
+        );
+        assert_eq!(
+            ClipboardTool::parse("arboard").unwrap().resolve(),
+            ClipboardTool::Arboard
+        );
+        assert_ne!(ClipboardTool::Auto.resolve(), ClipboardTool::Auto);
+        assert!(ClipboardTool::parse("xclip").is_err());
+    }
+
+    #[test]
+    fn test_restorable_type() {
+        assert_eq!(
+            restorable_type("text/html\ntext/plain\nimage/png\n"),
+            Some("text/plain")
+        );
+        assert_eq!(restorable_type("image/jpeg\nimage/png"), Some("image/png"));
+        assert_eq!(restorable_type("image/bmp"), Some("image/bmp"));
+        assert_eq!(restorable_type("application/x-kde-cutselection"), None);
     }
 }
@@ -1,9 +1,36 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use tracing::info;
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+use crate::audio::UploadFormat;
+use crate::clipboard::ClipboardTool;
+use crate::normalizer::CustomReplacementNormalizer;
+use crate::text_injection::{InjectionMode, PasteShortcut, Selection, MAX_TYPE_DELAY_MS};
+use crate::transcription::DEFAULT_TRANSCRIPTION_TIMEOUT_SECS;
+use crate::whisper::{AZURE_DEFAULT_API_VERSION, OPENAI_API_KEY_ENV};
+
+/// Values `whisper.provider` accepts
+pub const PROVIDERS: &[&str] = &[
+    "openai-api",
+    "groq",
+    "deepgram",
+    "azure-openai",
+    "openai-compatible",
+    "openai-cli",
+    "whisper-cpp",
+    "whisper-cpp-server",
+];
+const TASKS: &[&str] = &["transcribe", "translate"];
+const INDICATOR_POSITIONS: &[&str] = &["top-left", "top-right", "bottom-left", "bottom-right"];
+const NOTIFICATION_BACKENDS: &[&str] = &["hyprland", "notify-send", "auto"];
+const INPUT_METHODS: &[&str] = &["auto", "xdotool", "ydotool", "clipboard", "wtype", "enigo"];
+const INJECTION_METHODS: &[&str] = &["xdotool", "ydotool", "clipboard", "wtype", "enigo"];
+/// Sections a `[profiles.<name>]` table may override
+const PROFILE_SECTIONS: &[&str] = &["whisper", "behavior"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Config {
     pub audio: AudioConfig,
@@ -11,29 +38,65 @@ pub struct Config {
     pub ui: UiConfig,
     pub wayland: WaylandConfig,
     pub behavior: BehaviorConfig,
+    pub api: ApiConfig,
+    pub normalizer: NormalizerConfig,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct AudioConfig {
     pub device: String,
     pub sample_rate: u32,
     pub channels: u16,
+    /// Scale recordings so the peak sits at ~-1 dBFS before saving
+    pub normalize_audio: bool,
+    /// Bit depth of saved recordings: 32 (float) or 16 (PCM)
+    pub wav_bit_depth: u16,
+    /// Multiplier applied to captured samples, clamped to full scale
+    pub gain: f32,
+    /// RMS level below which the saved recording is silenced; 0 turns the gate off
+    pub noise_gate_threshold: f32,
+    pub noise_gate_attack_ms: u32,
+    pub noise_gate_release_ms: u32,
+    /// Format recordings are converted to with ffmpeg before uploading: wav, mp3 or opus
+    pub upload_format: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct WhisperConfig {
-    pub model: String,
+    /// Model name; each provider has its own default when unset
+    pub model: Option<String>,
     pub language: String,
     pub command_path: Option<String>,
     pub model_path: Option<String>,
     pub api_endpoint: Option<String>,
     pub provider: Option<String>,
     pub api_key: Option<String>,
+    /// "transcribe" keeps the spoken language, "translate" always outputs English
+    pub task: String,
+    /// Vocabulary hint for the model, e.g. "ChezWizper, Wayland, Hyprland"
+    pub initial_prompt: Option<String>,
+    /// Timeout for a single API request
+    pub request_timeout_secs: u64,
+    /// Attempts made when an API request fails transiently
+    pub max_attempts: u32,
+    /// Longest chunk sent when a recording is over the provider's upload limit
+    pub chunk_secs: u64,
+    /// Discard transcriptions scored below this (0.0 - 1.0); 0 keeps everything
+    pub min_confidence: f64,
+    /// Azure OpenAI resource name, the `{resource}` in `{resource}.openai.azure.com`
+    pub azure_resource: Option<String>,
+    /// Azure OpenAI deployment of the Whisper model
+    pub azure_deployment: Option<String>,
+    pub azure_api_version: String,
+    /// Keep the whisper.cpp model loaded in a `whisper-server` between recordings
+    pub keep_model_loaded: bool,
+    /// Abandon a transcription, killing any whisper subprocess, after this long
+    pub transcription_timeout_secs: u64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct UiConfig {
     pub indicator_position: String,
@@ -42,35 +105,163 @@ pub struct UiConfig {
     pub layer_shell_anchor: String,
     pub layer_shell_margin: u32,
     pub notification_color: String,
+    pub notification_timeout_ms: u32,
+    /// "hyprland", "notify-send", or "auto" to use whichever is installed
+    pub notification_backend: String,
+    pub notification_colors: NotificationColors,
+    pub sounds: SoundsConfig,
+    /// Send SIGRTMIN+N to waybar on every state change so its module refreshes instantly
+    pub waybar_signal: Option<u8>,
     pub waybar: WaybarConfig,
 }
 
+/// Custom sound files played instead of the generated beeps
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundsConfig {
+    pub start: Option<PathBuf>,
+    pub stop: Option<PathBuf>,
+    pub complete: Option<PathBuf>,
+    pub error: Option<PathBuf>,
+}
+
+/// Per-state Hyprland notification colors; unset states use `notification_color`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationColors {
+    pub recording: Option<String>,
+    pub processing: Option<String>,
+    pub complete: Option<String>,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct WaybarConfig {
     pub idle_text: String,
     pub recording_text: String,
+    pub paused_text: String,
     pub processing_text: String,
     pub idle_tooltip: String,
     pub recording_tooltip: String,
+    pub paused_tooltip: String,
     pub processing_tooltip: String,
+    pub error_text: String,
+    pub error_tooltip: String,
+    /// Shown while `POST /enabled` has turned recording off
+    pub disabled_text: String,
+    pub disabled_tooltip: String,
+    /// Seconds the error state is shown before returning to idle
+    pub error_timeout_secs: u64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct WaylandConfig {
+    /// Text injection method, or "auto" to go by injection_priority
     pub input_method: String,
+    /// Methods tried in order when input_method is "auto" or isn't usable; clipboard-only is the last resort
+    pub injection_priority: Vec<String>,
     pub use_hyprland_ipc: bool,
+    /// Keys pressed to paste, e.g. "ctrl+v" or "ctrl+shift+v" for terminals
+    pub paste_shortcut: String,
+    /// "type", "paste" or "auto" (type with paste fallback)
+    pub injection_mode: String,
+    /// Delay between typed characters for wtype/ydotool (0 = full speed)
+    pub type_delay_ms: u64,
+    /// ydotoold socket path; derived from $XDG_RUNTIME_DIR when unset
+    pub ydotool_socket: Option<String>,
+    /// "clipboard" pastes with paste_shortcut, "primary" with a middle click
+    pub selection: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct BehaviorConfig {
     pub auto_paste: bool,
+    /// Only copy transcriptions to the clipboard, never type or paste them
+    pub clipboard_only: bool,
     pub preserve_clipboard: bool,
+    /// "auto", "arboard" or "wl-clipboard"; auto uses wl-clipboard on Wayland
+    pub clipboard_tool: String,
     pub delete_audio_files: bool,
+    /// Keep every recording here, named after its time and first words
+    pub audio_archive_dir: Option<PathBuf>,
     #[serde(default = "default_audio_feedback")]
     pub audio_feedback: bool,
+    /// Number of recent transcriptions kept in memory for GET /history
+    pub history_size: usize,
+    /// Append every transcription to this file as a JSON line (off when unset)
+    pub history_file: Option<PathBuf>,
+    /// Size at which the history file is rotated to `<history_file>.1`
+    pub history_file_max_bytes: u64,
+    /// Stop recording automatically after a period of silence
+    pub auto_stop_on_silence: bool,
+    /// RMS level (0.0 - 1.0) below which input counts as silence
+    pub silence_threshold: f32,
+    /// How long the input must stay silent before recording stops
+    pub silence_duration_ms: u64,
+    /// Recordings stop automatically after this many seconds (0 = unlimited)
+    pub max_recording_secs: u64,
+    /// Recordings shorter than this are discarded instead of transcribed
+    pub min_recording_ms: u64,
+    /// Toggles this soon after recording started or stopped are ignored, so a
+    /// double-fired keybind doesn't stop a recording right away (0 = off)
+    pub toggle_debounce_ms: u64,
+    /// Directory for temporary recordings and transcription output
+    pub temp_dir: PathBuf,
+    /// On SIGINT/SIGTERM, transcribe an in-progress recording instead of
+    /// only saving it to temp_dir
+    pub transcribe_on_shutdown: bool,
+    /// Shell command that gets the transcription on stdin; its stdout is
+    /// copied and injected instead
+    pub post_process_command: Option<String>,
+    /// The original text is used when the command runs longer than this
+    pub post_process_timeout_secs: u64,
+    /// Shell command run when recording starts, e.g. "playerctl pause"
+    pub on_record_start: Option<String>,
+    /// Shell command run when recording stops or is cancelled
+    pub on_record_stop: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Bearer token required on requests that control recording or return
+    /// transcripts. Only read from config.toml so other local processes can't
+    /// discover it via env
+    pub auth_token: Option<String>,
+    /// Also require the token on the other read-only endpoints (/, /status, ...)
+    pub protect_status: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NormalizerConfig {
+    /// Text replacements applied in order after provider cleanup
+    pub replacements: Vec<ReplacementRule>,
+    /// Turn spoken commands like "new line" or "comma" into characters
+    pub spoken_commands: bool,
+    /// Spoken phrase -> text; replaces the built-in English commands when set
+    pub command_words: BTreeMap<String, String>,
+    /// Capitalize sentences and "I", and end the text with a period
+    pub capitalize: bool,
+    /// Keep non-speech annotations like `[BLANK_AUDIO]` or `(music)` instead of stripping them
+    pub keep_annotations: bool,
+    /// Collapse a phrase repeated back to back more than this many times; 0 turns it off
+    pub max_repeats: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplacementRule {
+    pub pattern: String,
+    pub replacement: String,
+    /// Treat `pattern` as a regex (with `$1` capture groups in `replacement`)
+    /// instead of literal text
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub case_insensitive: bool,
 }
 
 fn default_audio_feedback() -> bool {
@@ -83,6 +274,13 @@ impl Default for AudioConfig {
             device: "default".to_string(),
             sample_rate: 16000,
             channels: 1,
+            normalize_audio: false,
+            wav_bit_depth: 32,
+            gain: 1.0,
+            noise_gate_threshold: 0.0,
+            noise_gate_attack_ms: 5,
+            noise_gate_release_ms: 150,
+            upload_format: "wav".to_string(),
         }
     }
 }
@@ -90,13 +288,24 @@ impl Default for AudioConfig {
 impl Default for WhisperConfig {
     fn default() -> Self {
         Self {
-            model: "base".to_string(),
+            model: None,
             language: "en".to_string(),
             command_path: None,
             model_path: None,
-            api_endpoint: Some("https://api.openai.com/v1/audio/transcriptions".to_string()),
+            api_endpoint: None,
             provider: None,
             api_key: None,
+            task: "transcribe".to_string(),
+            initial_prompt: None,
+            request_timeout_secs: 60,
+            max_attempts: 3,
+            chunk_secs: 600,
+            min_confidence: 0.0,
+            azure_resource: None,
+            azure_deployment: None,
+            azure_api_version: AZURE_DEFAULT_API_VERSION.to_string(),
+            keep_model_loaded: false,
+            transcription_timeout_secs: DEFAULT_TRANSCRIPTION_TIMEOUT_SECS,
         }
     }
 }
@@ -110,6 +319,11 @@ impl Default for UiConfig {
             layer_shell_anchor: "top | right".to_string(),
             layer_shell_margin: 10,
             notification_color: "rgb(ff1744)".to_string(),
+            notification_timeout_ms: 3000,
+            notification_backend: "hyprland".to_string(),
+            notification_colors: NotificationColors::default(),
+            sounds: SoundsConfig::default(),
+            waybar_signal: None,
             waybar: WaybarConfig::default(),
         }
     }
@@ -120,10 +334,17 @@ impl Default for WaybarConfig {
         Self {
             idle_text: "󰑊".to_string(),       // Nerd Font circle with dot (idle)
             recording_text: "󰻃".to_string(),  // Nerd Font record button (recording)
+            paused_text: "󰏤".to_string(),     // Nerd Font pause icon (paused)
             processing_text: "󰦖".to_string(), // Nerd Font loading/processing icon
             idle_tooltip: "Press Super+R to record".to_string(),
             recording_tooltip: "Recording... Press Super+R to stop".to_string(),
+            paused_tooltip: "Recording paused".to_string(),
             processing_tooltip: "Processing transcription...".to_string(),
+            error_text: "󰀦".to_string(), // Nerd Font alert icon (error)
+            error_tooltip: "Transcription failed".to_string(),
+            disabled_text: "󰍭".to_string(), // Nerd Font microphone off icon
+            disabled_tooltip: "ChezWizper disabled".to_string(),
+            error_timeout_secs: 5,
         }
     }
 }
@@ -131,8 +352,16 @@ impl Default for WaybarConfig {
 impl Default for WaylandConfig {
     fn default() -> Self {
         Self {
-            input_method: "wtype".to_string(),
+            input_method: "auto".to_string(),
+            injection_priority: ["xdotool", "ydotool", "clipboard", "wtype"]
+                .map(String::from)
+                .to_vec(),
             use_hyprland_ipc: true,
+            paste_shortcut: "ctrl+v".to_string(),
+            injection_mode: "auto".to_string(),
+            type_delay_ms: 0,
+            ydotool_socket: None,
+            selection: "clipboard".to_string(),
         }
     }
 }
@@ -141,56 +370,560 @@ impl Default for BehaviorConfig {
     fn default() -> Self {
         Self {
             auto_paste: true,
+            clipboard_only: false,
             preserve_clipboard: false,
+            clipboard_tool: "auto".to_string(),
             delete_audio_files: true,
+            audio_archive_dir: None,
             audio_feedback: true,
+            history_size: 20,
+            history_file: None,
+            history_file_max_bytes: 10 * 1024 * 1024,
+            auto_stop_on_silence: false,
+            silence_threshold: 0.01,
+            silence_duration_ms: 2000,
+            max_recording_secs: 300,
+            min_recording_ms: 500,
+            toggle_debounce_ms: 150,
+            temp_dir: std::env::temp_dir(),
+            transcribe_on_shutdown: false,
+            post_process_command: None,
+            post_process_timeout_secs: 10,
+            on_record_start: None,
+            on_record_stop: None,
         }
     }
 }
 
+impl BehaviorConfig {
+    /// The history log path with a leading `~` expanded
+    pub fn history_file_path(&self) -> Option<PathBuf> {
+        self.history_file.as_deref().map(expand_home)
+    }
+
+    /// The recording archive directory with a leading `~` expanded
+    pub fn audio_archive_path(&self) -> Option<PathBuf> {
+        self.audio_archive_dir.as_deref().map(expand_home)
+    }
+
+    /// Make sure the configured temp directory exists and return it
+    pub fn ensure_temp_dir(&self) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.temp_dir).with_context(|| {
+            format!(
+                "Failed to create temp directory {:?}; set [behavior] temp_dir to a writable directory",
+                self.temp_dir
+            )
+        })?;
+
+        Ok(self.temp_dir.clone())
+    }
+}
+
+/// Allow "~/..." paths in the config
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
 impl Config {
-    pub fn load() -> Result<Self> {
+    /// Settings that differ from `other` but are only read at startup, so a
+    /// reload can't apply them
+    pub fn restart_required_changes(&self, other: &Config) -> Vec<&'static str> {
+        fn changed<T: Serialize>(a: &T, b: &T) -> bool {
+            serde_json::to_value(a).ok() != serde_json::to_value(b).ok()
+        }
+
+        // min_confidence is read per transcription, the rest builds the transcriber
+        let whisper = |w: &WhisperConfig| WhisperConfig {
+            min_confidence: 0.0,
+            ..w.clone()
+        };
+
+        let (old, new) = (self, other);
+        [
+            ("audio", changed(&old.audio, &new.audio)),
+            (
+                "whisper",
+                changed(&whisper(&old.whisper), &whisper(&new.whisper)),
+            ),
+            ("api", changed(&old.api, &new.api)),
+            ("ui.waybar", changed(&old.ui.waybar, &new.ui.waybar)),
+            (
+                "ui.waybar_signal",
+                old.ui.waybar_signal != new.ui.waybar_signal,
+            ),
+            (
+                "behavior.preserve_clipboard",
+                old.behavior.preserve_clipboard != new.behavior.preserve_clipboard,
+            ),
+            (
+                "behavior.clipboard_tool",
+                old.behavior.clipboard_tool != new.behavior.clipboard_tool,
+            ),
+            (
+                "behavior.history_size",
+                old.behavior.history_size != new.behavior.history_size,
+            ),
+            (
+                "behavior.silence_threshold",
+                old.behavior.silence_threshold != new.behavior.silence_threshold,
+            ),
+            (
+                "behavior.max_recording_secs",
+                old.behavior.max_recording_secs != new.behavior.max_recording_secs,
+            ),
+            (
+                "behavior.min_recording_ms",
+                old.behavior.min_recording_ms != new.behavior.min_recording_ms,
+            ),
+            (
+                "behavior.temp_dir",
+                old.behavior.temp_dir != new.behavior.temp_dir,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, changed)| changed.then_some(name))
+        .collect()
+    }
+
+    /// Check values that parse as TOML but can't work, reporting every problem at once
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        let mut one_of = |field: &str, value: &str, allowed: &[&str]| {
+            if !allowed.contains(&value) {
+                problems.push(format!(
+                    "{field} = \"{value}\" is not valid, expected one of: {}",
+                    allowed.join(", ")
+                ));
+            }
+        };
+        if let Some(provider) = &self.whisper.provider {
+            one_of("whisper.provider", provider, PROVIDERS);
+        }
+        one_of("whisper.task", &self.whisper.task, TASKS);
+        one_of(
+            "ui.indicator_position",
+            &self.ui.indicator_position,
+            INDICATOR_POSITIONS,
+        );
+        one_of(
+            "ui.notification_backend",
+            &self.ui.notification_backend,
+            NOTIFICATION_BACKENDS,
+        );
+        one_of(
+            "wayland.input_method",
+            &self.wayland.input_method,
+            INPUT_METHODS,
+        );
+        for method in &self.wayland.injection_priority {
+            one_of("wayland.injection_priority", method, INJECTION_METHODS);
+        }
+
+        if let Err(e) = UploadFormat::parse(&self.audio.upload_format) {
+            problems.push(e.to_string());
+        }
+        if let Err(e) = InjectionMode::parse(&self.wayland.injection_mode) {
+            problems.push(e.to_string());
+        }
+        if let Err(e) = PasteShortcut::parse(&self.wayland.paste_shortcut) {
+            problems.push(e.to_string());
+        }
+        if let Err(e) = Selection::parse(&self.wayland.selection) {
+            problems.push(e.to_string());
+        }
+        if let Err(e) = ClipboardTool::parse(&self.behavior.clipboard_tool) {
+            problems.push(e.to_string());
+        }
+        if let Err(e) = CustomReplacementNormalizer::new(&self.normalizer.replacements) {
+            problems.push(format!("{e:#}"));
+        }
+
+        let mut check = |ok: bool, problem: &str| {
+            if !ok {
+                problems.push(problem.to_string());
+            }
+        };
+        check(
+            (8000..=192_000).contains(&self.audio.sample_rate),
+            "audio.sample_rate must be between 8000 and 192000 Hz",
+        );
+        check(
+            self.audio.channels >= 1,
+            "audio.channels must be at least 1",
+        );
+        check(
+            matches!(self.audio.wav_bit_depth, 16 | 32),
+            "audio.wav_bit_depth must be 16 or 32",
+        );
+        check(
+            self.audio.gain > 0.0 && self.audio.gain <= 10.0,
+            "audio.gain must be greater than 0 and at most 10",
+        );
+        check(
+            (0.0..=1.0).contains(&self.audio.noise_gate_threshold),
+            "audio.noise_gate_threshold must be between 0.0 and 1.0",
+        );
+        check(
+            self.whisper.request_timeout_secs > 0,
+            "whisper.request_timeout_secs must be greater than 0",
+        );
+        check(
+            self.whisper.transcription_timeout_secs > 0,
+            "whisper.transcription_timeout_secs must be greater than 0",
+        );
+        check(
+            self.whisper.max_attempts >= 1,
+            "whisper.max_attempts must be at least 1",
+        );
+        check(
+            self.behavior.post_process_timeout_secs > 0,
+            "behavior.post_process_timeout_secs must be greater than 0",
+        );
+        check(
+            self.whisper.chunk_secs > 0,
+            "whisper.chunk_secs must be greater than 0",
+        );
+        check(
+            (0.0..=1.0).contains(&self.whisper.min_confidence),
+            "whisper.min_confidence must be between 0.0 and 1.0",
+        );
+        check(
+            self.whisper.provider.as_deref() != Some("openai-api")
+                || self
+                    .whisper
+                    .api_key
+                    .as_deref()
+                    .is_some_and(|key| !key.is_empty())
+                || std::env::var(OPENAI_API_KEY_ENV).is_ok_and(|key| !key.is_empty()),
+            &format!(
+                "whisper.provider = \"openai-api\" requires whisper.api_key or {OPENAI_API_KEY_ENV}"
+            ),
+        );
+        check(
+            self.whisper.provider.as_deref() != Some("openai-compatible")
+                || self.whisper.api_endpoint.is_some(),
+            "whisper.provider = \"openai-compatible\" requires whisper.api_endpoint",
+        );
+        check(
+            self.whisper.provider.as_deref() != Some("azure-openai")
+                || self.whisper.api_endpoint.is_some()
+                || (self.whisper.azure_resource.is_some()
+                    && self.whisper.azure_deployment.is_some()),
+            "whisper.provider = \"azure-openai\" requires whisper.azure_resource and whisper.azure_deployment, or whisper.api_endpoint",
+        );
+        check(
+            self.wayland.type_delay_ms <= MAX_TYPE_DELAY_MS,
+            &format!("wayland.type_delay_ms must be at most {MAX_TYPE_DELAY_MS}"),
+        );
+        check(
+            (0.0..=1.0).contains(&self.behavior.silence_threshold),
+            "behavior.silence_threshold must be between 0.0 and 1.0",
+        );
+        check(
+            !self.behavior.auto_stop_on_silence || self.behavior.silence_duration_ms > 0,
+            "behavior.silence_duration_ms must be greater than 0 when auto_stop_on_silence is on",
+        );
+
+        if problems.is_empty() {
+            return Ok(());
+        }
+        anyhow::bail!("Invalid configuration:\n  - {}", problems.join("\n  - "))
+    }
+
+    /// Load from the default location (`<config dir>/chezwizper/config.toml`)
+    pub fn load(profile: Option<&str>) -> Result<Self> {
         let config_path = Self::config_path()?;
-        Self::load_from_path(config_path)
+        Self::load_from(&config_path, profile)
     }
 
-    pub fn load_from_path(config_path: PathBuf) -> Result<Self> {
+    /// Load from an explicit file, writing the defaults there if it doesn't exist yet
+    pub fn load_from(config_path: &Path, profile: Option<&str>) -> Result<Self> {
         if !config_path.exists() {
+            if let Some(name) = profile {
+                anyhow::bail!("Profile '{name}' requested but {config_path:?} does not exist");
+            }
             info!(
                 "Config file not found, creating default at {:?}",
                 config_path
             );
             let config = Self::default();
-            config.save()?;
+            config.save_to(config_path)?;
             return Ok(config);
         }
 
-        let content =
-            std::fs::read_to_string(&config_path).context("Failed to read config file")?;
+        let content = std::fs::read_to_string(config_path).context("Failed to read config file")?;
 
-        let config: Self = toml::from_str(&content).context("Failed to parse config file")?;
+        let config = Self::parse(&content, profile).context("Failed to parse config file")?;
+        config
+            .validate()
+            .with_context(|| format!("Config file {config_path:?} has invalid values"))?;
 
         info!("Loaded config from {:?}", config_path);
         Ok(config)
     }
 
-    pub fn save(&self) -> Result<()> {
-        let config_path = Self::config_path()?;
+    /// Parse a config file, layering the named profile's sections over the base ones
+    fn parse(content: &str, profile: Option<&str>) -> Result<Self> {
+        let mut table: toml::Table = toml::from_str(content)?;
+        let profiles = match table.remove("profiles") {
+            Some(toml::Value::Table(profiles)) => profiles,
+            Some(_) => anyhow::bail!("[profiles] must be a table of named profiles"),
+            None => toml::Table::new(),
+        };
 
+        if let Some(name) = profile {
+            let overrides = profiles
+                .get(name)
+                .and_then(toml::Value::as_table)
+                .with_context(|| {
+                    let available: Vec<&str> = profiles.keys().map(String::as_str).collect();
+                    format!(
+                        "Profile '{name}' not found (available: {})",
+                        if available.is_empty() {
+                            "none".to_string()
+                        } else {
+                            available.join(", ")
+                        }
+                    )
+                })?;
+
+            for (section, values) in overrides {
+                if !PROFILE_SECTIONS.contains(&section.as_str()) {
+                    anyhow::bail!(
+                        "profiles.{name}.{section} is not supported; profiles may only override {}",
+                        PROFILE_SECTIONS.join(", ")
+                    );
+                }
+                let toml::Value::Table(values) = values else {
+                    anyhow::bail!("profiles.{name}.{section} must be a table");
+                };
+                // Keys the profile leaves out keep their base values
+                if let toml::Value::Table(base) = table
+                    .entry(section.as_str())
+                    .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                {
+                    base.extend(values.clone());
+                }
+            }
+        }
+
+        Ok(table.try_into()?)
+    }
+
+    pub fn save_to(&self, config_path: &Path) -> Result<()> {
         if let Some(parent) = config_path.parent() {
             std::fs::create_dir_all(parent).context("Failed to create config directory")?;
         }
 
-        let content = toml::to_string_pretty(self).context("Failed to serialize config")?;
+        let content = self.to_toml()?;
 
-        std::fs::write(&config_path, content).context("Failed to write config file")?;
+        std::fs::write(config_path, content).context("Failed to write config file")?;
 
         Ok(())
     }
 
+    /// Every setting as a config file would spell it
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).context("Failed to serialize config")
+    }
+
+    /// Copy with the API key and auth token masked, safe to print or share
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        let mask = |secret: &mut Option<String>| {
+            if secret.is_some() {
+                *secret = Some("<redacted>".to_string());
+            }
+        };
+        mask(&mut config.whisper.api_key);
+        mask(&mut config.api.auth_token);
+        config
+    }
+
     fn config_path() -> Result<PathBuf> {
         let config_dir = dirs::config_dir().context("Failed to determine config directory")?;
 
         Ok(config_dir.join("chezwizper").join("config.toml"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        assert!(Config::default().validate().is_ok());
+
+        let mut config = Config::default();
+        config.audio.sample_rate = 0;
+        config.wayland.injection_mode = "foo".to_string();
+        config.whisper.provider = Some("bar".to_string());
+        config.ui.indicator_position = "middle".to_string();
+
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("audio.sample_rate"), "{message}");
+        assert!(message.contains("injection_mode 'foo'"), "{message}");
+        assert!(message.contains("whisper.provider = \"bar\""), "{message}");
+        assert!(message.contains("ui.indicator_position"), "{message}");
+    }
+
+    #[test]
+    fn test_profile_overrides_base_sections() {
+        let content = r#"
+            [whisper]
+            provider = "whisper-cpp"
+            language = "de"
+
+            [profiles.work.whisper]
+            provider = "openai-api"
+            api_key = "sk-test"
+
+            [profiles.work.behavior]
+            history_size = 5
+        "#;
+
+        let base = Config::parse(content, None).unwrap();
+        assert_eq!(base.whisper.provider.as_deref(), Some("whisper-cpp"));
+
+        let work = Config::parse(content, Some("work")).unwrap();
+        assert_eq!(work.whisper.provider.as_deref(), Some("openai-api"));
+        assert_eq!(work.whisper.api_key.as_deref(), Some("sk-test"));
+        assert_eq!(work.whisper.language, "de");
+        assert_eq!(work.behavior.history_size, 5);
+
+        let missing = Config::parse(content, Some("home")).unwrap_err();
+        assert!(missing.to_string().contains("available: work"), "{missing}");
+
+        let ui = Config::parse(
+            "[profiles.work.ui]\nshow_notifications = false",
+            Some("work"),
+        );
+        assert!(ui.unwrap_err().to_string().contains("only override"));
+    }
+
+    #[test]
+    fn test_load_from_explicit_path() {
+        let dir = std::env::temp_dir().join(format!("chezwizper_config_{}", std::process::id()));
+        let path = dir.join("custom.toml");
+
+        // A missing file is created at the given path, not the default location
+        let created = Config::load_from(&path, None).unwrap();
+        assert!(path.exists());
+
+        std::fs::write(&path, "[whisper]\nlanguage = \"es\"\n").unwrap();
+        let loaded = Config::load_from(&path, None).unwrap();
+        assert_eq!(loaded.whisper.language, "es");
+        assert_eq!(loaded.audio.sample_rate, created.audio.sample_rate);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_example_config_is_valid() {
+        let config: Config = toml::from_str(include_str!("../../example_config.toml")).unwrap();
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_printed_config_round_trips_without_secrets() {
+        let mut config = Config::default();
+        config.whisper.api_key = Some("sk-secret".to_string());
+        config.behavior.min_recording_ms = 250;
+
+        let printed = config.redacted().to_toml().unwrap();
+        assert!(!printed.contains("sk-secret"));
+
+        let parsed = Config::parse(&printed, None).unwrap();
+        assert_eq!(parsed.whisper.api_key.as_deref(), Some("<redacted>"));
+        assert_eq!(parsed.api.auth_token, None);
+        assert_eq!(parsed.behavior.min_recording_ms, 250);
+    }
+
+    #[test]
+    fn test_validate_openai_api_needs_key() {
+        let mut config = Config::default();
+        config.whisper.provider = Some("openai-api".to_string());
+        if std::env::var(OPENAI_API_KEY_ENV).is_err() {
+            assert!(config.validate().is_err());
+        }
+
+        config.whisper.api_key = Some("sk-test".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_azure_openai_needs_deployment() {
+        let mut config = Config::default();
+        config.whisper.provider = Some("azure-openai".to_string());
+        config.whisper.azure_resource = Some("contoso".to_string());
+        assert!(config.validate().is_err());
+
+        config.whisper.azure_deployment = Some("whisper".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_injection_priority() {
+        let mut config = Config::default();
+        config.wayland.injection_priority = vec!["wtype".to_string(), "clipboard".to_string()];
+        assert!(config.validate().is_ok());
+
+        config
+            .wayland
+            .injection_priority
+            .push("xdotool".to_string());
+        assert!(config.validate().is_ok());
+
+        config
+            .wayland
+            .injection_priority
+            .push("keyboard".to_string());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(
+            err.contains("wayland.injection_priority = \"keyboard\""),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_load_input_method_clipboard() {
+        let dir =
+            std::env::temp_dir().join(format!("chezwizper_input_method_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+
+        for method in ["clipboard", "xdotool"] {
+            std::fs::write(&path, format!("[wayland]\ninput_method = \"{method}\"\n")).unwrap();
+            let loaded = Config::load_from(&path, None).unwrap();
+            assert_eq!(loaded.wayland.input_method, method);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_restart_required_changes() {
+        let old = Config::default();
+        let mut new = Config::default();
+        assert!(old.restart_required_changes(&new).is_empty());
+
+        // Live settings don't need a restart
+        new.behavior.auto_paste = !old.behavior.auto_paste;
+        new.ui.notification_color = "rgb(00ff00)".to_string();
+        new.wayland.injection_mode = "paste".to_string();
+        new.whisper.min_confidence = 0.5;
+        assert!(old.restart_required_changes(&new).is_empty());
+
+        new.audio.device = "usb-mic".to_string();
+        new.behavior.max_recording_secs += 60;
+        assert_eq!(
+            old.restart_required_changes(&new),
+            vec!["audio", "behavior.max_recording_secs"]
+        );
+    }
+}
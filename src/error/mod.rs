@@ -0,0 +1,120 @@
+/// Failure categories surfaced by the audio, whisper and text injection APIs,
+/// so callers can tell a missing microphone from a provider or network problem.
+/// Messages keep the full context chain of the underlying error.
+#[derive(Debug, thiserror::Error)]
+pub enum ChezWizperError {
+    /// No usable input device, or it can't capture with the configured settings
+    #[error("{0}")]
+    AudioDevice(String),
+    /// Starting, pausing or saving a recording failed
+    #[error("{0}")]
+    Recording(String),
+    /// The recording was shorter than `min_recording_ms`, so it wasn't saved
+    #[error("recording too short ({0}ms)")]
+    TooShort(u64),
+    /// The transcription provider is unknown, unconfigured or not installed
+    #[error("{0}")]
+    ProviderUnavailable(String),
+    /// The provider could not be reached
+    #[error("{0}")]
+    Network(String),
+    /// The provider ran but produced an error
+    #[error("{0}")]
+    Transcription(String),
+    /// A provider call outlived the transcription timeout and was abandoned
+    #[error("transcription timed out after {0}s")]
+    Timeout(u64),
+    /// No injection tool is available, or typing/pasting the text failed
+    #[error("{0}")]
+    Injection(String),
+}
+
+impl ChezWizperError {
+    /// Short machine readable name, sent with API error events
+    pub fn category(&self) -> &'static str {
+        match self {
+            ChezWizperError::AudioDevice(_) => "audio_device",
+            ChezWizperError::Recording(_) => "recording",
+            ChezWizperError::TooShort(_) => "too_short",
+            ChezWizperError::ProviderUnavailable(_) => "provider_unavailable",
+            ChezWizperError::Network(_) => "network",
+            ChezWizperError::Transcription(_) => "transcription",
+            ChezWizperError::Timeout(_) => "timeout",
+            ChezWizperError::Injection(_) => "injection",
+        }
+    }
+
+    /// Message for the indicator, worded for the category
+    pub fn user_message(&self) -> String {
+        match self {
+            ChezWizperError::AudioDevice(e) => format!("Microphone unavailable: {e}"),
+            ChezWizperError::Recording(e) => format!("Recording failed: {e}"),
+            ChezWizperError::TooShort(_) => "Recording too short".to_string(),
+            ChezWizperError::ProviderUnavailable(e) => format!("Provider unavailable: {e}"),
+            ChezWizperError::Network(_) => {
+                "Couldn't reach the transcription service, check your connection".to_string()
+            }
+            ChezWizperError::Transcription(e) => format!("Transcription failed: {e}"),
+            ChezWizperError::Timeout(_) => "Transcription timed out".to_string(),
+            ChezWizperError::Injection(e) => format!("Typing failed: {e}"),
+        }
+    }
+}
+
+/// Turns an `anyhow` error from the internals into a [`ChezWizperError`] at a
+/// public API boundary
+pub trait Categorize<T> {
+    /// Wrap the error in `kind`, unless it already carries a category or
+    /// comes from a failed HTTP request, which is reported as
+    /// [`ChezWizperError::Network`]
+    fn categorize(self, kind: fn(String) -> ChezWizperError) -> Result<T, ChezWizperError>;
+}
+
+impl<T> Categorize<T> for anyhow::Result<T> {
+    fn categorize(self, kind: fn(String) -> ChezWizperError) -> Result<T, ChezWizperError> {
+        self.map_err(|e| match e.downcast::<ChezWizperError>() {
+            Ok(categorized) => categorized,
+            Err(e) if e.chain().any(|cause| cause.is::<reqwest::Error>()) => {
+                ChezWizperError::Network(format!("{e:#}"))
+            }
+            Err(e) => kind(format!("{e:#}")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_categorize_keeps_context_and_existing_category() {
+        let result: anyhow::Result<()> =
+            Err(anyhow::anyhow!("wtype failed")).context("Failed to type text");
+        let err = result.categorize(ChezWizperError::Injection).unwrap_err();
+        assert!(matches!(err, ChezWizperError::Injection(_)));
+        assert_eq!(err.to_string(), "Failed to type text: wtype failed");
+        assert_eq!(err.category(), "injection");
+
+        let result: anyhow::Result<()> = Err(ChezWizperError::Timeout(30).into());
+        let err = result
+            .categorize(ChezWizperError::Transcription)
+            .unwrap_err();
+        assert!(matches!(err, ChezWizperError::Timeout(30)));
+        assert_eq!(err.user_message(), "Transcription timed out");
+    }
+
+    #[tokio::test]
+    async fn test_categorize_detects_network_errors() {
+        // Nothing listens on the discard port, so the connection is refused
+        let result = reqwest::Client::new()
+            .get("http://127.0.0.1:9/inference")
+            .send()
+            .await
+            .context("Failed to reach provider");
+        let err = result
+            .categorize(ChezWizperError::Transcription)
+            .unwrap_err();
+        assert!(matches!(err, ChezWizperError::Network(_)));
+    }
+}
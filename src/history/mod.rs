@@ -0,0 +1,151 @@
+use crate::whisper::Segment;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A single completed transcription
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryEntry {
+    pub text: String,
+    /// Unix timestamp (seconds) when the transcription finished
+    pub timestamp: u64,
+    pub provider: String,
+    pub duration_ms: u64,
+    /// Segment timestamps, only served from `GET /last?format=segments`
+    #[serde(skip)]
+    pub segments: Option<Vec<Segment>>,
+}
+
+impl HistoryEntry {
+    pub fn new(text: String, provider: &str, duration_ms: u64) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Self {
+            text,
+            timestamp,
+            provider: provider.to_string(),
+            duration_ms,
+            segments: None,
+        }
+    }
+
+    pub fn with_segments(mut self, segments: Option<Vec<Segment>>) -> Self {
+        self.segments = segments;
+        self
+    }
+}
+
+/// Fixed-size ring buffer of recent transcriptions, newest first
+#[derive(Debug)]
+pub struct TranscriptionHistory {
+    entries: VecDeque<HistoryEntry>,
+    capacity: usize,
+}
+
+impl TranscriptionHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Record an entry, dropping the oldest one once capacity is reached
+    pub fn push(&mut self, entry: HistoryEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_back();
+        }
+        self.entries.push_front(entry);
+    }
+
+    pub fn last(&self) -> Option<HistoryEntry> {
+        self.entries.front().cloned()
+    }
+
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        self.entries.iter().cloned().collect()
+    }
+}
+
+/// Append an entry to a JSON lines log. Once the file reaches `max_bytes` it is
+/// moved to `<path>.1`, replacing the previous one, and a new file is started.
+pub fn append_to_file(path: &Path, entry: &HistoryEntry, max_bytes: u64) -> Result<()> {
+    let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    if max_bytes > 0 && size >= max_bytes {
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(".1");
+        std::fs::rename(path, PathBuf::from(rotated))
+            .with_context(|| format!("Failed to rotate history file {path:?}"))?;
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory for {path:?}"))?;
+    }
+
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .with_context(|| format!("Failed to write history file {path:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_drops_oldest() {
+        let mut history = TranscriptionHistory::new(2);
+        history.push(HistoryEntry::new("one".to_string(), "test", 10));
+        history.push(HistoryEntry::new("two".to_string(), "test", 10));
+        history.push(HistoryEntry::new("three".to_string(), "test", 10));
+
+        let texts: Vec<String> = history.entries().into_iter().map(|e| e.text).collect();
+        assert_eq!(texts, vec!["three", "two"]);
+    }
+
+    #[test]
+    fn test_append_to_file_rotates() {
+        let dir = std::env::temp_dir().join(format!("chezwizper_history_{}", std::process::id()));
+        let path = dir.join("history.jsonl");
+        let entry = HistoryEntry::new("hello".to_string(), "test", 10);
+
+        append_to_file(&path, &entry, 1024).unwrap();
+        append_to_file(&path, &entry, 1024).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        let line: serde_json::Value =
+            serde_json::from_str(content.lines().next().unwrap()).unwrap();
+        assert_eq!(line["text"], "hello");
+        assert_eq!(line["provider"], "test");
+
+        // Over the limit, the next entry starts a fresh file
+        append_to_file(&path, &entry, 1).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        assert!(dir.join("history.jsonl.1").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_history_zero_capacity() {
+        let mut history = TranscriptionHistory::new(0);
+        history.push(HistoryEntry::new("one".to_string(), "test", 10));
+
+        assert!(history.entries().is_empty());
+    }
+}
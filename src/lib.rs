@@ -2,8 +2,185 @@ pub mod api;
 pub mod audio;
 pub mod clipboard;
 pub mod config;
+pub mod error;
+pub mod history;
 pub mod normalizer;
 pub mod text_injection;
 pub mod transcription;
 pub mod ui;
+pub mod version;
 pub mod whisper;
+
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+use audio::{AudioStreamManager, TempAudioFile};
+use config::Config;
+use error::ChezWizperError;
+use text_injection::{InjectionOutcome, TextInjector};
+use transcription::TranscriptionService;
+use whisper::Transcription;
+
+/// What a call to [`ChezWizper::record_toggle`] did
+#[derive(Debug)]
+pub enum Toggle {
+    /// A recording started; the next toggle stops it
+    Started,
+    /// The recording stopped and was transcribed
+    Transcribed(Transcription),
+}
+
+/// The daemon's record → transcribe → inject flow, for apps that embed
+/// ChezWizper instead of driving it through the HTTP API
+pub struct ChezWizper {
+    config: Config,
+    temp_dir: PathBuf,
+    transcription_service: TranscriptionService,
+    text_injector: TextInjector,
+    recorder: Mutex<Recorder>,
+}
+
+#[derive(Default)]
+struct Recorder {
+    /// Opened by the first recording, so transcribing files works without a microphone
+    audio: Option<AudioStreamManager>,
+    /// Where the recording in progress is saved when it stops
+    recording: Option<TempAudioFile>,
+}
+
+impl ChezWizper {
+    /// Set up transcription and text injection as described by `config`
+    pub fn new(config: Config) -> anyhow::Result<Self> {
+        config.validate()?;
+        let temp_dir = config.behavior.ensure_temp_dir()?;
+        let transcription_service = TranscriptionService::from_config(&config, temp_dir.clone())?;
+        let text_injector = TextInjector::from_config(&config)?;
+
+        Ok(Self {
+            config,
+            temp_dir,
+            transcription_service,
+            text_injector,
+            recorder: Mutex::new(Recorder::default()),
+        })
+    }
+
+    /// Start recording, or stop the recording in progress and transcribe it.
+    /// The text is also injected when `[behavior] auto_paste` is set and
+    /// `clipboard_only` isn't, like the daemon does.
+    pub async fn record_toggle(&self) -> Result<Toggle, ChezWizperError> {
+        let mut recorder = self.recorder.lock().await;
+        if recorder.audio.is_none() {
+            recorder.audio = Some(AudioStreamManager::from_config(&self.config)?);
+        }
+        let Recorder {
+            audio: Some(audio),
+            recording,
+        } = &mut *recorder
+        else {
+            unreachable!("audio is opened above");
+        };
+
+        let Some(file) = recording.take() else {
+            let file = TempAudioFile::new(
+                self.temp_dir.join(format!(
+                    "chezwizper_{}.wav",
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0)
+                )),
+                self.config.behavior.delete_audio_files,
+            );
+            audio.start_recording().await?;
+            *recording = Some(file);
+            return Ok(Toggle::Started);
+        };
+
+        audio.stop_recording(file.path().clone()).await?;
+        // Released so the next recording can start while this one is transcribed
+        drop(recorder);
+
+        let transcription = self.transcribe_file(file.path()).await?;
+        let behavior = &self.config.behavior;
+        if behavior.auto_paste && !behavior.clipboard_only {
+            self.inject(&transcription.text).await?;
+        }
+        Ok(Toggle::Transcribed(transcription))
+    }
+
+    /// Transcribe an audio file through the configured provider and normalizers
+    pub async fn transcribe_file(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Transcription, ChezWizperError> {
+        self.transcription_service
+            .transcribe(&path.as_ref().to_path_buf())
+            .await
+    }
+
+    /// Type or paste `text` into the focused window
+    pub async fn inject(&self, text: &str) -> Result<InjectionOutcome, ChezWizperError> {
+        self.text_injector.inject_text(text).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_transcribe_file_through_config() {
+        // Answers every request with a canned transcription
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!(
+            "http://{}/v1/audio/transcriptions",
+            listener.local_addr().unwrap()
+        );
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                // Read the whole request so the client isn't cut off mid-upload
+                let mut request = Vec::new();
+                let mut buf = [0; 8192];
+                while let Ok(n @ 1..) = socket.read(&mut buf).await {
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|line| {
+                                line.to_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(|v| v.trim().parse::<usize>().unwrap_or(0))
+                            })
+                            .unwrap_or(0);
+                        if request.len() >= end + 4 + length {
+                            break;
+                        }
+                    }
+                }
+                let body = r#"{"text": " hello world "}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let mut config = Config::default();
+        config.whisper.provider = Some("openai-compatible".to_string());
+        config.whisper.api_endpoint = Some(endpoint);
+        config.behavior.temp_dir = std::env::temp_dir();
+        let chezwizper = ChezWizper::new(config).unwrap();
+
+        let audio = std::env::temp_dir().join(format!("chezwizper_lib_{}.wav", std::process::id()));
+        audio::write_wav(&audio, &[0.0; 1600], 1, 16000, 16).unwrap();
+        let transcription = chezwizper.transcribe_file(&audio).await;
+        std::fs::remove_file(&audio).unwrap();
+
+        assert_eq!(transcription.unwrap().text, "hello world");
+    }
+}
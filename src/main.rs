@@ -1,41 +1,99 @@
 #![allow(clippy::arc_with_non_send_sync)]
 
-mod api;
-mod audio;
-mod clipboard;
-mod config;
-mod normalizer;
-mod text_injection;
-mod transcription;
-mod ui;
-mod whisper;
-
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex};
-use tracing::{error, info};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::EnvFilter;
 
-use crate::api::{ApiCommand, ApiServer};
-use crate::audio::AudioStreamManager;
-use crate::clipboard::ClipboardManager;
-use crate::config::Config;
-use crate::text_injection::TextInjector;
-use crate::transcription::TranscriptionService;
-use crate::ui::Indicator;
-use crate::whisper::WhisperTranscriber;
+use chezwizper::api::{
+    ApiCommand, ApiEvent, ApiServer, HealthCheck, RecordingOverrides, StatusTracker,
+};
+use chezwizper::audio::{AudioStreamManager, RecordingMonitor, TempAudioFile};
+use chezwizper::clipboard::{ClipboardManager, ClipboardTool};
+use chezwizper::config::Config;
+use chezwizper::error::ChezWizperError;
+use chezwizper::history::{HistoryEntry, TranscriptionHistory};
+use chezwizper::text_injection::{InjectionOutcome, TextInjector};
+use chezwizper::transcription::TranscriptionService;
+use chezwizper::ui::{Indicator, NotificationBackend};
+use chezwizper::{audio, history, transcription, version};
 
 #[derive(Parser)]
 #[command(name = "chezwizper")]
 #[command(about = "Voice transcription tool for Wayland/Hyprland", long_about = None)]
+#[command(disable_version_flag = true)]
 struct Args {
     #[arg(short, long)]
     config: Option<PathBuf>,
 
     #[arg(short, long)]
     verbose: bool,
+
+    /// Log as plain text or as one JSON object per line
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// Layer the named [profiles.<name>] settings over the base config
+    #[arg(short, long)]
+    profile: Option<String>,
+
+    /// Override whisper.provider (openai-api, openai-compatible, groq, deepgram,
+    /// azure-openai, openai-cli, whisper-cpp, whisper-cpp-server)
+    #[arg(long)]
+    provider: Option<String>,
+
+    /// Override whisper.model
+    #[arg(long)]
+    model: Option<String>,
+
+    /// Override whisper.language (e.g. "es", or "auto")
+    #[arg(long)]
+    language: Option<String>,
+
+    /// Turn off behavior.auto_paste
+    #[arg(long)]
+    no_auto_paste: bool,
+
+    /// List available audio input devices, then exit
+    #[arg(long)]
+    list_devices: bool,
+
+    /// Print the effective config (defaults merged with the file, profile and
+    /// overrides) as TOML with secrets masked, then exit
+    #[arg(long)]
+    print_config: bool,
+
+    /// Print the built-in default config as TOML, then exit
+    #[arg(long)]
+    print_default_config: bool,
+
+    /// Print version, build and provider info, then exit
+    #[arg(short = 'V', long)]
+    version: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Transcribe an audio file with the daemon's provider and normalizers, then exit
+    Transcribe {
+        /// Audio file to transcribe
+        file: PathBuf,
+    },
 }
 
 #[derive(Clone)]
@@ -44,184 +102,862 @@ struct RecordingState {
     audio_recorder: Arc<Mutex<AudioStreamManager>>,
 }
 
+fn init_logging(args: &Args, writer: BoxMakeWriter) {
+    let log_level = if args.verbose { "debug" } else { "info" };
+    let env_filter = EnvFilter::try_new(log_level).unwrap_or_else(|_| EnvFilter::new("info"));
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(env_filter)
+        .with_writer(writer);
+
+    match args.log_format {
+        LogFormat::Text => subscriber.init(),
+        LogFormat::Json => subscriber.json().init(),
+    }
+}
+
+fn list_devices() -> Result<()> {
+    let devices = AudioStreamManager::list_input_devices()?;
+
+    if devices.is_empty() {
+        println!("No audio input devices found");
+        return Ok(());
+    }
+
+    println!("Audio input devices (use the name as [audio] device in config):");
+    for device in devices {
+        let marker = if device.is_default { "*" } else { " " };
+        println!(
+            "{} {} ({} Hz, {} channel(s))",
+            marker, device.name, device.sample_rate, device.channels
+        );
+    }
+    println!("\n* = current default input device");
+
+    Ok(())
+}
+
+/// Request a stop once the recording hits its maximum duration or, when
+/// `silence_duration` is set, once the input has been silent for that long
+fn spawn_recording_watcher(
+    monitor: RecordingMonitor,
+    recording: Arc<Mutex<bool>>,
+    tx: mpsc::Sender<ApiCommand>,
+    silence_duration: Option<Duration>,
+) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+
+            if !*recording.lock().await {
+                return;
+            }
+
+            if monitor.limit_reached() {
+                warn!("Maximum recording duration reached, stopping recording");
+                let _ = tx.send(ApiCommand::StopRecording).await;
+                return;
+            }
+
+            if let Some(limit) = silence_duration {
+                if monitor
+                    .silence_duration()
+                    .is_some_and(|silence| silence >= limit)
+                {
+                    info!("No speech for {}ms, stopping recording", limit.as_millis());
+                    let _ = tx.send(ApiCommand::StopRecording).await;
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/// Run an on_record_start/on_record_stop command in the background, so a slow
+/// command never delays the recording. Failures are only logged.
+fn spawn_hook(name: &'static str, command: Option<&str>) {
+    let Some(command) = command.map(str::to_string) else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let output = tokio::process::Command::new("sh")
+            .args(["-c", &command])
+            .stdin(std::process::Stdio::null())
+            .output()
+            .await;
+        match output {
+            Ok(output) if output.status.success() => debug!("{} command finished", name),
+            Ok(output) => warn!(
+                "{} command '{}' exited with {}: {}",
+                name,
+                command,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => warn!("Failed to run {} command '{}': {}", name, command, e),
+        }
+    });
+}
+
+/// Everything needed to turn a finished recording into delivered text, shared
+/// with the task it runs in
+struct Pipeline {
+    config: Arc<Mutex<Config>>,
+    transcription_service: TranscriptionService,
+    text_injector: RwLock<Arc<TextInjector>>,
+    clipboard: Mutex<ClipboardManager>,
+    indicator: RwLock<Arc<Indicator>>,
+    history: Arc<Mutex<TranscriptionHistory>>,
+    events: broadcast::Sender<ApiEvent>,
+}
+
+impl Pipeline {
+    /// The indicator built from the current config
+    async fn indicator(&self) -> Arc<Indicator> {
+        self.indicator.read().await.clone()
+    }
+
+    /// Apply a freshly loaded config. Everything is rebuilt before anything is
+    /// swapped, so an invalid config leaves the previous settings in place
+    async fn reload(&self, new_config: Config) -> Result<()> {
+        let text_injector = TextInjector::from_config(&new_config)?;
+        let indicator = build_indicator(&new_config)?;
+        self.transcription_service
+            .reload_normalizers(&new_config.normalizer)?;
+
+        *self.text_injector.write().await = Arc::new(text_injector);
+        *self.indicator.write().await = Arc::new(indicator);
+
+        let mut config = self.config.lock().await;
+        for setting in config.restart_required_changes(&new_config) {
+            warn!(
+                "Changed setting {} requires a restart to take effect",
+                setting
+            );
+        }
+        *config = new_config;
+
+        Ok(())
+    }
+
+    /// Transcribe the recording with any per-recording overrides, then copy,
+    /// paste or type the result
+    async fn run(&self, temp_file: TempAudioFile, overrides: RecordingOverrides) {
+        // Settings are read once so a reload never changes a run halfway through
+        let (behavior, min_confidence) = {
+            let config = self.config.lock().await;
+            (config.behavior.clone(), config.whisper.min_confidence)
+        };
+        let text_injector = self.text_injector.read().await.clone();
+        let indicator = self.indicator().await;
+        let mut clipboard = self.clipboard.lock().await;
+
+        // Show processing indicator
+        if let Err(e) = indicator.show_processing().await {
+            error!("Failed to show processing indicator: {}", e);
+        }
+
+        // Names the archived recording, when there is one
+        let mut transcript = None;
+
+        // Transcribe audio
+        let _ = self.events.send(ApiEvent::Transcribing);
+        let started = std::time::Instant::now();
+        match self
+            .transcription_service
+            .transcribe_in(
+                temp_file.path(),
+                overrides.language.as_deref(),
+                overrides.model.as_deref(),
+            )
+            .await
+        {
+            Ok(transcription) => {
+                let low_confidence = min_confidence > 0.0 && !transcription.text.is_empty() && {
+                    let audio_secs = audio::wav_duration_secs(temp_file.path()).unwrap_or(0.0);
+                    let confidence = transcription::confidence(&transcription, audio_secs);
+                    debug!("Transcription confidence {:.2}", confidence);
+                    confidence < min_confidence
+                };
+                let text = transcription.text;
+                if !text.is_empty() && !low_confidence {
+                    info!("Transcription successful: {} chars", text.len());
+                    let text = match &behavior.post_process_command {
+                        Some(command) => {
+                            let timeout = Duration::from_secs(behavior.post_process_timeout_secs);
+                            match transcription::post_process(command, &text, timeout).await {
+                                Ok(processed) => processed,
+                                Err(e) => {
+                                    warn!("{:#}, using the original text", e);
+                                    text
+                                }
+                            }
+                        }
+                        None => text,
+                    };
+                    transcript = Some(text.clone());
+
+                    let entry = HistoryEntry::new(
+                        text.clone(),
+                        self.transcription_service.provider_name(),
+                        started.elapsed().as_millis() as u64,
+                    )
+                    .with_segments(transcription.segments);
+
+                    // Written off the event loop; a failed write only costs the log line
+                    if let Some(path) = behavior.history_file_path() {
+                        let entry = entry.clone();
+                        let max_bytes = behavior.history_file_max_bytes;
+                        tokio::task::spawn_blocking(move || {
+                            if let Err(e) = history::append_to_file(&path, &entry, max_bytes) {
+                                warn!("Failed to append to history file: {:#}", e);
+                            }
+                        });
+                    }
+                    self.history.lock().await.push(entry);
+
+                    // clipboard_only leaves the text for the user to paste themselves
+                    let inject = behavior.auto_paste && !behavior.clipboard_only;
+
+                    // Copy to clipboard, unless pure typing mode keeps it untouched
+                    let use_clipboard = !inject || text_injector.uses_clipboard();
+                    if use_clipboard {
+                        if let Err(e) = clipboard.copy_with_wayland_fallback(&text).await {
+                            error!("Failed to copy to clipboard: {}", e);
+                        }
+                    }
+
+                    // Inject text or paste
+                    let mut copied_only = false;
+                    if inject {
+                        match text_injector.inject_text(&text).await {
+                            Ok(InjectionOutcome::CopiedOnly) => copied_only = true,
+                            Ok(outcome) => debug!("Text delivered: {:?}", outcome),
+                            Err(e) if text_injector.uses_clipboard() => {
+                                error!("Failed to inject text: {}, trying paste", e);
+                                if let Err(e) = text_injector.paste_from_clipboard().await {
+                                    // The transcript is still on the clipboard for a manual paste
+                                    warn!("Fallback paste failed: {}", e);
+                                    copied_only = true;
+                                }
+                            }
+                            Err(e) => {
+                                error!("Failed to type text: {}", e);
+                                let _ = indicator.show_error(&e.user_message()).await;
+                            }
+                        }
+
+                        // Only a pasted transcription is replaced by the old content;
+                        // otherwise the user still needs it on the clipboard
+                        if !copied_only {
+                            if let Err(e) = clipboard.restore_previous().await {
+                                warn!("Failed to restore previous clipboard: {}", e);
+                            }
+                        }
+                    }
+
+                    // Sent once the text is delivered, so /status stays
+                    // "processing" until it has been pasted or typed
+                    let _ = self
+                        .events
+                        .send(ApiEvent::TranscriptionComplete { text: text.clone() });
+
+                    // Show completion, or that the user still has to paste
+                    if copied_only {
+                        let _ = indicator
+                            .show_warning("Copied to clipboard but couldn't auto-paste")
+                            .await;
+                    } else if let Err(e) = indicator.show_complete(&text).await {
+                        error!("Failed to show completion indicator: {}", e);
+                    }
+                } else {
+                    let (reason, category) = if low_confidence {
+                        warn!("Discarding low confidence transcription: {}", text);
+                        ("Low confidence, discarded", "low_confidence")
+                    } else {
+                        ("No speech detected", "no_speech")
+                    };
+                    let _ = self.events.send(ApiEvent::Error {
+                        message: reason.to_string(),
+                        category,
+                    });
+                    let _ = indicator.show_error(reason).await;
+                }
+            }
+            Err(e) => {
+                error!("Transcription failed ({}): {}", e.category(), e);
+                let _ = self.events.send(ApiEvent::error(&e));
+                let _ = indicator.show_error(&e.user_message()).await;
+            }
+        }
+
+        if let Some(dir) = behavior.audio_archive_path() {
+            let unix_secs = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let name = audio::archive_file_name(unix_secs, transcript.as_deref());
+            match temp_file.persist_to(&dir.join(name)) {
+                Ok(path) => info!("Archived recording to {:?}", path),
+                Err(e) => warn!("Failed to archive recording: {:#}", e),
+            }
+        }
+    }
+}
+
+/// Readiness checks behind `GET /health`, run on a blocking thread
+fn health_checks(pipeline: &Pipeline) -> Vec<HealthCheck> {
+    let service = &pipeline.transcription_service;
+    let provider = HealthCheck::new(
+        "provider",
+        service.provider_available(),
+        service.provider_name(),
+    );
+
+    // An unknown configured device falls back to the default one, so any device will do
+    let audio_input = match AudioStreamManager::list_input_devices() {
+        Ok(devices) if devices.is_empty() => {
+            HealthCheck::new("audio_input", false, "No audio input devices found")
+        }
+        Ok(devices) => HealthCheck::new(
+            "audio_input",
+            true,
+            format!("{} input device(s)", devices.len()),
+        ),
+        Err(e) => HealthCheck::new("audio_input", false, e.to_string()),
+    };
+
+    let text_injector = pipeline.text_injector.blocking_read().clone();
+    let text_injection = HealthCheck::new(
+        "text_injection",
+        text_injector.is_available(),
+        text_injector.method_name(),
+    );
+
+    vec![provider, audio_input, text_injection]
+}
+
+/// Load the config file, then apply command-line overrides on top of it
+fn load_config(args: &Args) -> Result<Config> {
+    let profile = args.profile.as_deref();
+    let mut config = match &args.config {
+        Some(path) => Config::load_from(path, profile)?,
+        None => Config::load(profile)?,
+    };
+
+    if let Some(provider) = &args.provider {
+        config.whisper.provider = Some(provider.clone());
+    }
+    if let Some(model) = &args.model {
+        config.whisper.model = Some(model.clone());
+    }
+    if let Some(language) = &args.language {
+        config.whisper.language = language.clone();
+    }
+    if args.no_auto_paste {
+        config.behavior.auto_paste = false;
+    }
+
+    config.validate().context("Invalid command-line override")?;
+    Ok(config)
+}
+
+/// One-shot transcription through the same config and pipeline stages as the daemon
+async fn transcribe_file(args: &Args, file: &PathBuf) -> Result<()> {
+    if !file.exists() {
+        anyhow::bail!("Audio file not found: {:?}", file);
+    }
+
+    let config = load_config(args)?;
+    let temp_dir = config.behavior.ensure_temp_dir()?;
+    let transcription_service = TranscriptionService::from_config(&config, temp_dir)?;
+
+    let transcription = transcription_service.transcribe(file).await?;
+    println!("{}", transcription.text);
+    Ok(())
+}
+
+fn build_indicator(config: &Config) -> Result<Indicator> {
+    Ok(Indicator::from_config(&config.ui)
+        .with_audio_feedback(config.behavior.audio_feedback)
+        .with_notifications(config.ui.show_notifications)
+        .with_notification_backend(NotificationBackend::parse(&config.ui.notification_backend)?))
+}
+
+/// Turn SIGHUP into a config reload request for the main loop
+#[cfg(unix)]
+fn spawn_reload_listener(tx: mpsc::Sender<ApiCommand>) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sighup = match signal(SignalKind::hangup()) {
+        Ok(sighup) => sighup,
+        Err(e) => {
+            error!("Failed to listen for SIGHUP, config reload disabled: {}", e);
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        while sighup.recv().await.is_some() {
+            info!("SIGHUP received, reloading config");
+            if tx.send(ApiCommand::ReloadConfig).await.is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Keep the shared status in step with the events the main loop publishes
+fn spawn_status_tracker(
+    status: Arc<Mutex<StatusTracker>>,
+    mut events: broadcast::Receiver<ApiEvent>,
+) {
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => status.lock().await.apply(&event),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Status tracker missed {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    });
+}
+
+/// Signal waybar after every state change so a `custom` module with a matching
+/// `signal` re-runs its exec right away instead of waiting for its next poll
+fn spawn_waybar_signaller(
+    signal: u8,
+    error_timeout: Duration,
+    mut events: broadcast::Receiver<ApiEvent>,
+) {
+    let signal_arg: Arc<str> = Arc::from(format!("-RTMIN+{signal}"));
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                // An error clears itself after the timeout, so refresh again then
+                Ok(ApiEvent::Error { .. }) => {
+                    let signal_arg = signal_arg.clone();
+                    tokio::spawn(async move {
+                        tokio::time::sleep(error_timeout).await;
+                        signal_waybar(&signal_arg).await;
+                    });
+                }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+
+            signal_waybar(&signal_arg).await;
+        }
+    });
+}
+
+async fn signal_waybar(signal_arg: &str) {
+    match tokio::process::Command::new("pkill")
+        .args([signal_arg, "-x", "waybar"])
+        .status()
+        .await
+    {
+        // Exit status 1 just means waybar isn't running
+        Ok(status) if status.success() || status.code() == Some(1) => {}
+        Ok(status) => debug!("pkill {} waybar exited with {}", signal_arg, status),
+        Err(e) => debug!("Failed to signal waybar: {}", e),
+    }
+}
+
+/// Resolve once SIGINT (Ctrl-C) or SIGTERM is received
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
-    // Initialize logging
-    let log_level = if args.verbose { "debug" } else { "info" };
-    let env_filter = EnvFilter::try_new(log_level).unwrap_or_else(|_| EnvFilter::new("info"));
+    if args.version {
+        print!("{}", version::long_version());
+        return Ok(());
+    }
+
+    if args.list_devices {
+        return list_devices();
+    }
+
+    if args.print_default_config {
+        print!("{}", Config::default().to_toml()?);
+        return Ok(());
+    }
+
+    if args.print_config {
+        print!("{}", load_config(&args)?.redacted().to_toml()?);
+        return Ok(());
+    }
+
+    if let Some(Command::Transcribe { file }) = &args.command {
+        // Keep stdout for the transcription itself
+        init_logging(&args, BoxMakeWriter::new(std::io::stderr));
+        return transcribe_file(&args, file).await;
+    }
 
-    tracing_subscriber::fmt().with_env_filter(env_filter).init();
+    init_logging(&args, BoxMakeWriter::new(std::io::stdout));
 
     info!("Starting ChezWizper");
 
     // Load configuration
-    let config = if let Some(config_path) = args.config {
-        Config::load_from_path(config_path)?
-    } else {
-        Config::load()?
-    };
+    let config = load_config(&args)?;
+    info!(
+        "Active config profile: {}",
+        args.profile.as_deref().unwrap_or("default")
+    );
+    let temp_dir = config.behavior.ensure_temp_dir()?;
+    info!("Using temp directory: {:?}", temp_dir);
+
     // Initialize components
     let (tx, mut rx) = mpsc::channel::<ApiCommand>(10);
 
-    let audio_recorder = AudioStreamManager::new()?;
-
-    // Build whisper transcriber
-    let whisper = if let Some(provider) = &config.whisper.provider {
-        let provider_config = whisper::ProviderConfig {
-            model: Some(config.whisper.model.clone()),
-            model_path: config.whisper.model_path.clone(),
-            language: Some(config.whisper.language.clone()),
-            command_path: config.whisper.command_path.clone(),
-            api_endpoint: config.whisper.api_endpoint.clone(),
-            api_key: config.whisper.api_key.clone(),
-        };
-        WhisperTranscriber::with_provider(provider, provider_config)?
-    } else {
-        // Auto-detect provider when no provider specified
-        let provider_config = whisper::ProviderConfig {
-            model: Some(config.whisper.model.clone()),
-            model_path: config.whisper.model_path.clone(),
-            language: Some(config.whisper.language.clone()),
-            command_path: config.whisper.command_path.clone(),
-            api_endpoint: config.whisper.api_endpoint.clone(),
-            api_key: config.whisper.api_key.clone(),
-        };
-        WhisperTranscriber::auto_detect(provider_config)?
-    };
+    let command_tx = tx.clone();
 
-    // Compose transcription service with whisper and normalizer
-    let transcription_service = TranscriptionService::new(whisper)?;
+    // Send errors only mean nobody is subscribed to /events right now
+    let (events, _) = broadcast::channel::<ApiEvent>(32);
 
-    let text_injector = TextInjector::new(Some(&config.wayland.input_method))?;
-    let mut clipboard = ClipboardManager::new()?.with_preserve(config.behavior.preserve_clipboard);
+    let audio_recorder = AudioStreamManager::from_config(&config)?;
 
-    let indicator =
-        Indicator::from_config(&config.ui).with_audio_feedback(config.behavior.audio_feedback);
+    let transcription_service = TranscriptionService::from_config(&config, temp_dir.clone())?;
+
+    let text_injector = TextInjector::from_config(&config)?;
+    let clipboard = ClipboardManager::new(ClipboardTool::parse(&config.behavior.clipboard_tool)?)?
+        .with_preserve(config.behavior.preserve_clipboard);
+    let indicator = build_indicator(&config)?;
 
     let recording_flag = Arc::new(Mutex::new(false));
+    let status = Arc::new(Mutex::new(StatusTracker::default()));
+    spawn_status_tracker(status.clone(), events.subscribe());
+    let history = Arc::new(Mutex::new(TranscriptionHistory::new(
+        config.behavior.history_size,
+    )));
+    let level_monitor = audio_recorder.monitor();
     let state = RecordingState {
         recording: recording_flag.clone(),
         audio_recorder: Arc::new(Mutex::new(audio_recorder)),
     };
 
     // Create and start API server
-    let api_server = ApiServer::new(tx, recording_flag.clone(), &config);
+    let api_server = ApiServer::new(
+        tx,
+        recording_flag.clone(),
+        status,
+        history.clone(),
+        events.clone(),
+        &config,
+    )
+    .with_level_monitor(level_monitor);
+
+    if let Some(signal) = config.ui.waybar_signal {
+        info!("Signalling waybar with RTMIN+{} on state changes", signal);
+        spawn_waybar_signaller(
+            signal,
+            Duration::from_secs(config.ui.waybar.error_timeout_secs),
+            events.subscribe(),
+        );
+    }
+
+    // Print instructions for Hyprland setup
+    info!("ChezWizper is ready!");
+    info!("Add this to your Hyprland config:");
+    info!("bindd = SUPER, R, ChezWizper, exec, curl -X POST http://127.0.0.1:3737/toggle");
+    info!("Or test manually: curl -X POST http://127.0.0.1:3737/toggle");
+
+    #[cfg(unix)]
+    spawn_reload_listener(command_tx.clone());
+
+    // Reloads replace the shared config; each loop iteration works on a snapshot
+    let shared_config = Arc::new(Mutex::new(config));
+    let pipeline = Arc::new(Pipeline {
+        config: shared_config.clone(),
+        transcription_service,
+        text_injector: RwLock::new(Arc::new(text_injector)),
+        clipboard: Mutex::new(clipboard),
+        indicator: RwLock::new(Arc::new(indicator)),
+        history: history.clone(),
+        events: events.clone(),
+    });
+    let mut pipeline_task: Option<JoinHandle<()>> = None;
+    // Requested when the current recording was started
+    let mut recording_overrides = RecordingOverrides::default();
+    // When recording last started or stopped, for toggle_debounce_ms
+    let mut last_state_change: Option<std::time::Instant> = None;
 
     // Start API server in background
+    let health_pipeline = pipeline.clone();
+    let diagnostics_pipeline = pipeline.clone();
+    let api_server = api_server
+        .with_provider_name(pipeline.transcription_service.provider_name())
+        .with_model(pipeline.transcription_service.model())
+        .with_health_probe(Arc::new(move || health_checks(&health_pipeline)))
+        .with_diagnostics_probe(Arc::new(move || {
+            let text_injector = diagnostics_pipeline.text_injector.blocking_read().clone();
+            serde_json::to_value(text_injector.diagnostics()).unwrap_or_default()
+        }));
     tokio::spawn(async move {
         if let Err(e) = api_server.start().await {
             error!("API server failed: {}", e);
         }
     });
 
-    // Print instructions for Hyprland setup
-    info!("ChezWizper is ready!");
-    info!("Add this to your Hyprland config:");
-    info!("bindd = SUPER, R, ChezWizper, exec, curl -X POST http://127.0.0.1:3737/toggle");
-    info!("Or test manually: curl -X POST http://127.0.0.1:3737/toggle");
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    // Main event loop. A shutdown signal is handled as a final stop request so
+    // an in-progress recording is flushed to disk before exiting.
+    loop {
+        let (command, shutting_down) = tokio::select! {
+            command = rx.recv() => match command {
+                Some(command) => (command, false),
+                None => break,
+            },
+            _ = &mut shutdown => {
+                info!("Shutdown signal received");
+                (ApiCommand::StopRecording, true)
+            }
+        };
 
-    // Main event loop
-    while let Some(command) = rx.recv().await {
+        let config = shared_config.lock().await.clone();
+        let indicator = pipeline.indicator().await;
+
+        let mut recording = state.recording.lock().await;
         match command {
-            ApiCommand::ToggleRecording => {
-                let mut recording = state.recording.lock().await;
+            ApiCommand::ReloadConfig => {
+                match load_config(&args) {
+                    Ok(new_config) => match pipeline.reload(new_config).await {
+                        Ok(()) => info!("Config reloaded"),
+                        Err(e) => error!("Invalid config, keeping previous settings: {}", e),
+                    },
+                    Err(e) => error!("Failed to reload config: {}", e),
+                }
+                continue;
+            }
+            ApiCommand::ToggleRecording(overrides) => {
+                let debounce = Duration::from_millis(config.behavior.toggle_debounce_ms);
+                if let Some(elapsed) = last_state_change
+                    .map(|at| at.elapsed())
+                    .filter(|elapsed| *elapsed < debounce)
+                {
+                    debug!(
+                        "Ignoring toggle {}ms after the last state change",
+                        elapsed.as_millis()
+                    );
+                    continue;
+                }
+                // Only one recording is transcribed at a time
+                if !*recording && pipeline_task.as_ref().is_some_and(|t| !t.is_finished()) {
+                    warn!("Ignoring toggle, still processing the previous recording");
+                    let _ = indicator
+                        .show_warning("Still processing the previous recording")
+                        .await;
+                    continue;
+                }
+                if !*recording {
+                    recording_overrides = overrides;
+                }
                 *recording = !*recording;
-
+            }
+            ApiCommand::StopRecording => {
+                // Stop requests (e.g. from silence detection) are no-ops when idle
+                if !*recording {
+                    if shutting_down {
+                        break;
+                    }
+                    continue;
+                }
+                *recording = false;
+            }
+            ApiCommand::CancelRecording => {
                 if *recording {
-                    // Start recording
-                    info!("Starting recording");
+                    *recording = false;
+                    spawn_hook("on_record_stop", config.behavior.on_record_stop.as_deref());
+                    let audio_recorder = state.audio_recorder.lock().await;
+                    match audio_recorder.cancel_recording().await {
+                        Ok(()) => {
+                            let _ = events.send(ApiEvent::RecordingCancelled);
+                            let _ = indicator.show_cancelled().await;
+                        }
+                        Err(e) => error!("Failed to cancel recording: {}", e),
+                    }
+                }
+                continue;
+            }
+            ApiCommand::PauseRecording => {
+                if *recording {
+                    let audio_recorder = state.audio_recorder.lock().await;
+                    match audio_recorder.pause_recording().await {
+                        Ok(()) => {
+                            let _ = events.send(ApiEvent::RecordingPaused);
+                            let _ = indicator.show_paused().await;
+                        }
+                        Err(e) => warn!("Can't pause: {}", e),
+                    }
+                }
+                continue;
+            }
+            ApiCommand::ResumeRecording => {
+                if *recording {
+                    let audio_recorder = state.audio_recorder.lock().await;
+                    match audio_recorder.resume_recording().await {
+                        Ok(()) => {
+                            let _ = events.send(ApiEvent::RecordingResumed);
+                            let _ = indicator.show_recording().await;
+                        }
+                        Err(e) => warn!("Can't resume: {}", e),
+                    }
+                }
+                continue;
+            }
+        }
+
+        last_state_change = Some(std::time::Instant::now());
+
+        // Without transcribe_on_shutdown the flushed recording is kept for the user
+        let transcribe = !shutting_down || config.behavior.transcribe_on_shutdown;
 
-                    if let Err(e) = indicator.show_recording().await {
-                        error!("Failed to show recording indicator: {}", e);
+        if *recording {
+            // Start recording
+            info!("Starting recording");
+
+            if let Err(e) = indicator.show_recording().await {
+                error!("Failed to show recording indicator: {}", e);
+            }
+            spawn_hook(
+                "on_record_start",
+                config.behavior.on_record_start.as_deref(),
+            );
+
+            let audio_recorder = state.audio_recorder.lock().await;
+            if let Err(e) = audio_recorder.start_recording().await {
+                error!("Failed to start recording: {}", e);
+                *recording = false;
+                spawn_hook("on_record_stop", config.behavior.on_record_stop.as_deref());
+                let _ = events.send(ApiEvent::error(&e));
+                let _ = indicator.show_error(&e.user_message()).await;
+                continue;
+            }
+            let _ = events.send(ApiEvent::RecordingStarted);
+
+            let silence_duration = config
+                .behavior
+                .auto_stop_on_silence
+                .then(|| Duration::from_millis(config.behavior.silence_duration_ms));
+            spawn_recording_watcher(
+                audio_recorder.monitor(),
+                state.recording.clone(),
+                command_tx.clone(),
+                silence_duration,
+            );
+        } else {
+            // Stop recording and process
+            info!("Stopping recording");
+
+            let audio_recorder = state.audio_recorder.lock().await;
+            // Removed on every exit path below when delete_audio_files is set
+            let temp_file = TempAudioFile::new(
+                temp_dir.join(format!(
+                    "chezwizper_{}.wav",
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs()
+                )),
+                config.behavior.delete_audio_files && transcribe,
+            );
+            let temp_path = temp_file.path().clone();
+
+            let stopped = audio_recorder.stop_recording(temp_path.clone()).await;
+            spawn_hook("on_record_stop", config.behavior.on_record_stop.as_deref());
+            match stopped {
+                Ok(_) => {
+                    let _ = events.send(ApiEvent::RecordingStopped);
+
+                    if !transcribe {
+                        info!("Recording saved to {:?} before shutdown", temp_path);
+                        break;
                     }
 
-                    let audio_recorder = state.audio_recorder.lock().await;
-                    if let Err(e) = audio_recorder.start_recording().await {
-                        error!("Failed to start recording: {}", e);
-                        *recording = false;
+                    if audio_recorder.monitor().clipping_detected() {
                         let _ = indicator
-                            .show_error(&format!("Recording failed: {e}"))
+                            .show_warning("Recording clipped, lower your mic volume")
                             .await;
-                        continue;
                     }
-                } else {
-                    // Stop recording and process
-                    info!("Stopping recording");
-
-                    let audio_recorder = state.audio_recorder.lock().await;
-                    let temp_path = PathBuf::from(format!(
-                        "/tmp/chezwizper_{}.wav",
-                        std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs()
-                    ));
-
-                    match audio_recorder.stop_recording(temp_path.clone()).await {
-                        Ok(_) => {
-                            // Show processing indicator
-                            if let Err(e) = indicator.show_processing().await {
-                                error!("Failed to show processing indicator: {}", e);
-                            }
 
-                            // Transcribe audio
-                            match transcription_service.transcribe(&temp_path).await {
-                                Ok(text) => {
-                                    if !text.is_empty() {
-                                        info!("Transcription successful: {} chars", text.len());
-
-                                        // Copy to clipboard
-                                        if let Err(e) =
-                                            clipboard.copy_with_wayland_fallback(&text).await
-                                        {
-                                            error!("Failed to copy to clipboard: {}", e);
-                                        }
-
-                                        // Inject text or paste
-                                        if config.behavior.auto_paste {
-                                            if let Err(e) = text_injector.inject_text(&text).await {
-                                                error!(
-                                                    "Failed to inject text: {}, trying paste",
-                                                    e
-                                                );
-                                                let _ = text_injector.paste_from_clipboard().await;
-                                            }
-                                        }
-
-                                        // Show completion
-                                        if let Err(e) = indicator.show_complete(&text).await {
-                                            error!("Failed to show completion indicator: {}", e);
-                                        }
-                                    } else {
-                                        let _ = indicator.show_error("No speech detected").await;
-                                    }
-                                }
-                                Err(e) => {
-                                    error!("Transcription failed: {}", e);
-                                    let _ = indicator
-                                        .show_error(&format!("Transcription failed: {e}"))
-                                        .await;
-                                }
-                            }
-
-                            // Clean up audio file
-                            if config.behavior.delete_audio_files {
-                                let _ = std::fs::remove_file(&temp_path);
-                            }
-                        }
-                        Err(e) => {
-                            error!("Failed to stop recording: {}", e);
-                            let _ = indicator
-                                .show_error(&format!("Failed to save audio: {e}"))
-                                .await;
-                        }
+                    if audio_recorder.monitor().limit_reached() {
+                        let _ = indicator
+                            .show_warning(&format!(
+                                "Recording limit of {}s reached",
+                                config.behavior.max_recording_secs
+                            ))
+                            .await;
                     }
+
+                    // Transcribe in the background so the loop keeps handling
+                    // commands; new recordings are refused until this one is delivered
+                    let pipeline = pipeline.clone();
+                    let overrides = std::mem::take(&mut recording_overrides);
+                    pipeline_task = Some(tokio::spawn(async move {
+                        pipeline.run(temp_file, overrides).await;
+                    }));
+                }
+                Err(e @ ChezWizperError::TooShort(_)) => {
+                    info!("Skipping transcription, {}", e);
+                    let _ = events.send(ApiEvent::error(&e));
+                    let _ = indicator.show_warning(&e.user_message()).await;
+                }
+                Err(e) => {
+                    error!("Failed to stop recording: {}", e);
+                    let _ = events.send(ApiEvent::error(&e));
+                    let _ = indicator.show_error(&e.user_message()).await;
                 }
             }
         }
+
+        if shutting_down {
+            break;
+        }
+    }
+
+    if let Some(task) = pipeline_task {
+        if !task.is_finished() {
+            info!("Waiting for the transcription in progress to finish");
+        }
+        if let Err(e) = task.await {
+            error!("Transcription task failed: {}", e);
+        }
     }
 
+    info!("ChezWizper stopped");
     Ok(())
 }
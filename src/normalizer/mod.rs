@@ -1,7 +1,10 @@
-use anyhow::Result;
-use regex::Regex;
+use anyhow::{Context, Result};
+use regex::{NoExpand, Regex, RegexBuilder};
+use std::collections::{BTreeMap, HashMap};
 use tracing::{debug, info};
 
+use crate::config::ReplacementRule;
+
 /// Trait for normalizing transcription output from various whisper implementations
 pub trait TranscriptionNormalizer: Send + Sync {
     /// Normalize the raw transcription output
@@ -88,6 +91,338 @@ impl TranscriptionNormalizer for OpenAIWhisperNormalizer {
     }
 }
 
+/// Strips the annotations whisper emits for non-speech audio, such as
+/// `[BLANK_AUDIO]`, `[Laughter]` or `(upbeat music)`, so they are never typed.
+/// Any short bracketed text counts; parentheses only around known sound words,
+/// since people do dictate parenthetical remarks.
+pub struct AnnotationNormalizer {
+    marker_regex: Regex,
+}
+
+impl AnnotationNormalizer {
+    pub fn new() -> Result<Self> {
+        let marker_regex = Regex::new(
+            r"(?i)(\[[^\]\n]{1,40}\]|\(\s*([a-z]+ )*(blank[_ ]audio|silence|no[_ ]speech|inaudible|music|noise|laughter|laughing|laughs|applause|coughing|sighs|static)\s*\))\s*",
+        )?;
+
+        Ok(Self { marker_regex })
+    }
+}
+
+impl TranscriptionNormalizer for AnnotationNormalizer {
+    fn normalize(&self, raw_output: &str) -> String {
+        if !self.marker_regex.is_match(raw_output) {
+            return raw_output.to_string();
+        }
+        self.marker_regex
+            .replace_all(raw_output, "")
+            .trim()
+            .to_string()
+    }
+
+    fn name(&self) -> &'static str {
+        "AnnotationNormalizer"
+    }
+}
+
+/// Longest phrase, in words, checked for hallucinated repetition
+const MAX_REPEAT_PHRASE_WORDS: usize = 12;
+
+/// Collapses the loops Whisper hallucinates on silent or noisy audio, such as
+/// "Thank you. Thank you. Thank you. ...". A phrase repeated back to back more
+/// than `max_repeats` times is kept once; shorter runs like "no no no" stay.
+pub struct RepetitionNormalizer {
+    max_repeats: usize,
+}
+
+impl RepetitionNormalizer {
+    pub fn new(max_repeats: usize) -> Self {
+        Self { max_repeats }
+    }
+
+    /// Number of back-to-back copies of the phrase `keys[start..start + len]`
+    fn run_length(keys: &[String], start: usize, len: usize) -> usize {
+        let phrase = &keys[start..start + len];
+        keys[start..]
+            .chunks_exact(len)
+            .take_while(|chunk| *chunk == phrase)
+            .count()
+    }
+}
+
+impl TranscriptionNormalizer for RepetitionNormalizer {
+    fn normalize(&self, raw_output: &str) -> String {
+        let words: Vec<&str> = raw_output.split_whitespace().collect();
+        // Compared without case or trailing punctuation, so "Thank you." matches "thank you,"
+        let keys: Vec<String> = words
+            .iter()
+            .map(|word| {
+                word.trim_end_matches(|c: char| c.is_ascii_punctuation())
+                    .to_lowercase()
+            })
+            .collect();
+
+        let mut kept = Vec::with_capacity(words.len());
+        let mut collapsed = false;
+        let mut i = 0;
+        while i < words.len() {
+            let longest = MAX_REPEAT_PHRASE_WORDS.min((words.len() - i) / (self.max_repeats + 1));
+            let repeat = (1..=longest)
+                .map(|len| (len, Self::run_length(&keys, i, len)))
+                .find(|&(_, count)| count > self.max_repeats);
+
+            match repeat {
+                Some((len, count)) => {
+                    debug!("Collapsed {} repeats of a {}-word phrase", count, len);
+                    kept.extend_from_slice(&words[i..i + len]);
+                    i += len * count;
+                    collapsed = true;
+                }
+                None => {
+                    kept.push(words[i]);
+                    i += 1;
+                }
+            }
+        }
+
+        if !collapsed {
+            return raw_output.to_string();
+        }
+        kept.join(" ")
+    }
+
+    fn name(&self) -> &'static str {
+        "RepetitionNormalizer"
+    }
+}
+
+/// User-defined replacements from the `[normalizer]` config, applied in order
+pub struct CustomReplacementNormalizer {
+    rules: Vec<(Regex, String, bool)>,
+}
+
+impl CustomReplacementNormalizer {
+    pub fn new(rules: &[ReplacementRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let pattern = if rule.regex {
+                    rule.pattern.clone()
+                } else {
+                    regex::escape(&rule.pattern)
+                };
+                let regex = RegexBuilder::new(&pattern)
+                    .case_insensitive(rule.case_insensitive)
+                    .build()
+                    .with_context(|| format!("Invalid replacement pattern '{}'", rule.pattern))?;
+                Ok((regex, rule.replacement.clone(), rule.regex))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { rules })
+    }
+}
+
+impl TranscriptionNormalizer for CustomReplacementNormalizer {
+    fn normalize(&self, raw_output: &str) -> String {
+        let mut text = raw_output.to_string();
+
+        for (regex, replacement, expand) in &self.rules {
+            // Literal rules must not interpret `$` in the replacement
+            let replaced = if *expand {
+                regex.replace_all(&text, replacement.as_str())
+            } else {
+                regex.replace_all(&text, NoExpand(replacement))
+            };
+            text = replaced.into_owned();
+        }
+
+        text
+    }
+
+    fn name(&self) -> &'static str {
+        "CustomReplacementNormalizer"
+    }
+}
+
+/// Built-in English spoken commands, used when no `command_words` are configured
+const DEFAULT_COMMAND_WORDS: &[(&str, &str)] = &[
+    ("new line", "\n"),
+    ("newline", "\n"),
+    ("new paragraph", "\n\n"),
+    ("period", "."),
+    ("full stop", "."),
+    ("comma", ","),
+    ("question mark", "?"),
+    ("exclamation mark", "!"),
+    ("exclamation point", "!"),
+    ("colon", ":"),
+    ("semicolon", ";"),
+];
+
+/// Converts spoken commands ("new line", "comma", ...) into the characters they name
+pub struct SpokenCommandNormalizer {
+    command_regex: Regex,
+    commands: HashMap<String, String>,
+}
+
+impl SpokenCommandNormalizer {
+    /// Build from a phrase -> text map, or the English defaults when it is empty
+    pub fn new(command_words: &BTreeMap<String, String>) -> Result<Self> {
+        let commands: HashMap<String, String> = if command_words.is_empty() {
+            DEFAULT_COMMAND_WORDS
+                .iter()
+                .map(|(phrase, text)| (phrase.to_string(), text.to_string()))
+                .collect()
+        } else {
+            command_words
+                .iter()
+                .map(|(phrase, text)| (canonical_phrase(phrase), text.clone()))
+                .collect()
+        };
+
+        // Longest phrases first so "new paragraph" wins over a shorter overlapping command
+        let mut phrases: Vec<&String> = commands.keys().collect();
+        phrases.sort_by_key(|phrase| std::cmp::Reverse(phrase.len()));
+        let alternation = phrases
+            .iter()
+            .map(|phrase| {
+                phrase
+                    .split(' ')
+                    .map(regex::escape)
+                    .collect::<Vec<_>>()
+                    .join(r"\s+")
+            })
+            .collect::<Vec<_>>()
+            .join("|");
+
+        // Whole words only, swallowing surrounding spaces and any comma or period
+        // the model added after the command itself
+        let command_regex = RegexBuilder::new(&format!(r"[ \t]*\b({alternation})\b[.,]?[ \t]*"))
+            .case_insensitive(true)
+            .build()
+            .context("Invalid spoken command words")?;
+
+        Ok(Self {
+            command_regex,
+            commands,
+        })
+    }
+}
+
+impl TranscriptionNormalizer for SpokenCommandNormalizer {
+    fn normalize(&self, raw_output: &str) -> String {
+        let replaced = self
+            .command_regex
+            .replace_all(raw_output, |caps: &regex::Captures| {
+                let text = self
+                    .commands
+                    .get(&canonical_phrase(&caps[1]))
+                    .cloned()
+                    .unwrap_or_default();
+                // Punctuation is followed by a space; line breaks stand alone
+                if text.ends_with('\n') {
+                    text
+                } else {
+                    format!("{text} ")
+                }
+            });
+
+        replaced
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+
+    fn name(&self) -> &'static str {
+        "SpokenCommandNormalizer"
+    }
+}
+
+/// Lowercase with single spaces, so "New   Line" looks up as "new line"
+fn canonical_phrase(phrase: &str) -> String {
+    phrase
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Capitalizes sentence starts and the word "I", and ends the text with a period
+pub struct CapitalizationNormalizer {
+    pronoun_regex: Regex,
+}
+
+impl CapitalizationNormalizer {
+    pub fn new() -> Result<Self> {
+        // A lone "i", plus an optional ".x" so abbreviations like "i.e." can be skipped
+        let pronoun_regex = Regex::new(r"\bi\b(\.\w)?")?;
+
+        Ok(Self { pronoun_regex })
+    }
+}
+
+impl TranscriptionNormalizer for CapitalizationNormalizer {
+    fn normalize(&self, raw_output: &str) -> String {
+        let text = self
+            .pronoun_regex
+            .replace_all(raw_output, |caps: &regex::Captures| {
+                if caps.get(1).is_some() {
+                    caps[0].to_string()
+                } else {
+                    "I".to_string()
+                }
+            });
+
+        let mut result = String::with_capacity(text.len() + 1);
+        let mut capitalize_next = true;
+        let mut after_terminal = false;
+
+        for c in text.chars() {
+            if capitalize_next && c.is_alphanumeric() {
+                result.extend(c.to_uppercase());
+                capitalize_next = false;
+                after_terminal = false;
+                continue;
+            }
+
+            if matches!(c, '.' | '?' | '!') {
+                after_terminal = true;
+            } else if c == '\n' || (after_terminal && c.is_whitespace()) {
+                // Only punctuation followed by whitespace ends a sentence, so "3.5" doesn't
+                capitalize_next = true;
+            } else if !c.is_whitespace() {
+                after_terminal = false;
+            }
+            result.push(c);
+        }
+
+        let trimmed_len = result.trim_end().len();
+        result.truncate(trimmed_len);
+        if result.ends_with(|c: char| c.is_alphanumeric()) {
+            result.push('.');
+        }
+
+        result
+    }
+
+    fn name(&self) -> &'static str {
+        "CapitalizationNormalizer"
+    }
+}
+
+/// How a provider formats its raw output, which decides the first normalizer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStyle {
+    /// Clean text, as returned by the APIs and the openai-whisper CLI
+    Plain,
+    /// whisper.cpp CLI output, whose lines may start with `[start --> end]` timestamps
+    Timestamped,
+}
+
 /// Enum to hold different normalizer types
 pub enum Normalizer {
     WhisperCpp(WhisperCppNormalizer),
@@ -95,28 +430,33 @@ pub enum Normalizer {
 }
 
 impl Normalizer {
-    /// Create a normalizer based on whether this is OpenAI whisper or whisper.cpp
-    pub fn create(is_openai_whisper: bool) -> Result<Self> {
-        if is_openai_whisper {
-            info!("Creating OpenAI Whisper normalizer");
-            Ok(Normalizer::OpenAIWhisper(OpenAIWhisperNormalizer::new()))
-        } else {
-            info!("Creating whisper.cpp normalizer");
-            Ok(Normalizer::WhisperCpp(WhisperCppNormalizer::new()?))
+    /// Create the normalizer that cleans up output in the given style
+    pub fn for_style(style: OutputStyle) -> Result<Self> {
+        match style {
+            OutputStyle::Plain => {
+                info!("Creating plain text normalizer");
+                Ok(Normalizer::OpenAIWhisper(OpenAIWhisperNormalizer::new()))
+            }
+            OutputStyle::Timestamped => {
+                info!("Creating whisper.cpp normalizer");
+                Ok(Normalizer::WhisperCpp(WhisperCppNormalizer::new()?))
+            }
         }
     }
+}
 
-    /// Run normalization using the appropriate normalizer
-    pub fn run(&self, raw_output: &str) -> String {
+impl TranscriptionNormalizer for Normalizer {
+    fn normalize(&self, raw_output: &str) -> String {
         match self {
-            Normalizer::WhisperCpp(n) => {
-                debug!("Running {}", n.name());
-                n.normalize(raw_output)
-            }
-            Normalizer::OpenAIWhisper(n) => {
-                debug!("Running {}", n.name());
-                n.normalize(raw_output)
-            }
+            Normalizer::WhisperCpp(n) => n.normalize(raw_output),
+            Normalizer::OpenAIWhisper(n) => n.normalize(raw_output),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Normalizer::WhisperCpp(n) => n.name(),
+            Normalizer::OpenAIWhisper(n) => n.name(),
         }
     }
 }
@@ -145,6 +485,160 @@ mod tests {
         assert_eq!(normalizer.normalize(input), expected);
     }
 
+    fn rule(
+        pattern: &str,
+        replacement: &str,
+        regex: bool,
+        case_insensitive: bool,
+    ) -> ReplacementRule {
+        ReplacementRule {
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            regex,
+            case_insensitive,
+        }
+    }
+
+    #[test]
+    fn test_silence_markers_removed() {
+        let normalizer = AnnotationNormalizer::new().unwrap();
+        for marker in [
+            "[BLANK_AUDIO]",
+            " [BLANK_AUDIO]\n",
+            "[blank audio]",
+            "(silence)",
+            "[ Silence ]",
+            "[NO_SPEECH]",
+            "(inaudible)",
+            "[Music]",
+            "(noise) [BLANK_AUDIO]",
+        ] {
+            assert_eq!(normalizer.normalize(marker), "", "{marker:?}");
+        }
+
+        assert_eq!(
+            normalizer.normalize("[BLANK_AUDIO] hello there"),
+            "hello there"
+        );
+        assert_eq!(
+            normalizer.normalize("keep (parentheses) intact"),
+            "keep (parentheses) intact"
+        );
+    }
+
+    #[test]
+    fn test_annotations_mixed_with_speech() {
+        let normalizer = AnnotationNormalizer::new().unwrap();
+        assert_eq!(
+            normalizer
+                .normalize("(upbeat music) Welcome back. [Laughter] So anyway [inaudible] done."),
+            "Welcome back. So anyway done."
+        );
+        assert_eq!(
+            normalizer.normalize("Meet at noon (or later) [SOUND]"),
+            "Meet at noon (or later)"
+        );
+    }
+
+    #[test]
+    fn test_repetition_collapses_hallucination_loop() {
+        let normalizer = RepetitionNormalizer::new(3);
+        let hallucinated = "Okay, send it tomorrow. Thank you. Thank you. Thank you. Thank you. \
+            Thank you. Thank you. Thank you. Thank you.";
+        assert_eq!(
+            normalizer.normalize(hallucinated),
+            "Okay, send it tomorrow. Thank you."
+        );
+
+        let looped = "I'll see you next time, I'll see you next time, I'll see you next time, \
+            I'll see you next time, I'll see you next time.";
+        assert_eq!(normalizer.normalize(looped), "I'll see you next time,");
+    }
+
+    #[test]
+    fn test_repetition_keeps_legitimate_repeats() {
+        let normalizer = RepetitionNormalizer::new(3);
+        for text in [
+            "no no no, that's wrong",
+            "It was very, very, very good.",
+            "Thank you. Thank you.\nNew line kept",
+        ] {
+            assert_eq!(normalizer.normalize(text), text);
+        }
+        assert_eq!(normalizer.normalize("no no no no no"), "no");
+    }
+
+    #[test]
+    fn test_custom_replacements() {
+        let normalizer = CustomReplacementNormalizer::new(&[
+            rule("chez whisper", "ChezWizper", false, true),
+            rule("open paren ", "(", false, false),
+            rule(r" close paren", ")", false, false),
+            rule(r"(\d+) dollars", "$$$1", true, false),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            normalizer.normalize("Chez Whisper costs open paren about 5 dollars close paren"),
+            "ChezWizper costs (about $5)"
+        );
+    }
+
+    #[test]
+    fn test_custom_replacements_in_order_and_literal() {
+        let normalizer = CustomReplacementNormalizer::new(&[
+            rule("a.b", "$1", false, false),
+            rule("$1", "x", false, false),
+        ])
+        .unwrap();
+
+        // The literal rule doesn't treat "." as a wildcard, and the second rule sees the first's output
+        assert_eq!(normalizer.normalize("a.b axb"), "x axb");
+        assert!(CustomReplacementNormalizer::new(&[rule("(", "", true, false)]).is_err());
+    }
+
+    #[test]
+    fn test_spoken_commands() {
+        let normalizer = SpokenCommandNormalizer::new(&BTreeMap::new()).unwrap();
+
+        assert_eq!(normalizer.normalize("hello new line world"), "hello\nworld");
+        assert_eq!(
+            normalizer.normalize("Hello comma world period New paragraph. Next question mark"),
+            "Hello, world.\n\nNext?"
+        );
+        // Commands inside other words are left alone
+        assert_eq!(
+            normalizer.normalize("the commander reads periodicals"),
+            "the commander reads periodicals"
+        );
+    }
+
+    #[test]
+    fn test_spoken_commands_custom_words() {
+        let words = BTreeMap::from([("neue zeile".to_string(), "\n".to_string())]);
+        let normalizer = SpokenCommandNormalizer::new(&words).unwrap();
+
+        assert_eq!(normalizer.normalize("hallo Neue Zeile welt"), "hallo\nwelt");
+        assert_eq!(normalizer.normalize("hello new line"), "hello new line");
+    }
+
+    #[test]
+    fn test_capitalization() {
+        let normalizer = CapitalizationNormalizer::new().unwrap();
+
+        let once = normalizer.normalize(
+            "hello world. how are you? i think i'm fine\nversion 3.5 is new, i.e., better",
+        );
+        assert_eq!(
+            once,
+            "Hello world. How are you? I think I'm fine\nVersion 3.5 is new, i.e., better."
+        );
+        // Running it again changes nothing
+        assert_eq!(normalizer.normalize(&once), once);
+        assert_eq!(normalizer.normalize("done!"), "Done!");
+        assert_eq!(normalizer.normalize(""), "");
+    }
+
     #[test]
     fn test_openai_whisper_normalizer() {
         let normalizer = OpenAIWhisperNormalizer::new();
@@ -154,4 +648,16 @@ mod tests {
 
         assert_eq!(normalizer.normalize(input), expected);
     }
+
+    #[test]
+    fn test_normalizer_for_style() {
+        let input = "[00:00:00.000 --> 00:00:01.000] Hello\n[00:00:01.000 --> 00:00:02.000] there";
+
+        let timestamped = Normalizer::for_style(OutputStyle::Timestamped).unwrap();
+        assert_eq!(timestamped.normalize(input), "Hello there");
+
+        // Plain output is only trimmed, line breaks included
+        let plain = Normalizer::for_style(OutputStyle::Plain).unwrap();
+        assert_eq!(plain.normalize(" Hello\nthere "), "Hello\nthere");
+    }
 }
@@ -0,0 +1,59 @@
+use anyhow::{Context, Result};
+use enigo::{Button, Direction, Enigo, Keyboard, Mouse, Settings};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::PasteShortcut;
+
+/// Types and pastes through the enigo library, without external tools
+pub struct EnigoInjector {
+    enigo: Mutex<Enigo>,
+}
+
+impl EnigoInjector {
+    pub fn new() -> Result<Self> {
+        let enigo = Enigo::new(&Settings::default()).context("Failed to initialize enigo")?;
+        Ok(Self {
+            enigo: Mutex::new(enigo),
+        })
+    }
+
+    pub fn type_text(&self, text: &str, delay_ms: u64) -> Result<()> {
+        let mut enigo = self.enigo.lock().unwrap_or_else(|e| e.into_inner());
+        if delay_ms == 0 {
+            return enigo.text(text).context("enigo failed to type text");
+        }
+
+        let mut buf = [0; 4];
+        for c in text.chars() {
+            enigo
+                .text(c.encode_utf8(&mut buf))
+                .context("enigo failed to type text")?;
+            std::thread::sleep(Duration::from_millis(delay_ms));
+        }
+        Ok(())
+    }
+
+    /// Hold the shortcut's modifiers, tap its key, release in reverse order
+    pub fn paste(&self, shortcut: &PasteShortcut) -> Result<()> {
+        let mut enigo = self.enigo.lock().unwrap_or_else(|e| e.into_inner());
+        let (modifiers, key) = shortcut.enigo_keys();
+
+        for modifier in &modifiers {
+            enigo.key(*modifier, Direction::Press)?;
+        }
+        let result = enigo.key(key, Direction::Click);
+        for modifier in modifiers.iter().rev() {
+            enigo.key(*modifier, Direction::Release)?;
+        }
+
+        result.context("enigo failed to press the paste shortcut")
+    }
+
+    pub fn middle_click(&self) -> Result<()> {
+        let mut enigo = self.enigo.lock().unwrap_or_else(|e| e.into_inner());
+        enigo
+            .button(Button::Middle, Direction::Click)
+            .context("enigo failed to middle-click")
+    }
+}
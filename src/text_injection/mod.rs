@@ -1,17 +1,178 @@
 use anyhow::{Context, Result};
+use arboard::{Clipboard, LinuxClipboardKind, SetExtLinux};
+use serde::Serialize;
+use std::os::unix::net::{UnixDatagram, UnixStream};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Mutex;
 use tracing::{debug, info, warn};
 use which::which;
 
+use crate::clipboard::ClipboardTool;
+use crate::config::Config;
+use crate::error::{Categorize, ChezWizperError};
+
+#[cfg(feature = "enigo")]
+mod enigo_injector;
+mod session;
+mod shortcut;
+mod typing;
+
+#[cfg(feature = "enigo")]
+use enigo_injector::EnigoInjector;
+use session::{Session, SessionEnv};
+pub use shortcut::PasteShortcut;
+
 pub struct TextInjector {
     method: InjectionMethod,
+    paste_shortcut: PasteShortcut,
+    mode: InjectionMode,
+    /// Delay between typed characters for wtype/ydotool; 0 types at full speed
+    type_delay_ms: u64,
+    /// Socket of the ydotoold daemon, passed to ydotool as YDOTOOL_SOCKET
+    ydotool_socket: PathBuf,
+    selection: Selection,
+    /// Tool for the paste fallback; `Auto` tries wl-copy, xclip and xsel in turn
+    clipboard_tool: ClipboardTool,
+    /// Kept alive so the arboard copy stays pasteable
+    arboard: Mutex<Option<Clipboard>>,
+    /// Whether xdotool was on PATH at startup, for typing into XWayland windows
+    xdotool_available: bool,
+    /// Ask Hyprland or Sway whether the focused window is an XWayland client
+    compositor_ipc: bool,
+    /// Set when the enigo method initialized successfully
+    #[cfg(feature = "enigo")]
+    enigo: Option<EnigoInjector>,
+}
+
+/// Longer delays make even short transcriptions take minutes to type
+pub const MAX_TYPE_DELAY_MS: u64 = 500;
+
+/// How transcribed text reaches the focused window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InjectionMode {
+    /// Type directly when a typing tool is available, falling back to paste
+    #[default]
+    Auto,
+    /// Only type with wtype/ydotool; the clipboard is never touched
+    Type,
+    /// Always copy to the clipboard and paste
+    Paste,
+}
+
+impl InjectionMode {
+    pub fn parse(mode: &str) -> Result<Self> {
+        match mode {
+            "auto" => Ok(InjectionMode::Auto),
+            "type" => Ok(InjectionMode::Type),
+            "paste" => Ok(InjectionMode::Paste),
+            other => Err(anyhow::anyhow!(
+                "Unknown injection_mode '{}', expected \"type\", \"paste\" or \"auto\"",
+                other
+            )),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 enum InjectionMethod {
     Wtype,
     Ydotool,
+    /// Picked on X11 sessions, and per injection for XWayland windows
+    Xdotool,
+    Clipboard,
+    #[cfg(feature = "enigo")]
+    Enigo,
+}
+
+impl InjectionMethod {
+    fn name(&self) -> &'static str {
+        match self {
+            InjectionMethod::Wtype => "wtype",
+            InjectionMethod::Ydotool => "ydotool",
+            InjectionMethod::Xdotool => "xdotool",
+            InjectionMethod::Clipboard => "clipboard",
+            #[cfg(feature = "enigo")]
+            InjectionMethod::Enigo => "enigo",
+        }
+    }
+}
+
+/// Which selection the paste fallback writes to and pastes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Selection {
+    /// The regular clipboard, pasted with the paste shortcut
+    #[default]
     Clipboard,
+    /// The PRIMARY selection, pasted with a middle click at the mouse pointer
+    Primary,
+}
+
+impl Selection {
+    pub fn parse(selection: &str) -> Result<Self> {
+        match selection {
+            "clipboard" => Ok(Selection::Clipboard),
+            "primary" => Ok(Selection::Primary),
+            other => Err(anyhow::anyhow!(
+                "Unknown selection '{}', expected \"clipboard\" or \"primary\"",
+                other
+            )),
+        }
+    }
+}
+
+/// How [`TextInjector::inject_text`] delivered the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InjectionOutcome {
+    /// Typed into the focused window with the named tool
+    Typed(&'static str),
+    /// Copied, then pasted with the named tool
+    Pasted(&'static str),
+    /// Copied, but every way of simulating the paste failed, so the user has
+    /// to paste it themselves
+    CopiedOnly,
+}
+
+/// Session and tools that injection method detection sees
+struct Probe {
+    session: Session,
+    /// Tools taken as installed instead of searching PATH, for tests
+    tools: Option<&'static [&'static str]>,
+}
+
+impl Probe {
+    fn system() -> Self {
+        Self {
+            session: Session::current(),
+            tools: None,
+        }
+    }
+
+    fn has(&self, tool: &str) -> bool {
+        match self.tools {
+            Some(tools) => tools.contains(&tool),
+            None => which(tool).is_ok(),
+        }
+    }
+}
+
+/// What text injection picked and can see, reported by `GET /diagnostics`
+#[derive(Debug, Serialize)]
+pub struct InjectionDiagnostics {
+    pub method: &'static str,
+    pub mode: InjectionMode,
+    pub selection: Selection,
+    pub clipboard_tool: ClipboardTool,
+    /// Clipboard CLI tools found on PATH
+    pub clipboard_backends: Vec<&'static str>,
+    /// "wayland", "x11" or "unknown"
+    pub session: &'static str,
+    /// `$XDG_CURRENT_DESKTOP`
+    pub desktop: Option<String>,
+    pub ydotool_socket: PathBuf,
+    pub ydotoold_reachable: bool,
 }
 
 #[derive(Debug)]
@@ -21,7 +182,31 @@ struct ClipboardBackend {
     copy_args: &'static [&'static str],
     read_cmd: &'static str,
     read_args: &'static [&'static str],
+    primary_copy_args: &'static [&'static str],
+    primary_read_args: &'static [&'static str],
     use_stdin: bool,
+    /// Whether reading the selection back right after a copy shows the new text.
+    /// xclip and xsel fork a background process to own the X11 selection and exit
+    /// before it has taken over, so an early read can return the old content.
+    reliable_readback: bool,
+    /// Wait after copying before the first read-back
+    settle_ms: u64,
+}
+
+impl ClipboardBackend {
+    fn copy_args(&self, selection: Selection) -> &'static [&'static str] {
+        match selection {
+            Selection::Clipboard => self.copy_args,
+            Selection::Primary => self.primary_copy_args,
+        }
+    }
+
+    fn read_args(&self, selection: Selection) -> &'static [&'static str] {
+        match selection {
+            Selection::Clipboard => self.read_args,
+            Selection::Primary => self.primary_read_args,
+        }
+    }
 }
 
 const CLIPBOARD_BACKENDS: &[ClipboardBackend] = &[
@@ -31,7 +216,11 @@ const CLIPBOARD_BACKENDS: &[ClipboardBackend] = &[
         copy_args: &[],
         read_cmd: "wl-paste",
         read_args: &["--no-newline"],
+        primary_copy_args: &["--primary"],
+        primary_read_args: &["--primary", "--no-newline"],
         use_stdin: true,
+        reliable_readback: true,
+        settle_ms: 10,
     },
     ClipboardBackend {
         name: "xclip",
@@ -39,7 +228,11 @@ const CLIPBOARD_BACKENDS: &[ClipboardBackend] = &[
         copy_args: &["-selection", "clipboard"],
         read_cmd: "xclip",
         read_args: &["-selection", "clipboard", "-out"],
+        primary_copy_args: &["-selection", "primary"],
+        primary_read_args: &["-selection", "primary", "-out"],
         use_stdin: true,
+        reliable_readback: false,
+        settle_ms: 100,
     },
     ClipboardBackend {
         name: "xsel",
@@ -47,33 +240,113 @@ const CLIPBOARD_BACKENDS: &[ClipboardBackend] = &[
         copy_args: &["--clipboard", "--input"],
         read_cmd: "xsel",
         read_args: &["--clipboard", "--output"],
+        primary_copy_args: &["--primary", "--input"],
+        primary_read_args: &["--primary", "--output"],
         use_stdin: true,
+        reliable_readback: false,
+        settle_ms: 100,
     },
 ];
 
 impl TextInjector {
-    pub fn new(preferred: Option<&str>) -> Result<Self> {
+    /// Pick an injection method: `preferred` when it works, otherwise the first
+    /// usable entry of `priority`, otherwise clipboard-only. ydotool is only
+    /// chosen when ydotoold answers on `ydotool_socket` (or the socket derived
+    /// from the environment).
+    pub fn new(
+        preferred: Option<&str>,
+        priority: &[String],
+        ydotool_socket: Option<&str>,
+    ) -> Result<Self, ChezWizperError> {
+        let socket = resolve_ydotool_socket(ydotool_socket);
+        let injector = Self::detect(&Probe::system(), preferred, priority, &socket)
+            .categorize(ChezWizperError::Injection)?;
+        Ok(Self {
+            ydotool_socket: socket,
+            xdotool_available: which("xdotool").is_ok(),
+            ..injector
+        })
+    }
+
+    /// Injector with the method, shortcut, mode and selection from `[wayland]`
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let paste_shortcut = PasteShortcut::parse(&config.wayland.paste_shortcut)?;
+        let injection_mode = InjectionMode::parse(&config.wayland.injection_mode)?;
+        // "auto" leaves the choice to injection_priority
+        let preferred = Some(config.wayland.input_method.as_str()).filter(|m| *m != "auto");
+        Ok(Self::new(
+            preferred,
+            &config.wayland.injection_priority,
+            config.wayland.ydotool_socket.as_deref(),
+        )?
+        .with_paste_shortcut(paste_shortcut)
+        .with_mode(injection_mode)
+        .with_type_delay(config.wayland.type_delay_ms)?
+        .with_selection(Selection::parse(&config.wayland.selection)?)
+        .with_clipboard_tool(ClipboardTool::parse(&config.behavior.clipboard_tool)?)
+        .with_compositor_ipc(config.wayland.use_hyprland_ipc))
+    }
+
+    fn detect(
+        probe: &Probe,
+        preferred: Option<&str>,
+        priority: &[String],
+        ydotool_socket: &Path,
+    ) -> Result<Self> {
+        let session = probe.session;
         match preferred {
             Some("ydotool") => {
-                if which("ydotool").is_ok() {
+                if !probe.has("ydotool") {
+                    warn!("ydotool requested in config but not found, falling back...");
+                } else if ydotoold_reachable(ydotool_socket) {
                     info!("Using ydotool for text injection (per config)");
-                    return Ok(Self {
-                        method: InjectionMethod::Ydotool,
-                    });
+                    return Ok(Self::from_method(InjectionMethod::Ydotool));
                 } else {
-                    warn!("ydotool requested in config but not found, falling back...");
+                    warn!(
+                        "ydotool requested in config but ydotoold isn't listening on {:?}. Start it with `systemctl --user enable --now ydotool` or `ydotoold &`; falling back...",
+                        ydotool_socket
+                    );
                 }
             }
             Some("wtype") => {
-                if which("wtype").is_ok() {
+                if session == Session::X11 {
+                    warn!("wtype requested in config but this is an X11 session, falling back...");
+                } else if probe.has("wtype") {
                     info!("Using wtype for text injection (per config)");
-                    return Ok(Self {
-                        method: InjectionMethod::Wtype,
-                    });
+                    return Ok(Self::from_method(InjectionMethod::Wtype));
                 } else {
                     warn!("wtype requested in config but not found, falling back...");
                 }
             }
+            Some("xdotool") => {
+                if session != Session::X11 {
+                    warn!("xdotool requested in config but this isn't an X11 session, falling back...");
+                } else if probe.has("xdotool") {
+                    info!("Using xdotool for text injection (per config)");
+                    return Ok(Self::from_method(InjectionMethod::Xdotool));
+                } else {
+                    warn!("xdotool requested in config but not found, falling back...");
+                }
+            }
+            Some("clipboard") => {
+                info!("Using clipboard+paste for text injection (per config)");
+                return Ok(Self::from_method(InjectionMethod::Clipboard));
+            }
+            #[cfg(feature = "enigo")]
+            Some("enigo") => match EnigoInjector::new() {
+                Ok(enigo) => {
+                    info!("Using enigo for text injection (per config)");
+                    return Ok(Self {
+                        enigo: Some(enigo),
+                        ..Self::from_method(InjectionMethod::Enigo)
+                    });
+                }
+                Err(e) => warn!("enigo requested in config but {:#}, falling back...", e),
+            },
+            #[cfg(not(feature = "enigo"))]
+            Some("enigo") => {
+                warn!("enigo requested in config but this build lacks the \"enigo\" feature, falling back...");
+            }
             Some(other) => {
                 warn!(
                     "Unknown input_method '{}' in config, falling back to auto-detect",
@@ -83,48 +356,221 @@ impl TextInjector {
             None => {}
         }
 
-        // Smart fallback logic - prioritize based on environment and availability
+        for method in priority {
+            if let Some(injector) = Self::try_method(probe, method, ydotool_socket) {
+                return Ok(injector);
+            }
+        }
 
-        // First, try ydotool (most reliable on Wayland when properly configured)
-        if which("ydotool").is_ok() {
-            info!("Using ydotool for text injection (auto-detected)");
-            return Ok(Self {
-                method: InjectionMethod::Ydotool,
-            });
+        // Final fallback to clipboard-only mode
+        info!(
+            "Using clipboard-only for text injection (none of {:?} available)",
+            priority
+        );
+        Ok(Self::from_method(InjectionMethod::Clipboard))
+    }
+
+    /// Injector for one `injection_priority` entry, when it is usable here
+    fn try_method(probe: &Probe, method: &str, ydotool_socket: &Path) -> Option<Self> {
+        let session = probe.session;
+        match method {
+            // Only on X11, where wtype can't type and the clipboard entry is off
+            "xdotool" if session == Session::X11 && probe.has("xdotool") => {
+                info!("Using xdotool for text injection (X11 session)");
+                return Some(Self::from_method(InjectionMethod::Xdotool));
+            }
+            "ydotool" if probe.has("ydotool") => {
+                if ydotoold_reachable(ydotool_socket) {
+                    info!("Using ydotool for text injection (auto-detected)");
+                    return Some(Self::from_method(InjectionMethod::Ydotool));
+                }
+                warn!(
+                    "ydotool is installed but ydotoold isn't listening on {:?}, start ydotoold to use it. Trying other methods",
+                    ydotool_socket
+                );
+            }
+            // Only on Wayland, where direct typing is the least reliable
+            "clipboard" if session == Session::Wayland && probe.has("wl-copy") => {
+                info!("Using clipboard+paste for text injection (Wayland detected)");
+                return Some(Self::from_method(InjectionMethod::Clipboard));
+            }
+            "wtype" if session != Session::X11 && probe.has("wtype") => {
+                info!("Using wtype for text injection (auto-detected, may fall back to clipboard)");
+                return Some(Self::from_method(InjectionMethod::Wtype));
+            }
+            #[cfg(feature = "enigo")]
+            "enigo" => match EnigoInjector::new() {
+                Ok(enigo) => {
+                    info!("Using enigo for text injection (auto-detected)");
+                    return Some(Self {
+                        enigo: Some(enigo),
+                        ..Self::from_method(InjectionMethod::Enigo)
+                    });
+                }
+                Err(e) => debug!("enigo unavailable: {:#}", e),
+            },
+            #[cfg(not(feature = "enigo"))]
+            "enigo" => debug!("Skipping enigo, this build lacks the \"enigo\" feature"),
+            "xdotool" | "ydotool" | "clipboard" | "wtype" => {}
+            other => warn!("Unknown injection_priority entry '{}', skipping", other),
         }
+        None
+    }
 
-        // Check if we're on Wayland and prefer clipboard method
-        if std::env::var("WAYLAND_DISPLAY").is_ok() && which("wl-copy").is_ok() {
-            info!("Using clipboard+paste for text injection (Wayland detected)");
-            return Ok(Self {
-                method: InjectionMethod::Clipboard,
-            });
+    fn from_method(method: InjectionMethod) -> Self {
+        Self {
+            method,
+            paste_shortcut: PasteShortcut::default(),
+            mode: InjectionMode::Auto,
+            type_delay_ms: 0,
+            ydotool_socket: resolve_ydotool_socket(None),
+            selection: Selection::Clipboard,
+            clipboard_tool: ClipboardTool::Auto,
+            arboard: Mutex::new(None),
+            xdotool_available: false,
+            compositor_ipc: true,
+            #[cfg(feature = "enigo")]
+            enigo: None,
         }
+    }
 
-        // Try wtype (limited compatibility but direct when it works)
-        if which("wtype").is_ok() {
-            info!("Using wtype for text injection (auto-detected, may fall back to clipboard)");
-            return Ok(Self {
-                method: InjectionMethod::Wtype,
-            });
+    pub fn with_paste_shortcut(mut self, shortcut: PasteShortcut) -> Self {
+        self.paste_shortcut = shortcut;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: InjectionMode) -> Self {
+        if mode == InjectionMode::Type && matches!(self.method, InjectionMethod::Clipboard) {
+            warn!("injection_mode = \"type\" but neither wtype nor ydotool is available");
         }
+        self.mode = mode;
+        self
+    }
 
-        // Final fallback to clipboard-only mode
-        info!("Using clipboard-only for text injection (no direct input tools available)");
-        Ok(Self {
-            method: InjectionMethod::Clipboard,
-        })
+    /// Slow down direct typing for apps that drop characters. Doesn't affect clipboard paste.
+    pub fn with_type_delay(mut self, delay_ms: u64) -> Result<Self> {
+        if delay_ms > MAX_TYPE_DELAY_MS {
+            return Err(anyhow::anyhow!(
+                "type_delay_ms = {} is too large, use a value between 0 and {}",
+                delay_ms,
+                MAX_TYPE_DELAY_MS
+            ));
+        }
+        self.type_delay_ms = delay_ms;
+        Ok(self)
+    }
+
+    /// Use a specific ydotoold socket instead of the one derived from the environment
+    fn ydotool_command(&self) -> Command {
+        let mut cmd = Command::new("ydotool");
+        cmd.env("YDOTOOL_SOCKET", &self.ydotool_socket);
+        cmd
+    }
+
+    /// Paste through the PRIMARY selection with a middle click instead of the clipboard
+    pub fn with_selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Copy with the same tool as `ClipboardManager` instead of trying each CLI tool
+    pub fn with_clipboard_tool(mut self, tool: ClipboardTool) -> Self {
+        self.clipboard_tool = tool;
+        self
+    }
+
+    /// Whether to ask the compositor for the focused window before typing
+    pub fn with_compositor_ipc(mut self, enabled: bool) -> Self {
+        self.compositor_ipc = enabled;
+        self
+    }
+
+    /// Name of the injection method picked at startup
+    pub fn method_name(&self) -> &'static str {
+        self.method.name()
+    }
+
+    /// Whether the tools behind the chosen method are still on PATH
+    pub fn is_available(&self) -> bool {
+        match self.method {
+            InjectionMethod::Wtype => which("wtype").is_ok(),
+            InjectionMethod::Ydotool => which("ydotool").is_ok(),
+            InjectionMethod::Xdotool => which("xdotool").is_ok(),
+            InjectionMethod::Clipboard => match self.clipboard_tool {
+                ClipboardTool::Arboard => true,
+                _ => self
+                    .clipboard_backends()
+                    .any(|backend| which(backend.copy_cmd).is_ok()),
+            },
+            #[cfg(feature = "enigo")]
+            InjectionMethod::Enigo => self.enigo.is_some(),
+        }
     }
 
-    pub async fn inject_text(&self, text: &str) -> Result<()> {
+    /// Snapshot of the detected tools and environment. Searches PATH and
+    /// connects to the ydotoold socket, so call it off the async runtime.
+    pub fn diagnostics(&self) -> InjectionDiagnostics {
+        InjectionDiagnostics {
+            method: self.method_name(),
+            mode: self.mode,
+            selection: self.selection,
+            clipboard_tool: self.clipboard_tool,
+            clipboard_backends: CLIPBOARD_BACKENDS
+                .iter()
+                .filter(|backend| which(backend.copy_cmd).is_ok())
+                .map(|backend| backend.name)
+                .collect(),
+            session: Session::current().name(),
+            desktop: std::env::var("XDG_CURRENT_DESKTOP").ok(),
+            ydotool_socket: self.ydotool_socket.clone(),
+            ydotoold_reachable: ydotoold_reachable(&self.ydotool_socket),
+        }
+    }
+
+    /// Whether injection may write to the clipboard; false in pure typing mode
+    pub fn uses_clipboard(&self) -> bool {
+        self.mode != InjectionMode::Type
+    }
+
+    pub async fn inject_text(&self, text: &str) -> Result<InjectionOutcome, ChezWizperError> {
+        self.inject(text)
+            .await
+            .categorize(ChezWizperError::Injection)
+    }
+
+    async fn inject(&self, text: &str) -> Result<InjectionOutcome> {
         if text.is_empty() {
-            return Ok(());
+            // Nothing to type counts as typed, there's nothing left to paste
+            return Ok(InjectionOutcome::Typed(self.method_name()));
         }
 
-        info!("Injecting text: {} chars", text.len());
+        info!(
+            "Injecting text: {} chars ({:?} mode)",
+            text.len(),
+            self.mode
+        );
         debug!("Text to inject: {}", text);
 
-        match self.method {
+        match self.mode {
+            InjectionMode::Auto => {}
+            InjectionMode::Paste => return self.inject_with_clipboard_paste(text).await,
+            InjectionMode::Type => {
+                let method = self.typing_method().await;
+                match method {
+                    InjectionMethod::Wtype => self.inject_with_wtype(text),
+                    InjectionMethod::Ydotool => self.inject_with_ydotool(text),
+                    InjectionMethod::Xdotool => self.inject_with_xdotool(text),
+                    #[cfg(feature = "enigo")]
+                    InjectionMethod::Enigo => self.inject_with_enigo(text),
+                    InjectionMethod::Clipboard => Err(anyhow::anyhow!(
+                        "injection_mode is \"type\" but no typing tool is available, install wtype or ydotool"
+                    )),
+                }?;
+                return Ok(InjectionOutcome::Typed(method.name()));
+            }
+        }
+
+        match self.typing_method().await {
             InjectionMethod::Wtype => {
                 self.try_inject_with_fallback(text, |t| self.inject_with_wtype(t), "wtype")
                     .await
@@ -133,16 +579,53 @@ impl TextInjector {
                 self.try_inject_with_fallback(text, |t| self.inject_with_ydotool(t), "ydotool")
                     .await
             }
+            InjectionMethod::Xdotool => {
+                self.try_inject_with_fallback(text, |t| self.inject_with_xdotool(t), "xdotool")
+                    .await
+            }
             InjectionMethod::Clipboard => self.inject_with_clipboard_paste(text).await,
+            #[cfg(feature = "enigo")]
+            InjectionMethod::Enigo => {
+                self.try_inject_with_fallback(text, |t| self.inject_with_enigo(t), "enigo")
+                    .await
+            }
+        }
+    }
+
+    /// Method to type this injection with: the startup pick, or xdotool when
+    /// a Wayland tool was picked but the focused window is an XWayland client
+    async fn typing_method(&self) -> InjectionMethod {
+        if !self.xdotool_available
+            || !matches!(
+                self.method,
+                InjectionMethod::Wtype | InjectionMethod::Ydotool
+            )
+        {
+            return self.method.clone();
         }
+
+        let env = SessionEnv::current();
+        let session = Session::from_env(&env);
+        // Only a Wayland session running XWayland has windows worth asking about
+        let focused_xwayland = if self.compositor_ipc && session == Session::Wayland && env.display
+        {
+            session::focused_window_is_xwayland().await
+        } else {
+            None
+        };
+        if session::prefers_xdotool(session, env.display, focused_xwayland) {
+            debug!("Focused window is an X11 client, typing with xdotool");
+            return InjectionMethod::Xdotool;
+        }
+        self.method.clone()
     }
 
     async fn try_inject_with_fallback<F>(
         &self,
         text: &str,
         inject_fn: F,
-        method_name: &str,
-    ) -> Result<()>
+        method_name: &'static str,
+    ) -> Result<InjectionOutcome>
     where
         F: FnOnce(&str) -> Result<()>,
     {
@@ -153,13 +636,17 @@ impl TextInjector {
             );
             self.inject_with_clipboard_paste(text).await
         } else {
-            Ok(())
+            Ok(InjectionOutcome::Typed(method_name))
         }
     }
 
     fn inject_with_wtype(&self, text: &str) -> Result<()> {
+        if let Some(c) = typing::wtype_unsupported(text) {
+            return Err(anyhow::anyhow!("wtype can't type {:?}", c));
+        }
+
         let output = Command::new("wtype")
-            .arg(text)
+            .args(typing::wtype_args(text, self.type_delay_ms))
             .output()
             .context("Failed to execute wtype")?;
 
@@ -171,52 +658,108 @@ impl TextInjector {
         Ok(())
     }
 
-    fn inject_with_ydotool(&self, text: &str) -> Result<()> {
-        // ydotool requires the daemon to be running
-        let output = Command::new("ydotool")
-            .arg("type")
+    fn inject_with_xdotool(&self, text: &str) -> Result<()> {
+        let mut cmd = Command::new("xdotool");
+        cmd.args(["type", "--clearmodifiers"]);
+        if self.type_delay_ms > 0 {
+            cmd.arg("--delay").arg(self.type_delay_ms.to_string());
+        }
+        let output = cmd
+            .arg("--")
             .arg(text)
             .output()
-            .context("Failed to execute ydotool")?;
+            .context("Failed to execute xdotool")?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            warn!("ydotool failed: {}", stderr);
-            return Err(anyhow::anyhow!(
-                "ydotool failed: {}. Make sure ydotoold is running",
-                stderr
-            ));
+            warn!("xdotool failed: {}", stderr);
+            return Err(anyhow::anyhow!("xdotool failed: {}", stderr));
         }
 
         Ok(())
     }
 
-    pub async fn paste_from_clipboard(&self) -> Result<()> {
+    fn inject_with_ydotool(&self, text: &str) -> Result<()> {
+        if let Some(c) = typing::ydotool_unsupported(text) {
+            return Err(anyhow::anyhow!("ydotool can't type {:?}", c));
+        }
+
+        // ydotool requires the daemon to be running
+        for args in typing::ydotool_commands(text, self.type_delay_ms) {
+            let output = self
+                .ydotool_command()
+                .args(args)
+                .output()
+                .context("Failed to execute ydotool")?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                warn!("ydotool failed: {}", stderr);
+                return Err(anyhow::anyhow!(
+                    "ydotool failed: {}. Make sure ydotoold is running",
+                    stderr
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "enigo")]
+    fn inject_with_enigo(&self, text: &str) -> Result<()> {
+        self.enigo
+            .as_ref()
+            .context("enigo is not initialized")?
+            .type_text(text, self.type_delay_ms)
+    }
+
+    pub async fn paste_from_clipboard(&self) -> Result<(), ChezWizperError> {
+        self.paste_with_method()
+            .categorize(ChezWizperError::Injection)
+    }
+
+    fn paste_with_method(&self) -> Result<()> {
+        if self.selection == Selection::Primary {
+            return self.simulate_middle_click().map(drop);
+        }
+
         info!("Simulating paste shortcut");
 
         match self.method {
             InjectionMethod::Wtype => {
                 Command::new("wtype")
-                    .args(["-M", "ctrl", "-P", "v", "-m", "ctrl", "-p", "v"])
+                    .args(self.paste_shortcut.wtype_args())
                     .output()
                     .context("Failed to simulate paste with wtype")?;
             }
             InjectionMethod::Ydotool => {
-                Command::new("ydotool")
-                    .args(["key", "ctrl+v"])
+                self.ydotool_command()
+                    .args(self.paste_shortcut.ydotool_args())
                     .output()
                     .context("Failed to simulate paste with ydotool")?;
             }
+            InjectionMethod::Xdotool => {
+                Command::new("xdotool")
+                    .args(["key", &self.paste_shortcut.xdotool_chord()])
+                    .output()
+                    .context("Failed to simulate paste with xdotool")?;
+            }
             InjectionMethod::Clipboard => {
                 // For clipboard method, paste is handled in inject_with_clipboard_paste
                 return Ok(());
             }
+            #[cfg(feature = "enigo")]
+            InjectionMethod::Enigo => {
+                if let Some(enigo) = &self.enigo {
+                    enigo.paste(&self.paste_shortcut)?;
+                }
+            }
         }
 
         Ok(())
     }
 
-    async fn inject_with_clipboard_paste(&self, text: &str) -> Result<()> {
+    async fn inject_with_clipboard_paste(&self, text: &str) -> Result<InjectionOutcome> {
         info!("Using clipboard paste method for text injection");
 
         // Copy text to clipboard with verification and retry
@@ -231,15 +774,18 @@ impl TextInjector {
         let max_total_ms = 1000;
         let mut total_ms = 0;
 
-        loop {
-            // Try to copy
-            self.copy_to_clipboard(text).await?;
+        if self.clipboard_tool == ClipboardTool::Arboard {
+            // arboard owns the selection in-process, so there's nothing to wait for
+            return self.copy_with_arboard(text);
+        }
 
-            // Small initial delay to let clipboard settle
-            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        let mut backend = self.copy_to_clipboard(text).await?;
+        // Give the backend time to take over the selection
+        tokio::time::sleep(tokio::time::Duration::from_millis(backend.settle_ms)).await;
 
-            // Verify it worked
-            if let Ok(clipboard_content) = self.read_clipboard().await {
+        loop {
+            // Read back with the tool that copied, so both see the same selection
+            if let Ok(clipboard_content) = self.read_clipboard(backend).await {
                 if clipboard_content.trim() == text.trim() {
                     debug!("Clipboard verified after {}ms", total_ms);
                     return Ok(());
@@ -248,10 +794,17 @@ impl TextInjector {
 
             // Check timeout
             if total_ms >= max_total_ms {
-                warn!(
-                    "Clipboard verification failed after {}ms, proceeding anyway",
-                    total_ms
-                );
+                if backend.reliable_readback {
+                    warn!(
+                        "Clipboard verification failed after {}ms, proceeding anyway",
+                        total_ms
+                    );
+                } else {
+                    debug!(
+                        "{} copy not visible after {}ms, its selection owner may still be starting",
+                        backend.name, total_ms
+                    );
+                }
                 return Ok(());
             }
 
@@ -259,40 +812,67 @@ impl TextInjector {
             tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
             total_ms += delay_ms;
             delay_ms = (delay_ms * 2).min(200); // Cap individual delay at 200ms
+
+            // Copying again with xclip/xsel would only start another background owner
+            if backend.reliable_readback {
+                backend = self.copy_to_clipboard(text).await?;
+            }
         }
     }
 
-    async fn read_clipboard(&self) -> Result<String> {
-        for backend in CLIPBOARD_BACKENDS {
-            if which(backend.read_cmd).is_err() {
-                continue;
-            }
+    async fn read_clipboard(&self, backend: &ClipboardBackend) -> Result<String> {
+        let output = Command::new(backend.read_cmd)
+            .args(backend.read_args(self.selection))
+            .output()
+            .with_context(|| format!("Failed to run {}", backend.read_cmd))?;
 
-            if let Ok(output) = Command::new(backend.read_cmd)
-                .args(backend.read_args)
-                .output()
-            {
-                if output.status.success() {
-                    return Ok(String::from_utf8_lossy(&output.stdout).to_string());
-                }
-            }
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "{} exited with {}",
+                backend.read_cmd,
+                output.status
+            ));
         }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    fn copy_with_arboard(&self, text: &str) -> Result<()> {
+        let kind = match self.selection {
+            Selection::Clipboard => LinuxClipboardKind::Clipboard,
+            Selection::Primary => LinuxClipboardKind::Primary,
+        };
+
+        let mut arboard = self.arboard.lock().unwrap_or_else(|e| e.into_inner());
+        if arboard.is_none() {
+            *arboard = Some(Clipboard::new().context("Failed to open the clipboard with arboard")?);
+        }
+        if let Some(clipboard) = arboard.as_mut() {
+            clipboard.set().clipboard(kind).text(text)?;
+        }
+
+        debug!("Text copied to {:?} selection with arboard", self.selection);
+        Ok(())
+    }
 
-        Err(anyhow::anyhow!(
-            "Failed to read clipboard - no working backend found"
-        ))
+    /// CLI tools the paste fallback may copy with, in order of preference
+    fn clipboard_backends(&self) -> impl Iterator<Item = &'static ClipboardBackend> {
+        let wl_only = self.clipboard_tool == ClipboardTool::WlClipboard;
+        CLIPBOARD_BACKENDS
+            .iter()
+            .filter(move |backend| !wl_only || backend.copy_cmd == "wl-copy")
     }
 
-    async fn copy_to_clipboard(&self, text: &str) -> Result<()> {
+    /// Copy with the first working backend and return it
+    async fn copy_to_clipboard(&self, text: &str) -> Result<&'static ClipboardBackend> {
         use std::io::Write;
 
-        for backend in CLIPBOARD_BACKENDS {
+        for backend in self.clipboard_backends() {
             if which(backend.copy_cmd).is_err() {
                 continue;
             }
 
             let mut cmd = Command::new(backend.copy_cmd);
-            cmd.args(backend.copy_args);
+            cmd.args(backend.copy_args(self.selection));
 
             if backend.use_stdin {
                 cmd.stdin(std::process::Stdio::piped());
@@ -309,8 +889,11 @@ impl TextInjector {
 
                 if let Ok(status) = child.wait() {
                     if status.success() {
-                        debug!("Text copied to clipboard with {}", backend.name);
-                        return Ok(());
+                        debug!(
+                            "Text copied to {:?} selection with {}",
+                            self.selection, backend.name
+                        );
+                        return Ok(backend);
                     }
                 }
             }
@@ -319,20 +902,38 @@ impl TextInjector {
         Err(anyhow::anyhow!("No clipboard tool available"))
     }
 
-    async fn simulate_paste(&self) -> Result<()> {
-        info!("Simulating Ctrl+V paste");
+    /// Paste with the first tool that manages to; `CopiedOnly` when none did
+    async fn simulate_paste(&self) -> Result<InjectionOutcome> {
+        if self.selection == Selection::Primary {
+            return self.simulate_middle_click();
+        }
+
+        info!("Simulating {} paste", self.paste_shortcut.xdotool_chord());
 
         // Try different paste methods based on available tools and detected environment
 
+        // Method 0: enigo, when it's the configured injection method
+        #[cfg(feature = "enigo")]
+        if let Some(enigo) = &self.enigo {
+            match enigo.paste(&self.paste_shortcut) {
+                Ok(()) => {
+                    debug!("Successfully pasted with enigo");
+                    return Ok(InjectionOutcome::Pasted("enigo"));
+                }
+                Err(e) => debug!("enigo paste failed: {:#}, trying other methods", e),
+            }
+        }
+
         // Method 1: ydotool (if available and properly configured)
         if which("ydotool").is_ok() {
-            if let Ok(output) = Command::new("ydotool")
-                .args(["key", "29:1", "47:1", "47:0", "29:0"]) // Ctrl+V key codes
+            if let Ok(output) = self
+                .ydotool_command()
+                .args(self.paste_shortcut.ydotool_args())
                 .output()
             {
                 if output.status.success() {
                     debug!("Successfully pasted with ydotool");
-                    return Ok(());
+                    return Ok(InjectionOutcome::Pasted("ydotool"));
                 }
             }
         }
@@ -340,12 +941,12 @@ impl TextInjector {
         // Method 2: wtype (if available)
         if which("wtype").is_ok() {
             if let Ok(output) = Command::new("wtype")
-                .args(["-M", "ctrl", "-P", "v", "-m", "ctrl", "-p", "v"])
+                .args(self.paste_shortcut.wtype_args())
                 .output()
             {
                 if output.status.success() {
                     debug!("Successfully pasted with wtype");
-                    return Ok(());
+                    return Ok(InjectionOutcome::Pasted("wtype"));
                 } else {
                     debug!("wtype paste failed, continuing with other methods");
                 }
@@ -354,10 +955,13 @@ impl TextInjector {
 
         // Method 3: xdotool (X11 fallback)
         if which("xdotool").is_ok() {
-            if let Ok(output) = Command::new("xdotool").args(["key", "ctrl+v"]).output() {
+            if let Ok(output) = Command::new("xdotool")
+                .args(["key", &self.paste_shortcut.xdotool_chord()])
+                .output()
+            {
                 if output.status.success() {
                     debug!("Successfully pasted with xdotool");
-                    return Ok(());
+                    return Ok(InjectionOutcome::Pasted("xdotool"));
                 }
             }
         }
@@ -377,7 +981,7 @@ impl TextInjector {
                     {
                         if output.status.success() {
                             debug!("Successfully pasted with KDE klipper");
-                            return Ok(());
+                            return Ok(InjectionOutcome::Pasted("klipper"));
                         }
                     }
                 }
@@ -393,7 +997,283 @@ impl TextInjector {
 
         // If all methods fail, inform user but don't error out
         warn!("All paste methods failed - text copied to clipboard, manual paste required");
-        info!("Text is available in clipboard. You can paste manually with Ctrl+V");
-        Ok(())
+        info!(
+            "Text is available in clipboard. You can paste manually with {}",
+            self.paste_shortcut.xdotool_chord()
+        );
+        Ok(InjectionOutcome::CopiedOnly)
+    }
+
+    /// Paste the PRIMARY selection at the mouse pointer. wtype can't click,
+    /// so this needs ydotool (Wayland) or xdotool (X11).
+    fn simulate_middle_click(&self) -> Result<InjectionOutcome> {
+        info!("Simulating middle click to paste the primary selection");
+
+        #[cfg(feature = "enigo")]
+        if let Some(enigo) = &self.enigo {
+            if enigo.middle_click().is_ok() {
+                debug!("Successfully middle-clicked with enigo");
+                return Ok(InjectionOutcome::Pasted("enigo"));
+            }
+        }
+
+        // 0xC2 = middle button down and up
+        if which("ydotool").is_ok() {
+            if let Ok(output) = self.ydotool_command().args(["click", "0xC2"]).output() {
+                if output.status.success() {
+                    debug!("Successfully middle-clicked with ydotool");
+                    return Ok(InjectionOutcome::Pasted("ydotool"));
+                }
+            }
+        }
+
+        if which("xdotool").is_ok() {
+            if let Ok(output) = Command::new("xdotool").args(["click", "2"]).output() {
+                if output.status.success() {
+                    debug!("Successfully middle-clicked with xdotool");
+                    return Ok(InjectionOutcome::Pasted("xdotool"));
+                }
+            }
+        }
+
+        warn!(
+            "Middle click failed - text is in the primary selection, paste it with a middle click"
+        );
+        Ok(InjectionOutcome::CopiedOnly)
+    }
+}
+
+/// Find the ydotoold socket: the configured path, then `$YDOTOOL_SOCKET`, then
+/// `.ydotool_socket` in the user's runtime dir, falling back to the legacy
+/// `/tmp/.ydotool_socket` when only that one exists
+fn resolve_ydotool_socket(configured: Option<&str>) -> PathBuf {
+    let socket = socket_candidate(
+        configured,
+        std::env::var("YDOTOOL_SOCKET").ok(),
+        std::env::var("XDG_RUNTIME_DIR").ok(),
+        current_uid(),
+    );
+
+    let legacy = PathBuf::from("/tmp/.ydotool_socket");
+    let socket = if configured.is_none() && !socket.exists() && legacy.exists() {
+        legacy
+    } else {
+        socket
+    };
+
+    debug!("Using ydotool socket: {:?}", socket);
+    socket
+}
+
+/// Whether a ydotoold daemon accepts connections on `socket`. It listens on a
+/// datagram socket; older versions used a stream one.
+fn ydotoold_reachable(socket: &Path) -> bool {
+    UnixDatagram::unbound()
+        .and_then(|client| client.connect(socket))
+        .is_ok()
+        || UnixStream::connect(socket).is_ok()
+}
+
+fn socket_candidate(
+    configured: Option<&str>,
+    env_socket: Option<String>,
+    runtime_dir: Option<String>,
+    uid: Option<u32>,
+) -> PathBuf {
+    if let Some(path) = configured.map(str::to_string).or(env_socket) {
+        return PathBuf::from(path);
+    }
+
+    let runtime_dir = runtime_dir
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| uid.map(|uid| PathBuf::from(format!("/run/user/{uid}"))))
+        .unwrap_or_else(|| PathBuf::from("/tmp"));
+
+    runtime_dir.join(".ydotool_socket")
+}
+
+/// Real uid of this process, read from the owner of /proc/self
+fn current_uid() -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata("/proc/self").ok().map(|meta| meta.uid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_delay_validation() {
+        let injector = TextInjector::from_method(InjectionMethod::Clipboard);
+        let injector = injector.with_type_delay(20).unwrap();
+        assert_eq!(injector.type_delay_ms, 20);
+        assert!(injector.with_type_delay(MAX_TYPE_DELAY_MS + 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_inject_reports_how_text_was_delivered() {
+        let injector = TextInjector::from_method(InjectionMethod::Clipboard);
+        let outcome = injector
+            .try_inject_with_fallback("hi", |_| Ok(()), "wtype")
+            .await
+            .unwrap();
+        assert_eq!(outcome, InjectionOutcome::Typed("wtype"));
+
+        let err = injector
+            .with_mode(InjectionMode::Type)
+            .inject_text("hi")
+            .await
+            .unwrap_err();
+        assert_eq!(err.category(), "injection");
+    }
+
+    #[test]
+    fn test_detect_falls_back_to_clipboard() {
+        let socket = Path::new("/nonexistent/.ydotool_socket");
+        let probe = Probe::system();
+        let injector =
+            TextInjector::detect(&probe, None, &["unknown".to_string()], socket).unwrap();
+        assert_eq!(injector.method_name(), "clipboard");
+
+        let injector = TextInjector::detect(&probe, None, &[], socket).unwrap();
+        assert_eq!(injector.method_name(), "clipboard");
+    }
+
+    #[test]
+    fn test_detect_follows_injection_priority() {
+        let socket = Path::new("/nonexistent/.ydotool_socket");
+        let probe = Probe {
+            session: Session::Wayland,
+            tools: Some(&["ydotool", "wtype", "wl-copy"]),
+        };
+        let priority = |methods: &[&str]| methods.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+
+        // ydotoold isn't running, so ydotool is skipped
+        let injector = TextInjector::detect(
+            &probe,
+            None,
+            &priority(&["ydotool", "wtype", "clipboard"]),
+            socket,
+        )
+        .unwrap();
+        assert_eq!(injector.method_name(), "wtype");
+
+        let injector =
+            TextInjector::detect(&probe, None, &priority(&["clipboard", "wtype"]), socket).unwrap();
+        assert_eq!(injector.method_name(), "clipboard");
+
+        // xdotool is skipped outside X11
+        let injector =
+            TextInjector::detect(&probe, None, &priority(&["xdotool", "wtype"]), socket).unwrap();
+        assert_eq!(injector.method_name(), "wtype");
+
+        let x11 = Probe {
+            session: Session::X11,
+            tools: Some(&["xdotool", "wtype", "wl-copy"]),
+        };
+        let injector = TextInjector::detect(
+            &x11,
+            None,
+            &priority(&["wtype", "clipboard", "xdotool"]),
+            socket,
+        )
+        .unwrap();
+        assert_eq!(injector.method_name(), "xdotool");
+    }
+
+    #[test]
+    fn test_selection_args() {
+        assert_eq!(Selection::parse("primary").unwrap(), Selection::Primary);
+        assert!(Selection::parse("secondary").is_err());
+
+        for backend in CLIPBOARD_BACKENDS {
+            assert_ne!(
+                backend.copy_args(Selection::Primary),
+                backend.copy_args(Selection::Clipboard)
+            );
+            assert!(backend
+                .read_args(Selection::Primary)
+                .iter()
+                .any(|arg| arg.contains("primary")));
+        }
+    }
+
+    #[test]
+    fn test_wl_clipboard_restricts_backends() {
+        let injector = TextInjector::from_method(InjectionMethod::Clipboard)
+            .with_clipboard_tool(ClipboardTool::WlClipboard);
+        let names: Vec<_> = injector.clipboard_backends().map(|b| b.name).collect();
+        assert_eq!(names, ["wl-copy"]);
+
+        let injector = injector.with_clipboard_tool(ClipboardTool::Auto);
+        assert_eq!(
+            injector.clipboard_backends().count(),
+            CLIPBOARD_BACKENDS.len()
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_json() {
+        let injector = TextInjector {
+            ydotool_socket: PathBuf::from("/nonexistent/.ydotool_socket"),
+            ..TextInjector::from_method(InjectionMethod::Clipboard)
+        }
+        .with_mode(InjectionMode::Paste)
+        .with_clipboard_tool(ClipboardTool::WlClipboard);
+        let json = serde_json::to_value(injector.diagnostics()).unwrap();
+
+        assert_eq!(json["method"], "clipboard");
+        assert_eq!(json["mode"], "paste");
+        assert_eq!(json["selection"], "clipboard");
+        assert_eq!(json["clipboard_tool"], "wl-clipboard");
+        assert_eq!(json["ydotoold_reachable"], false);
+    }
+
+    #[test]
+    fn test_ydotoold_reachable() {
+        let dir = std::env::temp_dir().join(format!("chezwizper-ydotoold-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join(".ydotool_socket");
+        let _ = std::fs::remove_file(&socket);
+
+        assert!(!ydotoold_reachable(&socket));
+        let _daemon = UnixDatagram::bind(&socket).unwrap();
+        assert!(ydotoold_reachable(&socket));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_socket_candidate() {
+        assert_eq!(
+            socket_candidate(
+                Some("/custom.sock"),
+                Some("/env.sock".into()),
+                None,
+                Some(1000)
+            ),
+            PathBuf::from("/custom.sock")
+        );
+        assert_eq!(
+            socket_candidate(None, Some("/env.sock".into()), None, Some(1000)),
+            PathBuf::from("/env.sock")
+        );
+        assert_eq!(
+            socket_candidate(None, None, Some("/run/user/1001".into()), Some(1000)),
+            PathBuf::from("/run/user/1001/.ydotool_socket")
+        );
+        assert_eq!(
+            socket_candidate(None, None, None, Some(1002)),
+            PathBuf::from("/run/user/1002/.ydotool_socket")
+        );
+    }
+
+    #[test]
+    fn test_injection_mode_parse() {
+        assert_eq!(InjectionMode::parse("type").unwrap(), InjectionMode::Type);
+        assert_eq!(InjectionMode::parse("paste").unwrap(), InjectionMode::Paste);
+        assert_eq!(InjectionMode::parse("auto").unwrap(), InjectionMode::Auto);
+        assert!(InjectionMode::parse("clipboard").is_err());
     }
 }
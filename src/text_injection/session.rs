@@ -0,0 +1,204 @@
+use serde_json::Value;
+use std::time::Duration;
+use tokio::process::Command;
+use tracing::debug;
+
+/// Longest wait for the compositor, which is asked before every injection
+const COMPOSITOR_QUERY_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// Display server of the desktop session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Session {
+    Wayland,
+    X11,
+    /// Neither, e.g. a TTY or SSH login
+    Unknown,
+}
+
+/// Environment variables that identify the session
+#[derive(Debug, Default)]
+pub(super) struct SessionEnv {
+    /// `$XDG_SESSION_TYPE`, set by the login manager
+    pub session_type: Option<String>,
+    /// Whether `$WAYLAND_DISPLAY` is set
+    pub wayland_display: bool,
+    /// Whether `$DISPLAY` is set, by an X server or XWayland
+    pub display: bool,
+}
+
+impl SessionEnv {
+    pub(super) fn current() -> Self {
+        Self {
+            session_type: std::env::var("XDG_SESSION_TYPE").ok(),
+            wayland_display: std::env::var_os("WAYLAND_DISPLAY").is_some(),
+            display: std::env::var_os("DISPLAY").is_some(),
+        }
+    }
+}
+
+impl Session {
+    pub(super) fn current() -> Self {
+        Self::from_env(&SessionEnv::current())
+    }
+
+    /// | `XDG_SESSION_TYPE` | `WAYLAND_DISPLAY` | `DISPLAY` | session |
+    /// |--------------------|-------------------|-----------|---------|
+    /// | `wayland`          | any               | any       | Wayland |
+    /// | `x11`              | any               | any       | X11     |
+    /// | other or unset     | set               | any       | Wayland (`DISPLAY` is XWayland's) |
+    /// | other or unset     | unset             | set       | X11     |
+    /// | other or unset     | unset             | unset     | Unknown |
+    ///
+    /// The login manager's `XDG_SESSION_TYPE` wins over the display variables,
+    /// which leak into X11 sessions from nested compositors and `ssh -X`.
+    pub(super) fn from_env(env: &SessionEnv) -> Self {
+        match env.session_type.as_deref() {
+            Some("wayland") => Session::Wayland,
+            Some("x11") => Session::X11,
+            _ if env.wayland_display => Session::Wayland,
+            _ if env.display => Session::X11,
+            _ => Session::Unknown,
+        }
+    }
+
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Session::Wayland => "wayland",
+            Session::X11 => "x11",
+            Session::Unknown => "unknown",
+        }
+    }
+}
+
+/// Whether to type with xdotool rather than wtype/ydotool:
+///
+/// | session | focused window          | tool            |
+/// |---------|-------------------------|-----------------|
+/// | X11     | any                     | xdotool         |
+/// | Wayland | XWayland, `DISPLAY` set | xdotool         |
+/// | Wayland | native or undetectable  | wtype / ydotool |
+/// | Unknown | any                     | wtype / ydotool |
+///
+/// wtype needs the Wayland virtual keyboard protocol, and X11 apps running
+/// under XWayland don't always pick up the keymap it uploads.
+pub(super) fn prefers_xdotool(
+    session: Session,
+    display: bool,
+    focused_xwayland: Option<bool>,
+) -> bool {
+    match session {
+        Session::X11 => true,
+        Session::Wayland => display && focused_xwayland == Some(true),
+        Session::Unknown => false,
+    }
+}
+
+/// Whether the focused window is an XWayland client, asked from Hyprland or
+/// Sway. None on other compositors, which don't expose it.
+pub(super) async fn focused_window_is_xwayland() -> Option<bool> {
+    if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        return compositor_query("hyprctl", &["activewindow", "-j"])
+            .await
+            .and_then(|window| window.get("xwayland")?.as_bool());
+    }
+    if std::env::var_os("SWAYSOCK").is_some() {
+        return compositor_query("swaymsg", &["-t", "get_tree"])
+            .await
+            .and_then(|tree| sway_focused_xwayland(&tree));
+    }
+    None
+}
+
+async fn compositor_query(cmd: &str, args: &[&str]) -> Option<Value> {
+    let query = Command::new(cmd).args(args).kill_on_drop(true).output();
+    let output = match tokio::time::timeout(COMPOSITOR_QUERY_TIMEOUT, query).await {
+        Ok(output) => output.ok()?,
+        Err(_) => {
+            debug!(
+                "{} didn't answer within {:?}",
+                cmd, COMPOSITOR_QUERY_TIMEOUT
+            );
+            return None;
+        }
+    };
+    if !output.status.success() {
+        debug!(
+            "{} failed: {}",
+            cmd,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        return None;
+    }
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+/// Shell of the focused node in `swaymsg -t get_tree` output
+fn sway_focused_xwayland(node: &Value) -> Option<bool> {
+    if node.get("focused").and_then(Value::as_bool) == Some(true) {
+        return Some(node.get("shell").and_then(Value::as_str) == Some("xwayland"));
+    }
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node.get(key)?.as_array())
+        .flatten()
+        .find_map(sway_focused_xwayland)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(session_type: Option<&str>, wayland_display: bool, display: bool) -> SessionEnv {
+        SessionEnv {
+            session_type: session_type.map(str::to_string),
+            wayland_display,
+            display,
+        }
+    }
+
+    #[test]
+    fn test_session_from_env() {
+        let cases = [
+            (env(Some("wayland"), false, true), Session::Wayland),
+            (env(Some("x11"), true, true), Session::X11),
+            (env(Some("tty"), true, true), Session::Wayland),
+            (env(None, false, true), Session::X11),
+            (env(None, false, false), Session::Unknown),
+        ];
+        for (env, expected) in cases {
+            assert_eq!(Session::from_env(&env), expected, "{env:?}");
+        }
+    }
+
+    #[test]
+    fn test_prefers_xdotool_for_x11_windows() {
+        assert!(prefers_xdotool(Session::X11, true, None));
+        assert!(prefers_xdotool(Session::Wayland, true, Some(true)));
+        assert!(!prefers_xdotool(Session::Wayland, false, Some(true)));
+        assert!(!prefers_xdotool(Session::Wayland, true, Some(false)));
+        assert!(!prefers_xdotool(Session::Wayland, true, None));
+        assert!(!prefers_xdotool(Session::Unknown, true, Some(true)));
+
+        let tree = serde_json::json!({
+            "focused": false,
+            "nodes": [{
+                "focused": false,
+                "nodes": [
+                    {"focused": false, "shell": "xdg_shell"},
+                    {"focused": true, "shell": "xwayland"}
+                ]
+            }]
+        });
+        assert_eq!(sway_focused_xwayland(&tree), Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_compositor_query_times_out() {
+        let started = std::time::Instant::now();
+        assert_eq!(compositor_query("sleep", &["5"]).await, None);
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let echoed = compositor_query("echo", &[r#"{"xwayland": true}"#]).await;
+        assert_eq!(echoed.unwrap()["xwayland"], true);
+    }
+}
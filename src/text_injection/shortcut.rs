@@ -0,0 +1,231 @@
+use anyhow::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Modifier {
+    Ctrl,
+    Shift,
+    Alt,
+    Super,
+}
+
+impl Modifier {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "ctrl" | "control" => Some(Modifier::Ctrl),
+            "shift" => Some(Modifier::Shift),
+            "alt" => Some(Modifier::Alt),
+            "super" | "logo" => Some(Modifier::Super),
+            _ => None,
+        }
+    }
+
+    /// Modifier name as understood by both wtype and xdotool
+    fn name(self) -> &'static str {
+        match self {
+            Modifier::Ctrl => "ctrl",
+            Modifier::Shift => "shift",
+            Modifier::Alt => "alt",
+            Modifier::Super => "super",
+        }
+    }
+
+    /// Linux input event code, used by ydotool
+    fn keycode(self) -> u16 {
+        match self {
+            Modifier::Ctrl => 29,
+            Modifier::Shift => 42,
+            Modifier::Alt => 56,
+            Modifier::Super => 125,
+        }
+    }
+
+    #[cfg(feature = "enigo")]
+    fn enigo_key(self) -> enigo::Key {
+        match self {
+            Modifier::Ctrl => enigo::Key::Control,
+            Modifier::Shift => enigo::Key::Shift,
+            Modifier::Alt => enigo::Key::Alt,
+            Modifier::Super => enigo::Key::Meta,
+        }
+    }
+}
+
+/// Keyboard shortcut used to paste from the clipboard, e.g. "ctrl+v" or "ctrl+shift+v"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasteShortcut {
+    modifiers: Vec<Modifier>,
+    /// X keysym name of the final key ("v", "Insert")
+    key: &'static str,
+    keycode: u16,
+}
+
+impl Default for PasteShortcut {
+    fn default() -> Self {
+        Self::parse("ctrl+v").expect("default paste shortcut is valid")
+    }
+}
+
+impl PasteShortcut {
+    /// Parse a `+`-separated shortcut of modifiers (ctrl, shift, alt, super)
+    /// followed by one key: a letter or "insert"
+    pub fn parse(shortcut: &str) -> Result<Self> {
+        let lowered = shortcut.trim().to_lowercase();
+        let mut parts: Vec<&str> = lowered.split('+').map(str::trim).collect();
+        let key_name = parts.pop().unwrap_or_default();
+
+        let modifiers = parts
+            .iter()
+            .map(|name| {
+                Modifier::parse(name).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Unknown modifier '{}' in paste_shortcut '{}', expected ctrl, shift, alt or super",
+                        name,
+                        shortcut
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let (key, keycode) = key_lookup(key_name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unsupported key '{}' in paste_shortcut '{}', expected a letter or \"insert\" (e.g. \"ctrl+shift+v\")",
+                key_name,
+                shortcut
+            )
+        })?;
+
+        Ok(Self {
+            modifiers,
+            key,
+            keycode,
+        })
+    }
+
+    /// Arguments for `wtype`: hold modifiers, tap the key, release modifiers
+    pub fn wtype_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        for modifier in &self.modifiers {
+            args.push("-M".to_string());
+            args.push(modifier.name().to_string());
+        }
+        args.push("-k".to_string());
+        args.push(self.key.to_string());
+        for modifier in self.modifiers.iter().rev() {
+            args.push("-m".to_string());
+            args.push(modifier.name().to_string());
+        }
+        args
+    }
+
+    /// Arguments for `ydotool key`, as raw `code:state` press/release events
+    pub fn ydotool_args(&self) -> Vec<String> {
+        let mut args = vec!["key".to_string()];
+        for modifier in &self.modifiers {
+            args.push(format!("{}:1", modifier.keycode()));
+        }
+        args.push(format!("{}:1", self.keycode));
+        args.push(format!("{}:0", self.keycode));
+        for modifier in self.modifiers.iter().rev() {
+            args.push(format!("{}:0", modifier.keycode()));
+        }
+        args
+    }
+
+    /// Modifiers and final key for the enigo backend
+    #[cfg(feature = "enigo")]
+    pub fn enigo_keys(&self) -> (Vec<enigo::Key>, enigo::Key) {
+        let modifiers = self.modifiers.iter().map(|m| m.enigo_key()).collect();
+        let key = match self.key {
+            "Insert" => enigo::Key::Insert,
+            letter => enigo::Key::Unicode(letter.chars().next().unwrap_or('v')),
+        };
+        (modifiers, key)
+    }
+
+    /// Key chord for `xdotool key`, e.g. "ctrl+shift+v"
+    pub fn xdotool_chord(&self) -> String {
+        self.modifiers
+            .iter()
+            .map(|modifier| modifier.name())
+            .chain(std::iter::once(self.key))
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+}
+
+/// Keysym name and Linux keycode for a supported paste key
+fn key_lookup(name: &str) -> Option<(&'static str, u16)> {
+    const LETTERS: &[(&str, u16)] = &[
+        ("a", 30),
+        ("b", 48),
+        ("c", 46),
+        ("d", 32),
+        ("e", 18),
+        ("f", 33),
+        ("g", 34),
+        ("h", 35),
+        ("i", 23),
+        ("j", 36),
+        ("k", 37),
+        ("l", 38),
+        ("m", 50),
+        ("n", 49),
+        ("o", 24),
+        ("p", 25),
+        ("q", 16),
+        ("r", 19),
+        ("s", 31),
+        ("t", 20),
+        ("u", 22),
+        ("v", 47),
+        ("w", 17),
+        ("x", 45),
+        ("y", 21),
+        ("z", 44),
+    ];
+
+    if name == "insert" {
+        return Some(("Insert", 110));
+    }
+
+    LETTERS.iter().find(|(letter, _)| *letter == name).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ctrl_shift_v() {
+        let shortcut = PasteShortcut::parse("Ctrl+Shift+V").unwrap();
+        assert_eq!(shortcut.xdotool_chord(), "ctrl+shift+v");
+        assert_eq!(
+            shortcut.ydotool_args(),
+            vec!["key", "29:1", "42:1", "47:1", "47:0", "42:0", "29:0"]
+        );
+        assert_eq!(
+            shortcut.wtype_args(),
+            vec!["-M", "ctrl", "-M", "shift", "-k", "v", "-m", "shift", "-m", "ctrl"]
+        );
+    }
+
+    #[cfg(feature = "enigo")]
+    #[test]
+    fn test_enigo_keys() {
+        let (modifiers, key) = PasteShortcut::parse("shift+insert").unwrap().enigo_keys();
+        assert_eq!(modifiers, vec![enigo::Key::Shift]);
+        assert_eq!(key, enigo::Key::Insert);
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown() {
+        assert!(PasteShortcut::parse("hyper+v").is_err());
+        assert!(PasteShortcut::parse("ctrl+f13").is_err());
+        assert!(PasteShortcut::parse("").is_err());
+        assert_eq!(
+            PasteShortcut::default(),
+            PasteShortcut::parse("ctrl+v").unwrap()
+        );
+        assert!(PasteShortcut::parse("shift+insert").is_ok());
+    }
+}
@@ -0,0 +1,175 @@
+/// Linux input event codes for the keys typed between text runs
+const KEY_ENTER: u16 = 28;
+const KEY_TAB: u16 = 15;
+
+/// A run of text or a key press, in the order they are typed
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum Keystroke<'a> {
+    Text(&'a str),
+    Return,
+    Tab,
+}
+
+/// Split text at line breaks and tabs, which are sent as key presses since
+/// typing tools differ in whether (and how) they type them as characters.
+/// `\r\n` and a lone `\r` count as one line break.
+pub(super) fn keystrokes(text: &str) -> Vec<Keystroke<'_>> {
+    let mut strokes = Vec::new();
+    let mut rest = text;
+    while let Some(at) = rest.find(['\r', '\n', '\t']) {
+        if at > 0 {
+            strokes.push(Keystroke::Text(&rest[..at]));
+        }
+        let (stroke, len) = match &rest[at..] {
+            tail if tail.starts_with("\r\n") => (Keystroke::Return, 2),
+            tail if tail.starts_with('\t') => (Keystroke::Tab, 1),
+            _ => (Keystroke::Return, 1),
+        };
+        strokes.push(stroke);
+        rest = &rest[at + len..];
+    }
+    if !rest.is_empty() {
+        strokes.push(Keystroke::Text(rest));
+    }
+    strokes
+}
+
+/// First character wtype can't type. It types any Unicode, emoji included,
+/// through a temporary keymap, but not control characters.
+pub(super) fn wtype_unsupported(text: &str) -> Option<char> {
+    text.chars()
+        .find(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+}
+
+/// First character ydotool can't type. It sends US keyboard scancodes, so only
+/// printable ASCII comes out right; anything else is dropped or mistyped.
+pub(super) fn ydotool_unsupported(text: &str) -> Option<char> {
+    text.chars()
+        .find(|c| !(c.is_ascii_graphic() || matches!(c, ' ' | '\n' | '\r' | '\t')))
+}
+
+/// Arguments for a single `wtype` call typing `text`
+pub(super) fn wtype_args(text: &str, delay_ms: u64) -> Vec<String> {
+    let mut args = Vec::new();
+    if delay_ms > 0 {
+        args.push("-d".to_string());
+        args.push(delay_ms.to_string());
+    }
+    for stroke in keystrokes(text) {
+        match stroke {
+            Keystroke::Text(mut run) => {
+                // wtype would read a leading dash as an option
+                while let Some(after) = run.strip_prefix('-') {
+                    args.push("-k".to_string());
+                    args.push("minus".to_string());
+                    run = after;
+                }
+                if !run.is_empty() {
+                    args.push(run.to_string());
+                }
+            }
+            Keystroke::Return => {
+                args.push("-k".to_string());
+                args.push("Return".to_string());
+            }
+            Keystroke::Tab => {
+                args.push("-k".to_string());
+                args.push("Tab".to_string());
+            }
+        }
+    }
+    args
+}
+
+/// Arguments for each `ydotool` call typing `text`: `type` for text runs,
+/// `key` press/release events for line breaks and tabs
+pub(super) fn ydotool_commands(text: &str, delay_ms: u64) -> Vec<Vec<String>> {
+    keystrokes(text)
+        .into_iter()
+        .map(|stroke| match stroke {
+            Keystroke::Text(run) => {
+                let mut args = vec!["type".to_string()];
+                if delay_ms > 0 {
+                    args.push("--key-delay".to_string());
+                    args.push(delay_ms.to_string());
+                }
+                args.push("--".to_string());
+                args.push(run.to_string());
+                args
+            }
+            Keystroke::Return => key_press(KEY_ENTER),
+            Keystroke::Tab => key_press(KEY_TAB),
+        })
+        .collect()
+}
+
+/// `ydotool key` arguments pressing and releasing one key
+fn key_press(code: u16) -> Vec<String> {
+    vec!["key".to_string(), format!("{code}:1"), format!("{code}:0")]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIXED: &str = "Hi 👋 café\r\n-5 degrees\tok\n\n";
+
+    #[test]
+    fn test_keystrokes_split_lines_and_tabs() {
+        assert_eq!(
+            keystrokes(MIXED),
+            vec![
+                Keystroke::Text("Hi 👋 café"),
+                Keystroke::Return,
+                Keystroke::Text("-5 degrees"),
+                Keystroke::Tab,
+                Keystroke::Text("ok"),
+                Keystroke::Return,
+                Keystroke::Return,
+            ]
+        );
+        assert!(keystrokes("").is_empty());
+    }
+
+    #[test]
+    fn test_wtype_args_with_newlines_and_emoji() {
+        assert_eq!(
+            wtype_args(MIXED, 10),
+            [
+                "-d",
+                "10",
+                "Hi 👋 café",
+                "-k",
+                "Return",
+                "-k",
+                "minus",
+                "5 degrees",
+                "-k",
+                "Tab",
+                "ok",
+                "-k",
+                "Return",
+                "-k",
+                "Return",
+            ]
+        );
+        assert_eq!(wtype_unsupported(MIXED), None);
+        assert_eq!(wtype_unsupported("bell\u{7}"), Some('\u{7}'));
+    }
+
+    #[test]
+    fn test_ydotool_commands_and_unsupported_chars() {
+        assert_eq!(ydotool_unsupported(MIXED), Some('👋'));
+        assert_eq!(ydotool_unsupported("plain $HOME `ls` 'quoted'\n"), None);
+
+        assert_eq!(
+            ydotool_commands("a\tb\n", 0),
+            vec![
+                vec!["type", "--", "a"],
+                vec!["key", "15:1", "15:0"],
+                vec!["type", "--", "b"],
+                vec!["key", "28:1", "28:0"],
+            ]
+        );
+    }
+}
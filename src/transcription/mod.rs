@@ -1,56 +1,572 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
 use tracing::{debug, info};
 
-use crate::normalizer::Normalizer;
-use crate::whisper::WhisperTranscriber;
+use crate::config::{Config, NormalizerConfig};
+use crate::error::ChezWizperError;
+use crate::normalizer::{
+    AnnotationNormalizer, CapitalizationNormalizer, CustomReplacementNormalizer, Normalizer,
+    OutputStyle, RepetitionNormalizer, SpokenCommandNormalizer, TranscriptionNormalizer,
+};
+use crate::whisper::{ProviderConfig, Transcription, WhisperTranscriber};
+
+type NormalizerChain = Arc<Vec<Box<dyn TranscriptionNormalizer>>>;
+
+/// Longest a provider call may take before it is abandoned
+pub const DEFAULT_TRANSCRIPTION_TIMEOUT_SECS: u64 = 300;
 
 /// Service that orchestrates transcription and normalization
 pub struct TranscriptionService {
     whisper: WhisperTranscriber,
-    normalizer: Normalizer,
+    /// Swapped as a whole on config reload; a running transcription keeps its chain
+    normalizers: RwLock<NormalizerChain>,
+    timeout: Duration,
 }
 
 impl TranscriptionService {
-    /// Create a new transcription service with the provided whisper transcriber
-    pub fn new(whisper: WhisperTranscriber) -> Result<Self> {
-        let normalizer = Normalizer::create(whisper.is_openai_whisper())?;
+    /// Create a new transcription service around a whisper transcriber, or any
+    /// `Box<dyn TranscriptionProvider>`
+    pub fn new(
+        whisper: impl Into<WhisperTranscriber>,
+        normalizer_config: &NormalizerConfig,
+    ) -> Result<Self> {
+        let whisper = whisper.into();
+        let normalizers = build_normalizers(whisper.output_style(), normalizer_config)?;
 
         Ok(Self {
             whisper,
-            normalizer,
+            normalizers: RwLock::new(Arc::new(normalizers)),
+            timeout: Duration::from_secs(DEFAULT_TRANSCRIPTION_TIMEOUT_SECS),
         })
     }
 
-    /// Transcribe audio file and return normalized text
-    pub async fn transcribe(&self, audio_path: &PathBuf) -> Result<String> {
+    /// Build the transcriber and normalizer chain described by the config,
+    /// auto-detecting the provider when none is configured
+    pub fn from_config(config: &Config, temp_dir: PathBuf) -> Result<Self> {
+        let whisper = WhisperTranscriber::new(ProviderConfig {
+            provider: config.whisper.provider.clone(),
+            model: config.whisper.model.clone(),
+            model_path: config.whisper.model_path.clone(),
+            language: Some(config.whisper.language.clone()),
+            command_path: config.whisper.command_path.clone(),
+            api_endpoint: config.whisper.api_endpoint.clone(),
+            api_key: config.whisper.api_key.clone(),
+            temp_dir: Some(temp_dir),
+            task: Some(config.whisper.task.clone()),
+            initial_prompt: config.whisper.initial_prompt.clone(),
+            request_timeout_secs: Some(config.whisper.request_timeout_secs),
+            max_attempts: Some(config.whisper.max_attempts),
+            chunk_secs: Some(config.whisper.chunk_secs),
+            upload_format: Some(config.audio.upload_format.clone()),
+            azure_resource: config.whisper.azure_resource.clone(),
+            azure_deployment: config.whisper.azure_deployment.clone(),
+            azure_api_version: Some(config.whisper.azure_api_version.clone()),
+            keep_model_loaded: Some(config.whisper.keep_model_loaded),
+        })?;
+
+        Ok(
+            Self::new(whisper, &config.normalizer)?.with_timeout(Duration::from_secs(
+                config.whisper.transcription_timeout_secs,
+            )),
+        )
+    }
+
+    /// Abandon provider calls that take longer than `timeout`. Dropping the
+    /// call kills any whisper subprocess it started.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Rebuild the normalizer chain from new settings, keeping the old one on error
+    pub fn reload_normalizers(&self, normalizer_config: &NormalizerConfig) -> Result<()> {
+        let normalizers = build_normalizers(self.whisper.output_style(), normalizer_config)?;
+        *self.normalizers.write().expect("normalizer lock poisoned") = Arc::new(normalizers);
+        Ok(())
+    }
+
+    /// Name of the underlying transcription provider
+    pub fn provider_name(&self) -> &'static str {
+        self.whisper.provider_name()
+    }
+
+    /// Model requests are sent with unless a recording overrides it
+    pub fn model(&self) -> Option<&str> {
+        self.whisper.model()
+    }
+
+    /// Whether the underlying provider can transcribe right now
+    pub fn provider_available(&self) -> bool {
+        self.whisper.is_available()
+    }
+
+    /// Transcribe audio file and return normalized text with any segment timestamps
+    pub async fn transcribe(&self, audio_path: &PathBuf) -> Result<Transcription, ChezWizperError> {
+        self.transcribe_in(audio_path, None, None).await
+    }
+
+    /// Like [`transcribe`](Self::transcribe), overriding the configured
+    /// language and model when set
+    pub async fn transcribe_in(
+        &self,
+        audio_path: &PathBuf,
+        language: Option<&str>,
+        model: Option<&str>,
+    ) -> Result<Transcription, ChezWizperError> {
         info!("Starting transcription pipeline for: {:?}", audio_path);
 
         // Step 1: Get raw transcription from whisper
         debug!("Getting raw transcription from whisper");
-        let raw_transcription = self.whisper.transcribe(audio_path).await?;
+        let raw_transcription = tokio::time::timeout(
+            self.timeout,
+            self.whisper.transcribe(audio_path, language, model),
+        )
+        .await
+        .map_err(|_| ChezWizperError::Timeout(self.timeout.as_secs()))??;
 
-        // Step 2: Normalize the transcription
+        // Step 2: Run the normalizer chain
         debug!("Normalizing transcription output");
-        let normalized = self.normalizer.run(&raw_transcription);
+        let normalizers = self
+            .normalizers
+            .read()
+            .expect("normalizer lock poisoned")
+            .clone();
+        let normalized = run_normalizers(&normalizers, &raw_transcription.text);
 
         info!(
             "Transcription pipeline complete: {} chars -> {} chars",
-            raw_transcription.len(),
+            raw_transcription.text.len(),
             normalized.len()
         );
 
-        Ok(normalized)
+        Ok(Transcription {
+            text: normalized,
+            segments: raw_transcription.segments,
+            confidence: raw_transcription.confidence,
+        })
     }
 }
 
+/// Provider cleanup first so later stages see plain text, then the optional stages
+fn build_normalizers(
+    output_style: OutputStyle,
+    normalizer_config: &NormalizerConfig,
+) -> Result<Vec<Box<dyn TranscriptionNormalizer>>> {
+    let mut normalizers: Vec<Box<dyn TranscriptionNormalizer>> =
+        vec![Box::new(Normalizer::for_style(output_style)?)];
+
+    if normalizer_config.keep_annotations {
+        info!("Keeping non-speech annotations like [BLANK_AUDIO]");
+    } else {
+        normalizers.push(Box::new(AnnotationNormalizer::new()?));
+    }
+
+    if normalizer_config.max_repeats > 0 {
+        info!(
+            "Collapsing phrases repeated more than {} times",
+            normalizer_config.max_repeats
+        );
+        normalizers.push(Box::new(RepetitionNormalizer::new(
+            normalizer_config.max_repeats,
+        )));
+    }
+
+    if normalizer_config.spoken_commands {
+        info!("Spoken command normalization enabled");
+        normalizers.push(Box::new(SpokenCommandNormalizer::new(
+            &normalizer_config.command_words,
+        )?));
+    }
+
+    if normalizer_config.capitalize {
+        info!("Sentence capitalization enabled");
+        normalizers.push(Box::new(CapitalizationNormalizer::new()?));
+    }
+
+    if !normalizer_config.replacements.is_empty() {
+        info!(
+            "Loaded {} custom replacement rule(s)",
+            normalizer_config.replacements.len()
+        );
+        normalizers.push(Box::new(CustomReplacementNormalizer::new(
+            &normalizer_config.replacements,
+        )?));
+    }
+
+    Ok(normalizers)
+}
+
+/// Apply each normalizer in order, feeding every stage the previous stage's output.
+/// Output that is only whitespace counts as no speech and comes back empty.
+fn run_normalizers(normalizers: &[Box<dyn TranscriptionNormalizer>], text: &str) -> String {
+    let normalized = normalizers
+        .iter()
+        .fold(text.to_string(), |text, normalizer| {
+            let normalized = normalizer.normalize(&text);
+            debug!(
+                "{}: {} chars -> {} chars ({:+})",
+                normalizer.name(),
+                text.len(),
+                normalized.len(),
+                normalized.len() as i64 - text.len() as i64
+            );
+            normalized
+        });
+
+    if normalized.trim().is_empty() {
+        return String::new();
+    }
+    normalized
+}
+
+/// Words per second below which a long recording's transcript looks like noise
+const MIN_WORDS_PER_SEC: f64 = 0.2;
+
+/// Confidence for a transcription: the provider's score when it has one,
+/// otherwise a heuristic that distrusts a few words from a long recording
+pub fn confidence(transcription: &Transcription, audio_secs: f64) -> f64 {
+    if let Some(confidence) = transcription.confidence {
+        return confidence;
+    }
+    // Short recordings legitimately hold a word or two
+    if audio_secs < 5.0 {
+        return 1.0;
+    }
+    let words = transcription.text.split_whitespace().count() as f64;
+    (words / (audio_secs * MIN_WORDS_PER_SEC)).min(1.0)
+}
+
+/// Pipe text through a user's shell command and return its stdout. Fails when
+/// the command exits non-zero, prints nothing, or runs past `timeout`.
+pub async fn post_process(command: &str, text: &str, timeout: Duration) -> Result<String> {
+    debug!("Running post-process command: {}", command);
+    let mut child = tokio::process::Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to run post-process command '{command}'"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).await?;
+        // Dropping stdin sends EOF so the command can finish
+    }
+
+    let output = tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Post-process command timed out after {}s",
+                timeout.as_secs_f32()
+            )
+        })??;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "Post-process command exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let processed = String::from_utf8(output.stdout)
+        .context("Post-process command printed invalid UTF-8")?
+        .trim_end()
+        .to_string();
+    if processed.is_empty() {
+        return Err(anyhow::anyhow!("Post-process command printed nothing"));
+    }
+
+    info!(
+        "Post-processed text: {} chars -> {} chars",
+        text.len(),
+        processed.len()
+    );
+    Ok(processed)
+}
+
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+    use crate::config::ReplacementRule;
+    use crate::normalizer::OpenAIWhisperNormalizer;
+    use crate::whisper::TranscriptionProvider;
+    use std::collections::BTreeMap;
+    use std::future::Future;
+    use std::path::Path;
+    use std::pin::Pin;
+
+    /// Answers every request with canned text, or fails when it has none
+    struct MockProvider(Option<&'static str>);
+
+    impl TranscriptionProvider for MockProvider {
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+
+        fn transcribe<'a>(
+            &'a self,
+            _audio_path: &'a Path,
+            _language: &'a str,
+            _model: Option<&'a str>,
+        ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>> {
+            Box::pin(async move {
+                self.0
+                    .map(str::to_string)
+                    .context("mock provider has no transcription")
+            })
+        }
+    }
+
+    /// Answers with the language and model each request was sent with
+    struct EchoProvider;
+
+    impl TranscriptionProvider for EchoProvider {
+        fn name(&self) -> &'static str {
+            "echo"
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+
+        fn transcribe<'a>(
+            &'a self,
+            _audio_path: &'a Path,
+            language: &'a str,
+            model: Option<&'a str>,
+        ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>> {
+            Box::pin(async move { Ok(format!("{language} {}", model.unwrap_or("none"))) })
+        }
+    }
+
+    async fn transcribe_with(
+        response: Option<&'static str>,
+        normalizer_config: &NormalizerConfig,
+    ) -> Result<Transcription, ChezWizperError> {
+        let provider: Box<dyn TranscriptionProvider> = Box::new(MockProvider(response));
+        let service = TranscriptionService::new(provider, normalizer_config).unwrap();
+        service.transcribe(&PathBuf::from("unused.wav")).await
+    }
+
+    #[test]
+    fn test_api_providers_default_their_own_model() {
+        for (provider, expected) in [
+            ("groq", "whisper-large-v3-turbo"),
+            ("deepgram", "nova-3"),
+            ("openai-api", "whisper-1"),
+        ] {
+            let mut config = Config::default();
+            config.whisper.provider = Some(provider.to_string());
+            config.whisper.api_key = Some("test-key".to_string());
+            let service = TranscriptionService::from_config(&config, std::env::temp_dir()).unwrap();
+            assert_eq!(service.model(), Some(expected), "{provider}");
+        }
+
+        let mut config = Config::default();
+        config.whisper.provider = Some("groq".to_string());
+        config.whisper.api_key = Some("test-key".to_string());
+        config.whisper.model = Some("whisper-large-v3".to_string());
+        let service = TranscriptionService::from_config(&config, std::env::temp_dir()).unwrap();
+        assert_eq!(service.model(), Some("whisper-large-v3"));
+    }
+
+    #[test]
+    fn test_empty_api_key_falls_back_to_env() {
+        // No other test depends on these variables
+        for (provider, env) in [("groq", "GROQ_API_KEY"), ("deepgram", "DEEPGRAM_API_KEY")] {
+            let mut config = Config::default();
+            config.whisper.provider = Some(provider.to_string());
+            config.whisper.api_key = Some(String::new());
+
+            std::env::remove_var(env);
+            let err = TranscriptionService::from_config(&config, std::env::temp_dir())
+                .err()
+                .unwrap();
+            assert!(format!("{err:#}").contains(env), "{provider}: {err:#}");
+
+            std::env::set_var(env, "env-key");
+            let service = TranscriptionService::from_config(&config, std::env::temp_dir());
+            std::env::remove_var(env);
+            assert!(service.is_ok(), "{provider}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_runs_normalizer_chain() {
+        let normalizer_config = NormalizerConfig {
+            spoken_commands: true,
+            capitalize: true,
+            replacements: vec![ReplacementRule {
+                pattern: "chez whisper".to_string(),
+                replacement: "ChezWizper".to_string(),
+                regex: false,
+                case_insensitive: true,
+            }],
+            ..Default::default()
+        };
+
+        let transcription = transcribe_with(
+            Some(" [BLANK_AUDIO] i tried chez whisper comma it works period "),
+            &normalizer_config,
+        )
+        .await
+        .unwrap();
+        assert_eq!(transcription.text, "I tried ChezWizper, it works.");
+
+        // Stages that are off leave the text alone
+        let transcription =
+            transcribe_with(Some("hello comma world"), &NormalizerConfig::default())
+                .await
+                .unwrap();
+        assert_eq!(transcription.text, "hello comma world");
+    }
 
     #[tokio::test]
-    async fn test_transcription_service_creation() {
-        //TODO: implement this
-        // NOTE:: This would require mocking WhisperTranscriber
+    async fn test_transcribe_rejects_blank_output() {
+        for response in ["", "  \n\t", "[BLANK_AUDIO]", " (silence) "] {
+            let transcription = transcribe_with(Some(response), &NormalizerConfig::default())
+                .await
+                .unwrap();
+            assert_eq!(transcription.text, "", "{response:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_provider_failure_is_categorized() {
+        let err = transcribe_with(None, &NormalizerConfig::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ChezWizperError::Transcription(_)), "{err}");
+        assert!(err.to_string().contains("no transcription"));
+    }
+
+    #[tokio::test]
+    async fn test_transcriber_builders_set_defaults() {
+        let provider: Box<dyn TranscriptionProvider> = Box::new(EchoProvider);
+        let audio = PathBuf::from("unused.wav");
+
+        let whisper = WhisperTranscriber::from(provider);
+        assert_eq!(whisper.model(), None);
+        let text = whisper.transcribe(&audio, None, None).await.unwrap().text;
+        assert_eq!(text, "en none");
+
+        let whisper = whisper.with_model("whisper-large-v3").with_language("es");
+        assert_eq!(whisper.model(), Some("whisper-large-v3"));
+        let text = whisper.transcribe(&audio, None, None).await.unwrap().text;
+        assert_eq!(text, "es whisper-large-v3");
+
+        // Per-recording overrides still win
+        let text = whisper
+            .transcribe(&audio, Some("fr"), Some("turbo"))
+            .await
+            .unwrap()
+            .text;
+        assert_eq!(text, "fr turbo");
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_times_out_on_stuck_provider() {
+        // Accepts the upload and never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!(
+            "http://{}/v1/audio/transcriptions",
+            listener.local_addr().unwrap()
+        );
+        let _server = tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let whisper = WhisperTranscriber::new(ProviderConfig {
+            provider: Some("openai-compatible".to_string()),
+            api_endpoint: Some(endpoint),
+            ..Default::default()
+        })
+        .unwrap();
+        let service = TranscriptionService::new(whisper, &NormalizerConfig::default())
+            .unwrap()
+            .with_timeout(Duration::from_millis(200));
+
+        let audio =
+            std::env::temp_dir().join(format!("chezwizper_stuck_{}.wav", std::process::id()));
+        std::fs::write(&audio, b"RIFF").unwrap();
+        let err = service.transcribe(&audio).await.unwrap_err();
+        std::fs::remove_file(&audio).unwrap();
+
+        assert!(matches!(err, ChezWizperError::Timeout(_)), "{err}");
+    }
+
+    #[test]
+    fn test_run_normalizers_in_order() {
+        let normalizers: Vec<Box<dyn TranscriptionNormalizer>> = vec![
+            Box::new(OpenAIWhisperNormalizer::new()),
+            Box::new(SpokenCommandNormalizer::new(&BTreeMap::new()).unwrap()),
+        ];
+
+        assert_eq!(
+            run_normalizers(&normalizers, "  hello comma world  "),
+            "hello, world"
+        );
+        assert_eq!(run_normalizers(&[], " untouched "), " untouched ");
+    }
+
+    #[test]
+    fn test_run_normalizers_blank_output_is_empty() {
+        let normalizers: Vec<Box<dyn TranscriptionNormalizer>> =
+            vec![Box::new(AnnotationNormalizer::new().unwrap())];
+
+        for text in ["\n", " ", "\t\n ", "[BLANK_AUDIO]", " (silence) "] {
+            assert_eq!(run_normalizers(&normalizers, text), "", "{text:?}");
+        }
+        assert_eq!(run_normalizers(&[], " \n"), "");
+    }
+
+    #[test]
+    fn test_confidence_heuristic() {
+        let transcription = |text: &str, confidence| Transcription {
+            text: text.to_string(),
+            segments: None,
+            confidence,
+        };
+
+        assert_eq!(confidence(&transcription("you", Some(0.4)), 60.0), 0.4);
+        assert_eq!(confidence(&transcription("Yes.", None), 2.0), 1.0);
+        // One word from a minute of audio is most likely hallucinated
+        assert!(confidence(&transcription("you", None), 60.0) < 0.1);
+        assert_eq!(
+            confidence(&transcription("a normal sentence of dictation", None), 20.0),
+            1.0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_post_process() {
+        let timeout = Duration::from_secs(5);
+        assert_eq!(
+            post_process("tr a-z A-Z", "hello world", timeout)
+                .await
+                .unwrap(),
+            "HELLO WORLD"
+        );
+        assert!(post_process("exit 3", "hello", timeout).await.is_err());
+        assert!(post_process("cat > /dev/null", "hello", timeout)
+            .await
+            .is_err());
+        assert!(post_process("sleep 5", "hello", Duration::from_millis(100))
+            .await
+            .is_err());
     }
 }
@@ -1,11 +1,65 @@
-use crate::config::UiConfig;
+use crate::config::{NotificationColors, SoundsConfig, UiConfig};
 use anyhow::Result;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tokio::io::AsyncWriteExt;
 use tracing::{debug, info, warn};
+use which::which;
+
+/// Which state a notification reports, used to pick its color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NotificationKind {
+    Recording,
+    Paused,
+    Processing,
+    Complete,
+    Cancelled,
+    Warning,
+    Error,
+}
+
+/// Program used to show desktop notifications
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationBackend {
+    Hyprland,
+    NotifySend,
+}
+
+impl NotificationBackend {
+    /// Parse the `notification_backend` setting, resolving "auto" to the installed tool
+    pub fn parse(backend: &str) -> Result<Self> {
+        match backend {
+            "hyprland" => Ok(NotificationBackend::Hyprland),
+            "notify-send" => Ok(NotificationBackend::NotifySend),
+            "auto" => Ok(Self::detect()),
+            other => Err(anyhow::anyhow!(
+                "Unknown notification_backend '{}', expected \"hyprland\", \"notify-send\" or \"auto\"",
+                other
+            )),
+        }
+    }
+
+    fn detect() -> Self {
+        if which("hyprctl").is_ok() {
+            NotificationBackend::Hyprland
+        } else if which("notify-send").is_ok() {
+            NotificationBackend::NotifySend
+        } else {
+            warn!("Neither hyprctl nor notify-send found, notifications will not be shown");
+            NotificationBackend::Hyprland
+        }
+    }
+}
 
 pub struct Indicator {
     audio_feedback_enabled: bool,
+    /// Desktop notifications; off leaves sounds and waybar as the only feedback
+    notifications_enabled: bool,
+    notification_backend: NotificationBackend,
     notification_color: String,
+    notification_colors: NotificationColors,
+    notification_timeout_ms: u32,
+    sounds: SoundsConfig,
 }
 
 impl Default for Indicator {
@@ -16,16 +70,18 @@ impl Default for Indicator {
 
 impl Indicator {
     pub fn new() -> Self {
-        Self {
-            audio_feedback_enabled: true,
-            notification_color: "rgb(ff1744)".to_string(),
-        }
+        Self::from_config(&UiConfig::default())
     }
 
     pub fn from_config(config: &UiConfig) -> Self {
         Self {
             audio_feedback_enabled: true,
+            notifications_enabled: true,
+            notification_backend: NotificationBackend::Hyprland,
             notification_color: config.notification_color.clone(),
+            notification_colors: config.notification_colors.clone(),
+            notification_timeout_ms: config.notification_timeout_ms,
+            sounds: config.sounds.clone(),
         }
     }
 
@@ -34,11 +90,22 @@ impl Indicator {
         self
     }
 
+    pub fn with_notifications(mut self, enabled: bool) -> Self {
+        self.notifications_enabled = enabled;
+        self
+    }
+
+    pub fn with_notification_backend(mut self, backend: NotificationBackend) -> Self {
+        info!("Using {:?} notifications", backend);
+        self.notification_backend = backend;
+        self
+    }
+
     pub async fn show_recording(&self) -> Result<()> {
         info!("Showing recording indicator");
 
-        if let Err(e) = self.hyprland_notify("󰻃 Recording...") {
-            debug!("Hyprland notification failed: {}", e);
+        if let Err(e) = self.notify(NotificationKind::Recording, "󰻃 Recording...") {
+            debug!("Notification failed: {}", e);
         }
 
         // Play recording start sound
@@ -47,11 +114,24 @@ impl Indicator {
         Ok(())
     }
 
+    pub async fn show_paused(&self) -> Result<()> {
+        info!("Showing paused indicator");
+
+        if let Err(e) = self.notify(NotificationKind::Paused, "󰏤 Recording paused") {
+            debug!("Notification failed: {}", e);
+        }
+
+        // Same cue as stopping, since the mic stops listening
+        self.play_sound("stop").await;
+
+        Ok(())
+    }
+
     pub async fn show_processing(&self) -> Result<()> {
         info!("Showing processing indicator");
 
-        if let Err(e) = self.hyprland_notify("󰦖 Processing...") {
-            debug!("Hyprland notification failed: {}", e);
+        if let Err(e) = self.notify(NotificationKind::Processing, "󰦖 Processing...") {
+            debug!("Notification failed: {}", e);
         }
 
         // Play recording stop sound
@@ -63,14 +143,9 @@ impl Indicator {
     pub async fn show_complete(&self, text: &str) -> Result<()> {
         info!("Showing completion indicator");
 
-        let preview = if text.len() > 50 {
-            format!("{}...", &text[..50])
-        } else {
-            text.to_string()
-        };
-
-        if let Err(e) = self.hyprland_notify(&format!("󰸞 {preview}")) {
-            debug!("Hyprland notification failed: {}", e);
+        if let Err(e) = self.notify(NotificationKind::Complete, &format!("󰸞 {}", preview(text)))
+        {
+            debug!("Notification failed: {}", e);
         }
 
         // Play completion sound
@@ -79,24 +154,113 @@ impl Indicator {
         Ok(())
     }
 
+    pub async fn show_cancelled(&self) -> Result<()> {
+        info!("Showing cancelled indicator");
+
+        if let Err(e) = self.notify(NotificationKind::Cancelled, "󰜺 Recording cancelled") {
+            debug!("Notification failed: {}", e);
+        }
+
+        // Play cancel sound
+        self.play_sound("cancel").await;
+
+        Ok(())
+    }
+
     pub async fn show_error(&self, error: &str) -> Result<()> {
         warn!("Showing error: {}", error);
 
-        if let Err(e) = self.hyprland_notify(&format!("Error: {error}")) {
-            debug!("Hyprland notification failed: {}", e);
+        if let Err(e) = self.notify(NotificationKind::Error, &format!("Error: {error}")) {
+            debug!("Notification failed: {}", e);
+        }
+
+        // Errors have no generated tone, so only a configured sound is played
+        if self.sounds.error.is_some() {
+            self.play_sound("error").await;
         }
 
         Ok(())
     }
 
-    fn hyprland_notify(&self, title: &str) -> Result<()> {
-        Command::new("hyprctl")
-            .args(["notify", "-1", "3000", &self.notification_color, title])
-            .output()?;
+    pub async fn show_warning(&self, message: &str) -> Result<()> {
+        warn!("Showing warning: {}", message);
+
+        if let Err(e) = self.notify(NotificationKind::Warning, &format!("Warning: {message}")) {
+            debug!("Notification failed: {}", e);
+        }
 
         Ok(())
     }
 
+    fn notify(&self, kind: NotificationKind, title: &str) -> Result<()> {
+        if !self.notifications_enabled {
+            return Ok(());
+        }
+
+        let (program, args) = self.notification_command(kind, title);
+        let output = Command::new(program).args(args).output()?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Program and arguments that show a notification on the configured backend
+    fn notification_command(
+        &self,
+        kind: NotificationKind,
+        title: &str,
+    ) -> (&'static str, Vec<String>) {
+        match self.notification_backend {
+            NotificationBackend::Hyprland => ("hyprctl", self.hyprland_args(kind, title)),
+            NotificationBackend::NotifySend => ("notify-send", self.notify_send_args(kind, title)),
+        }
+    }
+
+    fn notify_send_args(&self, kind: NotificationKind, title: &str) -> Vec<String> {
+        let urgency = match kind {
+            NotificationKind::Error => "critical",
+            NotificationKind::Warning => "normal",
+            _ => "low",
+        };
+
+        vec![
+            "--app-name=ChezWizper".to_string(),
+            format!("--urgency={urgency}"),
+            format!("--expire-time={}", self.notification_timeout_ms),
+            title.to_string(),
+        ]
+    }
+
+    fn hyprland_args(&self, kind: NotificationKind, title: &str) -> Vec<String> {
+        vec![
+            "notify".to_string(),
+            "-1".to_string(),
+            self.notification_timeout_ms.to_string(),
+            self.color_for(kind).to_string(),
+            title.to_string(),
+        ]
+    }
+
+    /// The configured color for a state, falling back to `notification_color`
+    fn color_for(&self, kind: NotificationKind) -> &str {
+        let colors = &self.notification_colors;
+        let color = match kind {
+            NotificationKind::Recording => colors.recording.as_deref(),
+            NotificationKind::Processing => colors.processing.as_deref(),
+            NotificationKind::Complete => colors.complete.as_deref(),
+            NotificationKind::Error => colors.error.as_deref(),
+            NotificationKind::Paused | NotificationKind::Cancelled | NotificationKind::Warning => {
+                None
+            }
+        };
+        color.unwrap_or(&self.notification_color)
+    }
+
     async fn play_sound(&self, sound_type: &str) {
         if !self.audio_feedback_enabled {
             return;
@@ -104,32 +268,84 @@ impl Indicator {
 
         debug!("Playing {} sound", sound_type);
 
-        // Use a simple approach with system commands
+        let custom_sound = self.custom_sound(sound_type);
         let sound_type = sound_type.to_string();
         tokio::spawn(async move {
+            if let Some(path) = custom_sound {
+                match Self::play_sound_file(&path).await {
+                    Ok(()) => return,
+                    Err(e) => warn!(
+                        "Failed to play custom {} sound {:?}, using default: {}",
+                        sound_type, path, e
+                    ),
+                }
+            }
+
             if let Err(e) = Self::play_simple_sound(&sound_type).await {
                 debug!("Failed to play sound: {}", e);
             }
         });
     }
 
+    /// The user's sound file for this cue, if one is configured
+    fn custom_sound(&self, sound_type: &str) -> Option<PathBuf> {
+        let path = match sound_type {
+            "start" => self.sounds.start.as_ref(),
+            "stop" => self.sounds.stop.as_ref(),
+            "complete" => self.sounds.complete.as_ref(),
+            "error" => self.sounds.error.as_ref(),
+            _ => None,
+        }?;
+
+        // Allow "~/sounds/start.wav" in the config
+        match (path.strip_prefix("~"), dirs::home_dir()) {
+            (Ok(rest), Some(home)) => Some(home.join(rest)),
+            _ => Some(path.clone()),
+        }
+    }
+
+    async fn play_sound_file(path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Err(anyhow::anyhow!("file does not exist"));
+        }
+
+        // paplay decodes wav/ogg/flac; aplay covers ALSA-only systems with wav files
+        for player in ["paplay", "aplay"] {
+            match tokio::process::Command::new(player)
+                .arg(path)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .await
+            {
+                Ok(status) if status.success() => {
+                    debug!("Played {:?} with {}", path, player);
+                    return Ok(());
+                }
+                Ok(status) => debug!("{} could not play {:?}: {}", player, path, status),
+                Err(e) => debug!("{} unavailable: {}", player, e),
+            }
+        }
+
+        Err(anyhow::anyhow!("neither paplay nor aplay could play it"))
+    }
+
     async fn play_simple_sound(sound_type: &str) -> Result<()> {
         let (freq, duration_ms) = match sound_type {
             "start" => (800, 150),     // High pitch, short beep
             "stop" => (400, 200),      // Low pitch, longer beep
             "complete" => (1000, 100), // Very high pitch, very short beep
+            "cancel" => (300, 300),    // Lowest pitch, longest beep
             _ => (500, 150),
         };
 
         // Try generating custom beep tones first (more distinctive)
-        if let Ok(output) = Self::generate_beep_tone(freq, duration_ms).await {
-            if output.status.success() || output.status.code() == Some(124) {
-                debug!(
-                    "Played {} with generated tone ({}Hz, {}ms)",
-                    sound_type, freq, duration_ms
-                );
-                return Ok(());
-            }
+        if Self::generate_beep_tone(freq, duration_ms).await.is_ok() {
+            debug!(
+                "Played {} with generated tone ({}Hz, {}ms)",
+                sound_type, freq, duration_ms
+            );
+            return Ok(());
         }
 
         // Fallback to system sounds if tone generation fails
@@ -154,56 +370,202 @@ impl Indicator {
         Ok(())
     }
 
-    async fn generate_beep_tone(freq: u32, duration_ms: u32) -> Result<std::process::Output> {
-        // Try different methods to generate custom beep tones
-
-        // Method 1: Use speaker-test (if available)
-        let duration_secs = format!("{:.1}", duration_ms as f64 / 1000.0);
-        if let Ok(output) = Command::new("timeout")
-            .args([
-                &duration_secs,
-                "speaker-test",
-                "-t",
-                "sine",
-                "-f",
-                &freq.to_string(),
-                "-c",
-                "1",
-            ])
-            .output()
-        {
-            if output.status.success() || output.status.code() == Some(124) {
-                // 124 = timeout success
-                return Ok(output);
+    async fn generate_beep_tone(freq: u32, duration_ms: u32) -> Result<()> {
+        let pcm = sine_tone_pcm(freq, duration_ms);
+
+        // Raw 16-bit mono PCM, played through PulseAudio/PipeWire or straight to ALSA
+        let players: [(&str, &[&str]); 2] = [
+            (
+                "paplay",
+                &["--raw", "--format=s16le", "--rate=44100", "--channels=1"],
+            ),
+            (
+                "aplay",
+                &["-q", "-t", "raw", "-f", "S16_LE", "-r", "44100", "-c", "1"],
+            ),
+        ];
+
+        for (player, args) in players {
+            match Self::pipe_to_player(player, args, &pcm).await {
+                Ok(()) => return Ok(()),
+                Err(e) => debug!("{} could not play tone: {}", player, e),
             }
         }
 
-        // Method 2: Use beep command (if available)
-        if let Ok(output) = Command::new("beep")
-            .args(["-f", &freq.to_string(), "-l", &duration_ms.to_string()])
-            .output()
-        {
-            return Ok(output);
-        }
-
-        // Method 3: Generate tone with paplay + Python
-        let python_cmd = format!(
-            "python3 -c \"
-import math, sys
-samples = int(44100 * {duration_ms} / 1000)
-freq = {freq}
-for i in range(samples):
-    t = i / 44100.0
-    sample = math.sin(2.0 * math.pi * freq * t) * 0.3
-    sample_i16 = int(sample * 16384)
-    sys.stdout.buffer.write(sample_i16.to_bytes(2, 'little', signed=True))
-\" | paplay --raw --format=s16le --rate=44100 --channels=1"
-        );
+        Err(anyhow::anyhow!("No tone playback method available"))
+    }
+
+    async fn pipe_to_player(player: &str, args: &[&str], pcm: &[u8]) -> Result<()> {
+        let mut child = tokio::process::Command::new(player)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(pcm).await?;
+            // Dropping stdin closes the pipe so the player finishes
+        }
 
-        if let Ok(output) = Command::new("bash").args(["-c", &python_cmd]).output() {
-            return Ok(output);
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
         }
 
-        Err(anyhow::anyhow!("No tone generation method available"))
+        Ok(())
+    }
+}
+
+const TONE_SAMPLE_RATE: u32 = 44100;
+
+/// A sine tone as signed 16-bit little-endian mono PCM at 44.1kHz, with a short
+/// fade in and out so the beep doesn't click
+/// First 50 characters of a transcription, for the completion notification
+fn preview(text: &str) -> String {
+    let mut chars = text.chars();
+    let preview: String = chars.by_ref().take(50).collect();
+    if chars.next().is_some() {
+        format!("{preview}...")
+    } else {
+        preview
+    }
+}
+
+fn sine_tone_pcm(freq: u32, duration_ms: u32) -> Vec<u8> {
+    let samples = (TONE_SAMPLE_RATE as u64 * duration_ms as u64 / 1000) as usize;
+    let fade = (TONE_SAMPLE_RATE as usize / 200).min(samples / 2).max(1);
+
+    let mut pcm = Vec::with_capacity(samples * 2);
+    for i in 0..samples {
+        let t = i as f32 / TONE_SAMPLE_RATE as f32;
+        let envelope = (i.min(samples - 1 - i) as f32 / fade as f32).min(1.0);
+        let sample = (2.0 * std::f32::consts::PI * freq as f32 * t).sin() * 0.3 * envelope;
+        pcm.extend_from_slice(&((sample * i16::MAX as f32) as i16).to_le_bytes());
+    }
+    pcm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_for_falls_back_to_notification_color() {
+        let config = UiConfig {
+            notification_color: "rgb(111111)".to_string(),
+            notification_colors: NotificationColors {
+                complete: Some("rgb(00e676)".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let indicator = Indicator::from_config(&config);
+
+        assert_eq!(
+            indicator.color_for(NotificationKind::Complete),
+            "rgb(00e676)"
+        );
+        assert_eq!(
+            indicator.color_for(NotificationKind::Recording),
+            "rgb(111111)"
+        );
+        assert_eq!(
+            indicator.color_for(NotificationKind::Warning),
+            "rgb(111111)"
+        );
+    }
+
+    #[test]
+    fn test_notify_send_args() {
+        let indicator = Indicator::new().with_notification_backend(NotificationBackend::NotifySend);
+
+        assert_eq!(
+            indicator.notify_send_args(NotificationKind::Error, "Error: boom"),
+            vec![
+                "--app-name=ChezWizper",
+                "--urgency=critical",
+                "--expire-time=3000",
+                "Error: boom"
+            ]
+        );
+        assert_eq!(
+            indicator.notify_send_args(NotificationKind::Recording, "Recording")[1],
+            "--urgency=low"
+        );
+        assert!(NotificationBackend::parse("dunst").is_err());
+    }
+
+    #[test]
+    fn test_warnings_follow_notification_backend() {
+        let indicator = Indicator::new().with_notification_backend(NotificationBackend::NotifySend);
+        let (program, args) =
+            indicator.notification_command(NotificationKind::Warning, "Warning: hi");
+        assert_eq!(program, "notify-send");
+        assert_eq!(args[1], "--urgency=normal");
+
+        let indicator = indicator.with_notification_backend(NotificationBackend::Hyprland);
+        let (program, args) =
+            indicator.notification_command(NotificationKind::Warning, "Warning: hi");
+        assert_eq!(program, "hyprctl");
+        assert_eq!(args.last().unwrap(), "Warning: hi");
+    }
+
+    #[test]
+    fn test_preview_cuts_on_char_boundary() {
+        assert_eq!(preview("short"), "short");
+
+        // The emoji spans bytes 49..53
+        let text = format!("{}😀 and more", "a".repeat(49));
+        assert_eq!(preview(&text), format!("{}😀...", "a".repeat(49)));
+        assert_eq!(preview(&"é".repeat(50)), "é".repeat(50));
+    }
+
+    #[test]
+    fn test_sine_tone_pcm() {
+        let pcm = sine_tone_pcm(800, 150);
+
+        // 150ms of 16-bit mono samples at 44.1kHz
+        assert_eq!(pcm.len(), 6615 * 2);
+        // Fades in from and out to silence
+        assert_eq!(&pcm[..2], &[0, 0]);
+        assert_eq!(&pcm[pcm.len() - 2..], &[0, 0]);
+        let peak = pcm
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]).unsigned_abs())
+            .max()
+            .unwrap();
+        assert!(peak > 9000 && peak < 10000, "peak {peak}");
+        assert!(sine_tone_pcm(800, 0).is_empty());
+    }
+
+    #[test]
+    fn test_custom_sound_paths() {
+        let config = UiConfig {
+            sounds: SoundsConfig {
+                start: Some(PathBuf::from("/usr/share/sounds/start.wav")),
+                complete: Some(PathBuf::from("~/sounds/done.ogg")),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let indicator = Indicator::from_config(&config);
+
+        assert_eq!(
+            indicator.custom_sound("start"),
+            Some(PathBuf::from("/usr/share/sounds/start.wav"))
+        );
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(
+                indicator.custom_sound("complete"),
+                Some(home.join("sounds/done.ogg"))
+            );
+        }
+        assert_eq!(indicator.custom_sound("stop"), None);
+        assert_eq!(indicator.custom_sound("cancel"), None);
     }
 }
@@ -0,0 +1,135 @@
+use serde_json::{json, Value};
+use which::which;
+
+/// Transcription providers `whisper.provider` accepts; every build includes all of them
+pub use crate::config::PROVIDERS;
+
+/// Optional Cargo features this binary was built with
+pub const FEATURES: &[(&str, bool)] = &[
+    ("enigo", cfg!(feature = "enigo")),
+    ("groq-smoke-test", cfg!(feature = "groq-smoke-test")),
+];
+
+/// Crate version, taken from Cargo.toml at compile time
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Short commit hash set by build.rs, absent when built outside a git checkout
+pub const GIT_HASH: Option<&str> = option_env!("CHEZWIZPER_GIT_HASH");
+
+/// UTC build time set by build.rs (SOURCE_DATE_EPOCH when given)
+pub const BUILD_TIMESTAMP: &str = env!("CHEZWIZPER_BUILD_TIMESTAMP");
+
+/// External tools ChezWizper shells out to at runtime
+const TOOLS: &[&str] = &[
+    "whisper-cli",
+    "whisper-server",
+    "whisper",
+    "wtype",
+    "ydotool",
+    "xdotool",
+    "wl-copy",
+    "wl-paste",
+    "xclip",
+    "xsel",
+    "hyprctl",
+];
+
+/// Names of the enabled optional features
+pub fn enabled_features() -> Vec<&'static str> {
+    FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// Build profile this binary was compiled with
+pub fn build_profile() -> &'static str {
+    if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    }
+}
+
+/// Check which external tools are available on PATH
+pub fn detect_tools() -> Vec<(&'static str, bool)> {
+    TOOLS
+        .iter()
+        .map(|tool| (*tool, which(tool).is_ok()))
+        .collect()
+}
+
+/// Version and build information as JSON, used by the API
+pub fn info_json() -> Value {
+    let tools: serde_json::Map<String, Value> = detect_tools()
+        .into_iter()
+        .map(|(tool, available)| (tool.to_string(), Value::Bool(available)))
+        .collect();
+
+    json!({
+        "service": "chezwizper",
+        "version": VERSION,
+        "git_hash": GIT_HASH,
+        "build_timestamp": BUILD_TIMESTAMP,
+        "profile": build_profile(),
+        "target": format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+        "features": enabled_features(),
+        "providers": PROVIDERS,
+        "tools": tools,
+    })
+}
+
+/// Human readable version report, used by `--version`
+pub fn long_version() -> String {
+    let mut out = format!(
+        "chezwizper {VERSION} ({} build, {}-{})\n",
+        build_profile(),
+        std::env::consts::ARCH,
+        std::env::consts::OS
+    );
+
+    out.push_str(&format!("commit: {}\n", GIT_HASH.unwrap_or("unknown")));
+    out.push_str(&format!("built: {BUILD_TIMESTAMP}\n"));
+
+    let features = enabled_features();
+    out.push_str(&format!(
+        "features: {}\n",
+        if features.is_empty() {
+            "none".to_string()
+        } else {
+            features.join(", ")
+        }
+    ));
+
+    out.push_str(&format!("providers: {}\n", PROVIDERS.join(", ")));
+    out.push_str("tools:\n");
+    for (tool, available) in detect_tools() {
+        let mark = if available { "found" } else { "missing" };
+        out.push_str(&format!("  {tool:<12} {mark}\n"));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_matches_cargo() {
+        assert_eq!(VERSION, env!("CARGO_PKG_VERSION"));
+        assert!(long_version().starts_with(&format!("chezwizper {VERSION}")));
+        assert_eq!(info_json()["version"], VERSION);
+    }
+
+    #[test]
+    fn test_features_reported() {
+        assert_eq!(
+            enabled_features().contains(&"enigo"),
+            cfg!(feature = "enigo")
+        );
+        assert_eq!(info_json()["features"], json!(enabled_features()));
+        assert!(long_version().contains("features: "));
+    }
+}
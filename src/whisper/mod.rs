@@ -1,122 +1,610 @@
 use anyhow::{Context, Result};
-use std::path::PathBuf;
-use tracing::{info, warn};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::audio::{self, UploadFormat};
+use crate::error::{Categorize, ChezWizperError};
+use crate::normalizer::OutputStyle;
 
 mod provider;
 mod providers;
 
-use provider::TranscriptionProvider;
-use providers::{OpenAIProvider, OpenAIWhisperCliProvider, WhisperCppProvider};
+use provider::Task;
+pub use provider::{is_known_language, Segment, Transcription, TranscriptionProvider};
+pub use providers::azure_openai::AZURE_DEFAULT_API_VERSION;
+use providers::azure_openai::AZURE_OPENAI_API_KEY_ENV;
+use providers::deepgram_api::{DEEPGRAM_API_KEY_ENV, DEEPGRAM_DEFAULT_MODEL};
+use providers::groq_api::{GROQ_API_KEY_ENV, GROQ_DEFAULT_MODEL};
+pub use providers::openai_api::OPENAI_API_KEY_ENV;
+use providers::openai_api::{
+    AuthStyle, DEFAULT_MAX_ATTEMPTS, DEFAULT_TIMEOUT_SECS, OPENAI_ENDPOINT,
+};
+use providers::{
+    AzureOpenAIProvider, DeepgramProvider, GroqProvider, OpenAIProvider, OpenAIWhisperCliProvider,
+    WhisperCppProvider, WhisperCppServerProvider,
+};
+
+/// Longest chunk sent when a recording is over the provider's upload limit
+const DEFAULT_CHUNK_SECS: u64 = 600;
 
+/// Transcribes audio through a pluggable provider.
+///
+/// Constructors:
+/// - [`WhisperTranscriber::new`] is the entry point callers should use. It selects
+///   the provider named in `config.provider`, or auto-detects one when that is `None`.
+/// - [`WhisperTranscriber::with_provider`] forces a specific provider by name.
+/// - [`WhisperTranscriber::auto_detect`] always auto-detects, ignoring `config.provider`.
+///
+/// The `with_*` builders then adjust the built transcriber:
+///
+/// ```no_run
+/// # use chezwizper::whisper::{ProviderConfig, WhisperTranscriber};
+/// let whisper = WhisperTranscriber::new(ProviderConfig {
+///     provider: Some("groq".to_string()),
+///     ..Default::default()
+/// })?
+/// .with_model("whisper-large-v3")
+/// .with_language("es");
+/// # Ok::<(), chezwizper::error::ChezWizperError>(())
+/// ```
 pub struct WhisperTranscriber {
     provider: Box<dyn TranscriptionProvider>,
+    /// Replaces the provider's model when a transcription doesn't pick one
+    model: Option<String>,
     language: String,
+    chunk_secs: u64,
+    upload_format: UploadFormat,
 }
 
 impl WhisperTranscriber {
-    pub fn auto_detect(config: ProviderConfig) -> Result<Self> {
+    /// Build a transcriber, choosing the provider from `config.provider` or auto-detecting
+    pub fn new(config: ProviderConfig) -> Result<Self, ChezWizperError> {
+        match config.provider.clone() {
+            Some(provider_name) => Self::with_provider(&provider_name, config),
+            None => Self::auto_detect(config),
+        }
+    }
+
+    /// Build a transcriber using the first available provider, preferring local ones
+    pub fn auto_detect(config: ProviderConfig) -> Result<Self, ChezWizperError> {
+        let provider =
+            Self::auto_detect_provider(&config).categorize(ChezWizperError::ProviderUnavailable)?;
         let language = config.language.unwrap_or_else(|| "en".to_string());
-        let provider = Self::auto_detect_provider(config.command_path)?;
+        let chunk_secs = config.chunk_secs.unwrap_or(DEFAULT_CHUNK_SECS);
+        let upload_format = UploadFormat::parse(config.upload_format.as_deref().unwrap_or("wav"))
+            .categorize(ChezWizperError::ProviderUnavailable)?;
 
-        Ok(Self { provider, language })
+        Ok(Self {
+            provider,
+            model: None,
+            language,
+            chunk_secs,
+            upload_format,
+        })
     }
 
-    pub fn with_provider(provider_name: &str, config: ProviderConfig) -> Result<Self> {
+    /// Build a transcriber for the named provider (`openai-api`, `groq`, `deepgram`,
+    /// `azure-openai`, `openai-compatible`, `openai-cli`, `whisper-cpp`, `whisper-cpp-server`)
+    pub fn with_provider(
+        provider_name: &str,
+        config: ProviderConfig,
+    ) -> Result<Self, ChezWizperError> {
         let language = config.language.clone().unwrap_or_else(|| "en".to_string());
+        let chunk_secs = config.chunk_secs.unwrap_or(DEFAULT_CHUNK_SECS);
+        let upload_format = UploadFormat::parse(config.upload_format.as_deref().unwrap_or("wav"))
+            .categorize(ChezWizperError::ProviderUnavailable)?;
+        let provider = Self::build_provider(provider_name, config)
+            .categorize(ChezWizperError::ProviderUnavailable)?;
+
+        info!("Using {} for transcription", provider.name());
+
+        Ok(Self {
+            provider,
+            model: None,
+            language,
+            chunk_secs,
+            upload_format,
+        })
+    }
+
+    /// Model used when a transcription doesn't pick one, instead of the provider's
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Language used when a transcription doesn't pick one, or "auto" to detect it
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = language.into();
+        self
+    }
+
+    /// Longest chunk sent when a recording is over the provider's upload limit
+    pub fn with_chunk_secs(mut self, chunk_secs: u64) -> Self {
+        self.chunk_secs = chunk_secs;
+        self
+    }
+
+    /// Format recordings are converted to before they are uploaded
+    pub fn with_upload_format(mut self, upload_format: UploadFormat) -> Self {
+        self.upload_format = upload_format;
+        self
+    }
+
+    fn build_provider(
+        provider_name: &str,
+        config: ProviderConfig,
+    ) -> Result<Box<dyn TranscriptionProvider>> {
+        let task = Task::parse(config.task.as_deref())?;
+        let prompt = config.initial_prompt.clone();
+        let (timeout, max_attempts) = config.retry_policy();
 
         let provider: Box<dyn TranscriptionProvider> = match provider_name {
             "openai-api" => {
+                // The config key wins over the environment
                 let api_key = config
                     .api_key
-                    .context("api_key is required for OpenAI API provider")?;
+                    .filter(|key| !key.is_empty())
+                    .or_else(OpenAIProvider::api_key_from_env)
+                    .with_context(|| {
+                        format!(
+                            "api_key or {OPENAI_API_KEY_ENV} is required for OpenAI API provider"
+                        )
+                    })?;
+
+                let model = config.model.unwrap_or_else(|| "whisper-1".to_string());
+                Box::new(
+                    OpenAIProvider::new(api_key, config.api_endpoint, model)?
+                        .with_task(task)
+                        .with_initial_prompt(prompt)
+                        .with_retry_policy(timeout, max_attempts),
+                )
+            }
+            "openai-compatible" => {
+                // Self-hosted servers usually run without auth; OPENAI_API_KEY is
+                // deliberately not read so it is never sent to another host
+                let endpoint = config
+                    .api_endpoint
+                    .context("api_endpoint is required for the openai-compatible provider")?;
+                let (api_key, auth_style) = match config.api_key.filter(|key| !key.is_empty()) {
+                    Some(key) => (key, AuthStyle::Bearer),
+                    None => (String::new(), AuthStyle::None),
+                };
 
                 let model = config.model.unwrap_or_else(|| "whisper-1".to_string());
-                Box::new(OpenAIProvider::new(api_key, config.api_endpoint, model)?)
+                Box::new(
+                    OpenAIProvider::new(api_key, Some(endpoint), model)?
+                        .with_auth_style(auth_style)
+                        .with_task(task)
+                        .with_initial_prompt(prompt)
+                        .with_retry_policy(timeout, max_attempts),
+                )
+            }
+            "groq" => {
+                let api_key = config
+                    .api_key
+                    .filter(|key| !key.is_empty())
+                    .or_else(GroqProvider::api_key_from_env)
+                    .with_context(|| {
+                        format!("api_key or {GROQ_API_KEY_ENV} is required for Groq provider")
+                    })?;
+
+                // The default api_endpoint points at OpenAI, which Groq keys can't use
+                let endpoint = config.api_endpoint.filter(|e| e != OPENAI_ENDPOINT);
+                let model = config
+                    .model
+                    .unwrap_or_else(|| GROQ_DEFAULT_MODEL.to_string());
+                Box::new(
+                    GroqProvider::new(api_key, endpoint, model)?
+                        .with_task(task)
+                        .with_initial_prompt(prompt)
+                        .with_retry_policy(timeout, max_attempts),
+                )
+            }
+            "deepgram" => {
+                let api_key = config
+                    .api_key
+                    .filter(|key| !key.is_empty())
+                    .or_else(DeepgramProvider::api_key_from_env)
+                    .with_context(|| {
+                        format!(
+                            "api_key or {DEEPGRAM_API_KEY_ENV} is required for Deepgram provider"
+                        )
+                    })?;
+
+                if task == Task::Translate {
+                    warn!("Deepgram does not translate, transcribing in the spoken language");
+                }
+                if prompt.is_some() {
+                    warn!("Deepgram does not take an initial_prompt, ignoring it");
+                }
+
+                let endpoint = config.api_endpoint.filter(|e| e != OPENAI_ENDPOINT);
+                let model = config
+                    .model
+                    .unwrap_or_else(|| DEEPGRAM_DEFAULT_MODEL.to_string());
+                Box::new(
+                    DeepgramProvider::new(api_key, endpoint, model)?
+                        .with_retry_policy(timeout, max_attempts),
+                )
+            }
+            "azure-openai" => {
+                let api_key = config
+                    .api_key
+                    .filter(|key| !key.is_empty())
+                    .or_else(AzureOpenAIProvider::api_key_from_env)
+                    .with_context(|| {
+                        format!(
+                            "api_key or {AZURE_OPENAI_API_KEY_ENV} is required for Azure OpenAI provider"
+                        )
+                    })?;
+
+                // Azure routes by deployment, the model field is only informational
+                let deployment = config
+                    .azure_deployment
+                    .or(config.model)
+                    .context("azure_deployment is required for Azure OpenAI provider")?;
+                let endpoint = match config.api_endpoint.filter(|e| e != OPENAI_ENDPOINT) {
+                    Some(endpoint) => endpoint,
+                    None => {
+                        let resource = config.azure_resource.context(
+                            "azure_resource or api_endpoint is required for Azure OpenAI provider",
+                        )?;
+                        let api_version = config
+                            .azure_api_version
+                            .unwrap_or_else(|| AZURE_DEFAULT_API_VERSION.to_string());
+                        AzureOpenAIProvider::endpoint(&resource, &deployment, &api_version)
+                    }
+                };
+                Box::new(
+                    AzureOpenAIProvider::new(api_key, endpoint, deployment)?
+                        .with_task(task)
+                        .with_initial_prompt(prompt)
+                        .with_retry_policy(timeout, max_attempts),
+                )
             }
             "openai-cli" => {
                 let model = config.model.unwrap_or_else(|| "base".to_string());
-                Box::new(OpenAIWhisperCliProvider::new(config.command_path, model)?)
+                Box::new(
+                    OpenAIWhisperCliProvider::new(config.command_path, model, config.temp_dir)?
+                        .with_task(task)
+                        .with_initial_prompt(prompt),
+                )
             }
             "whisper-cpp" => {
                 let model = config.model.unwrap_or_else(|| "base".to_string());
-                Box::new(WhisperCppProvider::new(
-                    config.command_path,
-                    model,
-                    config.model_path,
-                )?)
+                Box::new(
+                    WhisperCppProvider::new(config.command_path, model, config.model_path)?
+                        .with_task(task)
+                        .with_initial_prompt(prompt)
+                        .with_resident_server(config.keep_model_loaded.unwrap_or(false)),
+                )
+            }
+            "whisper-cpp-server" => {
+                // The model is whatever the server was started with
+                let endpoint = config.api_endpoint.filter(|e| e != OPENAI_ENDPOINT);
+                Box::new(
+                    WhisperCppServerProvider::new(endpoint)?
+                        .with_task(task)
+                        .with_initial_prompt(prompt)
+                        .with_timeout(timeout),
+                )
             }
             _ => {
                 warn!("Unknown provider '{}', using auto-detection", provider_name);
-                Self::auto_detect_provider(config.command_path)?
+                Self::auto_detect_provider(&config)?
             }
         };
 
-        info!("Using {} for transcription", provider.name());
-
-        Ok(Self { provider, language })
+        Ok(provider)
     }
 
-    fn auto_detect_provider(custom_path: Option<String>) -> Result<Box<dyn TranscriptionProvider>> {
+    fn auto_detect_provider(config: &ProviderConfig) -> Result<Box<dyn TranscriptionProvider>> {
         info!("Auto-detecting transcription provider...");
 
+        let custom_path = config.command_path.clone();
+        let task = Task::parse(config.task.as_deref())?;
+
         // Note: OpenAI API requires explicit configuration with api_key
-        // Auto-detection skips API providers that need authentication
+        // Local providers are preferred; Groq and Deepgram are used only as a fallback
+        // via GROQ_API_KEY and DEEPGRAM_API_KEY
 
-        if let Ok(provider) = OpenAIWhisperCliProvider::new(custom_path.clone(), "base".to_string())
-        {
+        if let Ok(provider) = OpenAIWhisperCliProvider::new(
+            custom_path.clone(),
+            "base".to_string(),
+            config.temp_dir.clone(),
+        ) {
             if provider.is_available() {
                 info!("Auto-detected: OpenAI Whisper CLI");
-                return Ok(Box::new(provider));
+                return Ok(Box::new(
+                    provider
+                        .with_task(task)
+                        .with_initial_prompt(config.initial_prompt.clone()),
+                ));
             }
         }
 
         if let Ok(provider) = WhisperCppProvider::new(custom_path, "base".to_string(), None) {
             if provider.is_available() {
                 info!("Auto-detected: whisper.cpp");
-                return Ok(Box::new(provider));
+                return Ok(Box::new(
+                    provider
+                        .with_task(task)
+                        .with_initial_prompt(config.initial_prompt.clone())
+                        .with_resident_server(config.keep_model_loaded.unwrap_or(false)),
+                ));
             }
         }
 
+        // An api_key in the config means the user set up OpenAI explicitly
+        if let Some(api_key) = config.api_key.clone().filter(|key| !key.is_empty()) {
+            info!("Auto-detected: OpenAI API (api_key from config)");
+            return Ok(Box::new(Self::auto_openai_provider(api_key, config, task)?));
+        }
+
+        // Groq is only picked up when its key is present in the environment
+        if let Some(api_key) = GroqProvider::api_key_from_env() {
+            let (timeout, max_attempts) = config.retry_policy();
+            let provider = GroqProvider::new(api_key, None, GROQ_DEFAULT_MODEL.to_string())?
+                .with_task(task)
+                .with_initial_prompt(config.initial_prompt.clone())
+                .with_retry_policy(timeout, max_attempts);
+            info!("Auto-detected: Groq API ({} set)", GROQ_API_KEY_ENV);
+            return Ok(Box::new(provider));
+        }
+
+        if let Some(api_key) = DeepgramProvider::api_key_from_env() {
+            let (timeout, max_attempts) = config.retry_policy();
+            let provider =
+                DeepgramProvider::new(api_key, None, DEEPGRAM_DEFAULT_MODEL.to_string())?
+                    .with_retry_policy(timeout, max_attempts);
+            info!("Auto-detected: Deepgram API ({} set)", DEEPGRAM_API_KEY_ENV);
+            return Ok(Box::new(provider));
+        }
+
+        if let Some(api_key) = OpenAIProvider::api_key_from_env() {
+            info!("Auto-detected: OpenAI API ({} set)", OPENAI_API_KEY_ENV);
+            return Ok(Box::new(Self::auto_openai_provider(api_key, config, task)?));
+        }
+
         Err(anyhow::anyhow!(
-            "No transcription provider available. Install whisper-cpp, openai-whisper, or configure OpenAI API with api_key"
+            "No transcription provider available. Install whisper-cpp, openai-whisper, set {}, {} or {}, or configure OpenAI API with api_key",
+            GROQ_API_KEY_ENV,
+            DEEPGRAM_API_KEY_ENV,
+            OPENAI_API_KEY_ENV
         ))
     }
 
-    pub async fn transcribe(&self, audio_path: &PathBuf) -> Result<String> {
+    fn auto_openai_provider(
+        api_key: String,
+        config: &ProviderConfig,
+        task: Task,
+    ) -> Result<OpenAIProvider> {
+        let (timeout, max_attempts) = config.retry_policy();
+        Ok(OpenAIProvider::new(
+            api_key,
+            config.api_endpoint.clone(),
+            "whisper-1".to_string(),
+        )?
+        .with_task(task)
+        .with_initial_prompt(config.initial_prompt.clone())
+        .with_retry_policy(timeout, max_attempts))
+    }
+
+    /// Transcribe in `language` with `model`, falling back to the configured
+    /// language and model for whichever is `None`
+    pub async fn transcribe(
+        &self,
+        audio_path: &PathBuf,
+        language: Option<&str>,
+        model: Option<&str>,
+    ) -> Result<Transcription, ChezWizperError> {
+        self.transcribe_audio(audio_path, language, model)
+            .await
+            .categorize(ChezWizperError::Transcription)
+    }
+
+    async fn transcribe_audio(
+        &self,
+        audio_path: &PathBuf,
+        language: Option<&str>,
+        model: Option<&str>,
+    ) -> Result<Transcription> {
+        let language = language.unwrap_or(&self.language);
+        let model = model.or(self.model.as_deref());
         info!(
             "Transcribing audio file: {:?} with {}",
             audio_path,
             self.provider.name()
         );
+
+        // Only uploads are compressed; local providers read the WAV directly
+        let encoded = match self.provider.max_upload_bytes() {
+            Some(_) if self.upload_format != UploadFormat::Wav => {
+                match audio::encode(audio_path, self.upload_format).await {
+                    Ok(file) => Some(file),
+                    Err(e) => {
+                        warn!("{:#}, uploading the WAV instead", e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        let upload_path = encoded.as_ref().map_or(audio_path, |file| file.path());
+
+        let size = std::fs::metadata(upload_path).map(|m| m.len()).unwrap_or(0);
+        if let Some(limit) = self
+            .provider
+            .max_upload_bytes()
+            .filter(|&limit| size > limit)
+        {
+            // Chunks are cut from the WAV, which split_wav can read
+            return self
+                .transcribe_in_chunks(audio_path, limit, language, model)
+                .await;
+        }
+
         self.provider
-            .transcribe(audio_path.as_path(), &self.language)
+            .transcribe_with_segments(upload_path.as_path(), language, model)
             .await
     }
 
-    pub fn is_openai_whisper(&self) -> bool {
-        self.provider.name() == "OpenAI Whisper CLI"
+    /// Send an oversized recording piece by piece and join the results in order
+    async fn transcribe_in_chunks(
+        &self,
+        audio_path: &Path,
+        max_bytes: u64,
+        language: &str,
+        model: Option<&str>,
+    ) -> Result<Transcription> {
+        let chunks = audio::split_wav(audio_path, self.chunk_secs, max_bytes)
+            .context("Failed to split oversized recording")?;
+        info!(
+            "Recording is over the {} MB upload limit of {}, sending it in {} chunks",
+            max_bytes / (1024 * 1024),
+            self.provider.name(),
+            chunks.len()
+        );
+
+        let mut texts = Vec::with_capacity(chunks.len());
+        let mut segments = Some(Vec::new());
+        let mut confidence = Some(1.0f64);
+        for (index, chunk) in chunks.iter().enumerate() {
+            debug!(
+                "Transcribing chunk {}/{} starting at {:.1}s",
+                index + 1,
+                chunks.len(),
+                chunk.start_secs
+            );
+            let part = self
+                .provider
+                .transcribe_with_segments(chunk.file.path(), language, model)
+                .await
+                .with_context(|| format!("Chunk {} of {} failed", index + 1, chunks.len()))?;
+
+            texts.push(part.text.trim().to_string());
+            // The weakest chunk decides, and only when every chunk was scored
+            confidence = confidence.zip(part.confidence).map(|(a, b)| a.min(b));
+            // Timestamps are only kept when every chunk has them
+            segments = match (segments, part.segments) {
+                (Some(mut all), Some(part)) => {
+                    all.extend(part.into_iter().map(|segment| Segment {
+                        start: segment.start + chunk.start_secs,
+                        end: segment.end + chunk.start_secs,
+                        text: segment.text,
+                    }));
+                    Some(all)
+                }
+                _ => None,
+            };
+        }
+
+        texts.retain(|text| !text.is_empty());
+        Ok(Transcription {
+            text: texts.join(" "),
+            segments,
+            confidence,
+        })
+    }
+
+    pub fn provider_name(&self) -> &'static str {
+        self.provider.name()
+    }
+
+    /// Whether the provider can transcribe right now (binary present, API key set)
+    pub fn is_available(&self) -> bool {
+        self.provider.is_available()
+    }
+
+    /// Format of the provider's raw output
+    pub fn output_style(&self) -> OutputStyle {
+        self.provider.output_style()
+    }
+
+    /// Model used when a recording doesn't pick one, if the provider chooses it
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref().or_else(|| self.provider.model())
+    }
+}
+
+/// Wrap an already built provider, e.g. one of your own or a test double,
+/// with the default language, chunk length and upload format
+impl From<Box<dyn TranscriptionProvider>> for WhisperTranscriber {
+    fn from(provider: Box<dyn TranscriptionProvider>) -> Self {
+        Self {
+            provider,
+            model: None,
+            language: "en".to_string(),
+            chunk_secs: DEFAULT_CHUNK_SECS,
+            upload_format: UploadFormat::Wav,
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct ProviderConfig {
+    /// Provider name used by `WhisperTranscriber::new`; `None` auto-detects
+    pub provider: Option<String>,
     pub model: Option<String>,
     pub model_path: Option<String>,
     pub language: Option<String>,
     pub command_path: Option<String>,
     pub api_endpoint: Option<String>,
     pub api_key: Option<String>,
+    /// Directory for intermediate files written by CLI providers
+    pub temp_dir: Option<PathBuf>,
+    /// "transcribe" (default) or "translate" to English
+    pub task: Option<String>,
+    /// Vocabulary hint passed to the model (names, acronyms, jargon)
+    pub initial_prompt: Option<String>,
+    /// Per-request timeout for API providers
+    pub request_timeout_secs: Option<u64>,
+    /// Attempts made for transient API failures (429/5xx, connection errors)
+    pub max_attempts: Option<u32>,
+    /// Longest chunk sent when a recording is over the provider's upload limit
+    pub chunk_secs: Option<u64>,
+    /// "wav" (default), "mp3" or "opus"; API uploads are converted with ffmpeg
+    pub upload_format: Option<String>,
+    /// Azure OpenAI resource, deployment and API version, used to build its endpoint
+    pub azure_resource: Option<String>,
+    pub azure_deployment: Option<String>,
+    pub azure_api_version: Option<String>,
+    /// Keep the whisper.cpp model in memory between recordings (whisper-cpp only)
+    pub keep_model_loaded: Option<bool>,
+}
+
+impl ProviderConfig {
+    fn retry_policy(&self) -> (Duration, u32) {
+        (
+            Duration::from_secs(self.request_timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS)),
+            self.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS),
+        )
+    }
 }
 
 impl Default for ProviderConfig {
     fn default() -> Self {
         Self {
+            provider: None,
             model: None,
             model_path: None,
             language: Some("en".to_string()),
             command_path: None,
             api_endpoint: None,
             api_key: None,
+            temp_dir: None,
+            task: None,
+            initial_prompt: None,
+            request_timeout_secs: None,
+            max_attempts: None,
+            chunk_secs: None,
+            upload_format: None,
+            azure_resource: None,
+            azure_deployment: None,
+            azure_api_version: None,
+            keep_model_loaded: None,
         }
     }
 }
@@ -1,16 +1,148 @@
 use anyhow::Result;
+use serde::Serialize;
 use std::future::Future;
 use std::path::Path;
 use std::pin::Pin;
 
+use crate::normalizer::OutputStyle;
+
+/// What the model should produce from the audio
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Task {
+    /// Text in the spoken language
+    #[default]
+    Transcribe,
+    /// English text regardless of the spoken language
+    Translate,
+}
+
+impl Task {
+    pub fn parse(task: Option<&str>) -> Result<Self> {
+        match task.unwrap_or("transcribe") {
+            "transcribe" => Ok(Task::Transcribe),
+            "translate" => Ok(Task::Translate),
+            other => Err(anyhow::anyhow!(
+                "Unknown task '{}', expected \"transcribe\" or \"translate\"",
+                other
+            )),
+        }
+    }
+}
+
+/// Language value meaning "let the model detect the spoken language"
+pub const AUTO_LANGUAGE: &str = "auto";
+
+/// Whether `language` asks for detection rather than a fixed language
+pub fn is_auto_language(language: &str) -> bool {
+    language.is_empty() || language.eq_ignore_ascii_case(AUTO_LANGUAGE)
+}
+
+/// ISO 639-1 codes Whisper can transcribe
+const LANGUAGE_CODES: &[&str] = &[
+    "af", "am", "ar", "as", "az", "ba", "be", "bg", "bn", "bo", "br", "bs", "ca", "cs", "cy", "da",
+    "de", "el", "en", "es", "et", "eu", "fa", "fi", "fo", "fr", "gl", "gu", "ha", "he", "hi", "hr",
+    "ht", "hu", "hy", "id", "is", "it", "ja", "jw", "ka", "kk", "km", "kn", "ko", "la", "lb", "ln",
+    "lo", "lt", "lv", "mg", "mi", "mk", "ml", "mn", "mr", "ms", "mt", "my", "ne", "nl", "nn", "no",
+    "oc", "pa", "pl", "ps", "pt", "ro", "ru", "sa", "sd", "si", "sk", "sl", "sn", "so", "sq", "sr",
+    "su", "sv", "sw", "ta", "te", "tg", "th", "tk", "tl", "tr", "tt", "uk", "ur", "uz", "vi", "yi",
+    "yo", "yue", "zh",
+];
+
+/// Whether `language` is a code Whisper knows, or "auto"
+pub fn is_known_language(language: &str) -> bool {
+    is_auto_language(language) || LANGUAGE_CODES.contains(&language.to_ascii_lowercase().as_str())
+}
+
+/// A timed span of transcribed speech, times in seconds from the start of the audio
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Segment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+/// Transcribed text plus per-segment timestamps when the provider supports them
+#[derive(Debug, Clone, Default)]
+pub struct Transcription {
+    pub text: String,
+    pub segments: Option<Vec<Segment>>,
+    /// How likely the text is real speech (0.0 - 1.0), for providers that report it
+    pub confidence: Option<f64>,
+}
+
 pub trait TranscriptionProvider: Send + Sync {
     fn name(&self) -> &'static str;
 
     fn is_available(&self) -> bool;
 
+    /// Format of the text this provider returns, which picks the normalizer
+    /// that cleans it up
+    fn output_style(&self) -> OutputStyle {
+        OutputStyle::Plain
+    }
+
+    /// Model requests go to when no per-recording model is given; `None` when
+    /// the server or the loaded model file decides
+    fn model(&self) -> Option<&str> {
+        None
+    }
+
+    /// Largest audio file accepted in one request; larger recordings are sent in chunks
+    fn max_upload_bytes(&self) -> Option<u64> {
+        None
+    }
+
+    /// Transcribe with `model`, or with the model the provider was built with when `None`
     fn transcribe<'a>(
         &'a self,
         audio_path: &'a Path,
         language: &'a str,
+        model: Option<&'a str>,
     ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>>;
+
+    /// Transcribe and also return segment timestamps. Providers without
+    /// timestamp support fall back to plain text with `segments: None`.
+    fn transcribe_with_segments<'a>(
+        &'a self,
+        audio_path: &'a Path,
+        language: &'a str,
+        model: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = Result<Transcription>> + Send + 'a>> {
+        Box::pin(async move {
+            let text = self.transcribe(audio_path, language, model).await?;
+            Ok(Transcription {
+                text,
+                ..Default::default()
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_parse() {
+        assert_eq!(Task::parse(None).unwrap(), Task::Transcribe);
+        assert_eq!(Task::parse(Some("translate")).unwrap(), Task::Translate);
+        assert!(Task::parse(Some("summarize")).is_err());
+    }
+
+    #[test]
+    fn test_is_auto_language() {
+        assert!(is_auto_language("auto"));
+        assert!(is_auto_language("Auto"));
+        assert!(is_auto_language(""));
+        assert!(!is_auto_language("en"));
+    }
+
+    #[test]
+    fn test_is_known_language() {
+        assert!(is_known_language("es"));
+        assert!(is_known_language("EN"));
+        assert!(is_known_language("auto"));
+        assert!(!is_known_language("english"));
+        assert!(!is_known_language("xx"));
+    }
 }
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::time::Duration;
+use tracing::info;
+
+use crate::whisper::provider::{Task, Transcription, TranscriptionProvider};
+use crate::whisper::providers::openai_api::AuthStyle;
+use crate::whisper::providers::OpenAIProvider;
+
+pub const AZURE_OPENAI_API_KEY_ENV: &str = "AZURE_OPENAI_API_KEY";
+pub const AZURE_DEFAULT_API_VERSION: &str = "2024-06-01";
+
+/// Azure OpenAI serves the same Whisper API per deployment, with an `api-key`
+/// header instead of a bearer token, so requests go through `OpenAIProvider`
+pub struct AzureOpenAIProvider {
+    inner: OpenAIProvider,
+}
+
+impl AzureOpenAIProvider {
+    /// `endpoint` is the full transcriptions URL, see [`AzureOpenAIProvider::endpoint`]
+    pub fn new(api_key: String, endpoint: String, deployment: String) -> Result<Self> {
+        info!(
+            "Initialized Azure OpenAI provider with deployment: {}",
+            deployment
+        );
+
+        Ok(Self {
+            inner: OpenAIProvider::new(api_key, Some(endpoint), deployment)?
+                .with_auth_style(AuthStyle::ApiKeyHeader),
+        })
+    }
+
+    /// Transcriptions URL for a deployment on an Azure OpenAI resource
+    pub fn endpoint(resource: &str, deployment: &str, api_version: &str) -> String {
+        format!(
+            "https://{resource}.openai.azure.com/openai/deployments/{deployment}/audio/transcriptions?api-version={api_version}"
+        )
+    }
+
+    pub fn with_task(mut self, task: Task) -> Self {
+        self.inner = self.inner.with_task(task);
+        self
+    }
+
+    pub fn with_initial_prompt(mut self, prompt: Option<String>) -> Self {
+        self.inner = self.inner.with_initial_prompt(prompt);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, timeout: Duration, max_attempts: u32) -> Self {
+        self.inner = self.inner.with_retry_policy(timeout, max_attempts);
+        self
+    }
+
+    /// Read the API key from `AZURE_OPENAI_API_KEY`, ignoring empty values
+    pub fn api_key_from_env() -> Option<String> {
+        std::env::var(AZURE_OPENAI_API_KEY_ENV)
+            .ok()
+            .filter(|key| !key.is_empty())
+    }
+}
+
+impl TranscriptionProvider for AzureOpenAIProvider {
+    fn name(&self) -> &'static str {
+        "Azure OpenAI"
+    }
+
+    fn is_available(&self) -> bool {
+        self.inner.is_available()
+    }
+
+    fn max_upload_bytes(&self) -> Option<u64> {
+        self.inner.max_upload_bytes()
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio_path: &'a Path,
+        language: &'a str,
+        model: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>> {
+        self.inner.transcribe(audio_path, language, model)
+    }
+
+    fn transcribe_with_segments<'a>(
+        &'a self,
+        audio_path: &'a Path,
+        language: &'a str,
+        model: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = Result<Transcription>> + Send + 'a>> {
+        self.inner
+            .transcribe_with_segments(audio_path, language, model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint() {
+        assert_eq!(
+            AzureOpenAIProvider::endpoint("contoso", "whisper", AZURE_DEFAULT_API_VERSION),
+            "https://contoso.openai.azure.com/openai/deployments/whisper/audio/transcriptions?api-version=2024-06-01"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::time::Duration;
+use tracing::{debug, info};
+
+use crate::whisper::provider::{is_auto_language, Segment, Transcription, TranscriptionProvider};
+use crate::whisper::providers::openai_api::{
+    mime_type, DEFAULT_MAX_ATTEMPTS, DEFAULT_TIMEOUT_SECS,
+};
+use crate::whisper::providers::retry::send_with_retries;
+
+pub const DEEPGRAM_ENDPOINT: &str = "https://api.deepgram.com/v1/listen";
+pub const DEEPGRAM_DEFAULT_MODEL: &str = "nova-3";
+pub const DEEPGRAM_API_KEY_ENV: &str = "DEEPGRAM_API_KEY";
+
+#[derive(Debug, Deserialize)]
+struct ListenResponse {
+    results: ListenResults,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListenResults {
+    #[serde(default)]
+    channels: Vec<Channel>,
+    #[serde(default)]
+    utterances: Vec<Utterance>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Channel {
+    #[serde(default)]
+    alternatives: Vec<Alternative>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Alternative {
+    transcript: String,
+    #[serde(default)]
+    confidence: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Utterance {
+    start: f64,
+    end: f64,
+    transcript: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    err_code: Option<String>,
+    err_msg: Option<String>,
+}
+
+impl ListenResponse {
+    /// Top alternative of the first channel; recordings are always mono
+    fn best_alternative(&self) -> Option<&Alternative> {
+        self.results
+            .channels
+            .first()
+            .and_then(|channel| channel.alternatives.first())
+    }
+}
+
+/// Deepgram's prerecorded endpoint takes the raw audio as the request body
+/// rather than a multipart form, with options passed as query parameters
+pub struct DeepgramProvider {
+    client: reqwest::Client,
+    api_key: String,
+    endpoint: String,
+    model: String,
+    timeout: Duration,
+    max_attempts: u32,
+}
+
+impl DeepgramProvider {
+    pub fn new(api_key: String, endpoint: Option<String>, model: String) -> Result<Self> {
+        let endpoint = endpoint.unwrap_or_else(|| DEEPGRAM_ENDPOINT.to_string());
+        info!("Initialized Deepgram provider with model: {}", model);
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            api_key,
+            endpoint,
+            model,
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        })
+    }
+
+    /// Per-request timeout and how many times transient failures are attempted
+    pub fn with_retry_policy(mut self, timeout: Duration, max_attempts: u32) -> Self {
+        self.timeout = timeout;
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Read the API key from `DEEPGRAM_API_KEY`, ignoring empty values
+    pub fn api_key_from_env() -> Option<String> {
+        std::env::var(DEEPGRAM_API_KEY_ENV)
+            .ok()
+            .filter(|key| !key.is_empty())
+    }
+
+    /// Query parameters for a request. Deepgram detects the language itself
+    /// when asked to instead of treating a missing `language` as auto.
+    fn query_params(&self, language: &str, model: &str, utterances: bool) -> Vec<(&str, String)> {
+        let mut params = vec![
+            ("model", model.to_string()),
+            ("smart_format", "true".to_string()),
+        ];
+
+        if is_auto_language(language) {
+            params.push(("detect_language", "true".to_string()));
+        } else {
+            params.push(("language", language.to_string()));
+        }
+
+        if utterances {
+            params.push(("utterances", "true".to_string()));
+        }
+
+        params
+    }
+
+    /// POST the audio with retries and return the parsed response
+    async fn send_request(
+        &self,
+        audio_path: &Path,
+        language: &str,
+        model: Option<&str>,
+        utterances: bool,
+    ) -> Result<ListenResponse> {
+        info!("Transcribing audio file via Deepgram API: {:?}", audio_path);
+
+        let audio_data = tokio::fs::read(audio_path)
+            .await
+            .context("Failed to read audio file")?;
+
+        let model = model.unwrap_or(&self.model);
+        debug!(
+            "Sending request to Deepgram API with model: {}, language: {}",
+            model, language
+        );
+        let params = self.query_params(language, model, utterances);
+
+        let response_text = send_with_retries(
+            "Deepgram API",
+            self.max_attempts,
+            || {
+                Ok(self
+                    .client
+                    .post(&self.endpoint)
+                    .query(&params)
+                    .header("Authorization", format!("Token {}", self.api_key))
+                    .header("Content-Type", mime_type(audio_path))
+                    .timeout(self.timeout)
+                    .body(audio_data.clone()))
+            },
+            |body| {
+                let error: ErrorResponse = serde_json::from_str(body).ok()?;
+                Some(format!("{} (code: {:?})", error.err_msg?, error.err_code))
+            },
+        )
+        .await?;
+
+        serde_json::from_str(&response_text).context("Failed to parse Deepgram response")
+    }
+}
+
+impl TranscriptionProvider for DeepgramProvider {
+    fn name(&self) -> &'static str {
+        "Deepgram API"
+    }
+
+    fn is_available(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
+    fn model(&self) -> Option<&str> {
+        Some(&self.model)
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio_path: &'a Path,
+        language: &'a str,
+        model: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>> {
+        Box::pin(async move {
+            let response = self
+                .send_request(audio_path, language, model, false)
+                .await?;
+
+            let text = response
+                .best_alternative()
+                .map(|alternative| alternative.transcript.trim().to_string())
+                .unwrap_or_default();
+            info!("Transcription complete: {} chars", text.len());
+            debug!("Raw transcription: {}", text);
+
+            Ok(text)
+        })
+    }
+
+    fn transcribe_with_segments<'a>(
+        &'a self,
+        audio_path: &'a Path,
+        language: &'a str,
+        model: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = Result<Transcription>> + Send + 'a>> {
+        Box::pin(async move {
+            let response = self.send_request(audio_path, language, model, true).await?;
+
+            let (text, confidence) = match response.best_alternative() {
+                Some(alternative) => (
+                    alternative.transcript.trim().to_string(),
+                    alternative.confidence,
+                ),
+                None => (String::new(), None),
+            };
+
+            let segments = response
+                .results
+                .utterances
+                .into_iter()
+                .map(|u| Segment {
+                    start: u.start,
+                    end: u.end,
+                    text: u.transcript.trim().to_string(),
+                })
+                .collect::<Vec<_>>();
+
+            info!(
+                "Transcription complete: {} chars, {} segments",
+                text.len(),
+                segments.len()
+            );
+
+            Ok(Transcription {
+                text,
+                segments: Some(segments),
+                confidence,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_listen_response() {
+        let response: ListenResponse = serde_json::from_str(
+            r#"{"metadata": {"request_id": "abc"}, "results": {
+                "channels": [{"alternatives": [{"transcript": " Hello there. ", "confidence": 0.97}]}],
+                "utterances": [{"start": 0.1, "end": 1.2, "transcript": "Hello there.", "confidence": 0.97}]
+            }}"#,
+        )
+        .unwrap();
+        let alternative = response.best_alternative().unwrap();
+        assert_eq!(alternative.transcript.trim(), "Hello there.");
+        assert_eq!(alternative.confidence, Some(0.97));
+        assert_eq!(response.results.utterances.len(), 1);
+
+        let response: ListenResponse =
+            serde_json::from_str(r#"{"results": {"channels": []}}"#).unwrap();
+        assert!(response.best_alternative().is_none());
+    }
+
+    #[test]
+    fn test_query_params_language() {
+        let provider =
+            DeepgramProvider::new("key".to_string(), None, DEEPGRAM_DEFAULT_MODEL.to_string())
+                .unwrap();
+        let params = provider.query_params("de", "nova-3", false);
+        assert!(params.contains(&("language", "de".to_string())));
+        assert!(!params.iter().any(|(key, _)| *key == "detect_language"));
+
+        let params = provider.query_params("auto", "nova-3", true);
+        assert!(params.contains(&("detect_language", "true".to_string())));
+        assert!(params.contains(&("utterances", "true".to_string())));
+        assert!(!params.iter().any(|(key, _)| *key == "language"));
+    }
+}
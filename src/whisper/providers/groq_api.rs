@@ -0,0 +1,88 @@
+use anyhow::Result;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::time::Duration;
+use tracing::info;
+
+use crate::whisper::provider::{Task, Transcription, TranscriptionProvider};
+use crate::whisper::providers::OpenAIProvider;
+
+pub const GROQ_ENDPOINT: &str = "https://api.groq.com/openai/v1/audio/transcriptions";
+pub const GROQ_DEFAULT_MODEL: &str = "whisper-large-v3-turbo";
+pub const GROQ_API_KEY_ENV: &str = "GROQ_API_KEY";
+
+/// Groq's Whisper endpoint is OpenAI-compatible, so requests go through `OpenAIProvider`
+pub struct GroqProvider {
+    inner: OpenAIProvider,
+}
+
+impl GroqProvider {
+    pub fn new(api_key: String, endpoint: Option<String>, model: String) -> Result<Self> {
+        let endpoint = endpoint.unwrap_or_else(|| GROQ_ENDPOINT.to_string());
+        info!("Initialized Groq provider with model: {}", model);
+
+        Ok(Self {
+            inner: OpenAIProvider::new(api_key, Some(endpoint), model)?,
+        })
+    }
+
+    pub fn with_task(mut self, task: Task) -> Self {
+        self.inner = self.inner.with_task(task);
+        self
+    }
+
+    pub fn with_initial_prompt(mut self, prompt: Option<String>) -> Self {
+        self.inner = self.inner.with_initial_prompt(prompt);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, timeout: Duration, max_attempts: u32) -> Self {
+        self.inner = self.inner.with_retry_policy(timeout, max_attempts);
+        self
+    }
+
+    /// Read the API key from `GROQ_API_KEY`, ignoring empty values
+    pub fn api_key_from_env() -> Option<String> {
+        std::env::var(GROQ_API_KEY_ENV)
+            .ok()
+            .filter(|key| !key.is_empty())
+    }
+}
+
+impl TranscriptionProvider for GroqProvider {
+    fn name(&self) -> &'static str {
+        "Groq API"
+    }
+
+    fn is_available(&self) -> bool {
+        self.inner.is_available()
+    }
+
+    fn model(&self) -> Option<&str> {
+        self.inner.model()
+    }
+
+    fn max_upload_bytes(&self) -> Option<u64> {
+        self.inner.max_upload_bytes()
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio_path: &'a Path,
+        language: &'a str,
+        model: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>> {
+        self.inner.transcribe(audio_path, language, model)
+    }
+
+    fn transcribe_with_segments<'a>(
+        &'a self,
+        audio_path: &'a Path,
+        language: &'a str,
+        model: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = Result<Transcription>> + Send + 'a>> {
+        self.inner
+            .transcribe_with_segments(audio_path, language, model)
+    }
+}
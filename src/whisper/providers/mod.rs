@@ -1,7 +1,16 @@
+pub mod azure_openai;
+pub mod deepgram_api;
+pub mod groq_api;
 pub mod openai_api;
 pub mod openai_cli;
+mod retry;
 pub mod whisper_cpp;
+pub mod whisper_cpp_server;
 
+pub use azure_openai::AzureOpenAIProvider;
+pub use deepgram_api::DeepgramProvider;
+pub use groq_api::GroqProvider;
 pub use openai_api::OpenAIProvider;
 pub use openai_cli::OpenAIWhisperCliProvider;
 pub use whisper_cpp::WhisperCppProvider;
+pub use whisper_cpp_server::WhisperCppServerProvider;
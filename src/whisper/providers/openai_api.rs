@@ -4,25 +4,140 @@ use serde::Deserialize;
 use std::future::Future;
 use std::path::Path;
 use std::pin::Pin;
-use tracing::{debug, error, info};
+use std::time::Duration;
+use tracing::{debug, info};
 
-use crate::whisper::provider::TranscriptionProvider;
+use crate::whisper::provider::{
+    is_auto_language, Segment, Task, Transcription, TranscriptionProvider,
+};
+use crate::whisper::providers::retry::send_with_retries;
+
+pub const OPENAI_ENDPOINT: &str = "https://api.openai.com/v1/audio/transcriptions";
+pub const OPENAI_API_KEY_ENV: &str = "OPENAI_API_KEY";
+pub const DEFAULT_TIMEOUT_SECS: u64 = 60;
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+/// Upload limit of the transcription endpoint
+pub const MAX_UPLOAD_BYTES: u64 = 25 * 1024 * 1024;
+
+/// How the API key is sent with each request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuthStyle {
+    /// `Authorization: Bearer <key>`, used by OpenAI and compatible APIs
+    #[default]
+    Bearer,
+    /// `api-key: <key>`, used by Azure OpenAI
+    ApiKeyHeader,
+    /// No credentials, for local OpenAI-compatible servers
+    None,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct VerboseTranscriptionResponse {
+    #[serde(default)]
+    pub(crate) text: String,
+    #[serde(default)]
+    segments: Vec<VerboseSegment>,
+}
 
 #[derive(Debug, Deserialize)]
-struct TranscriptionResponse {
+struct VerboseSegment {
+    #[serde(default)]
+    start: f64,
+    #[serde(default)]
+    end: f64,
+    #[serde(default)]
     text: String,
+    #[serde(default)]
+    avg_logprob: Option<f64>,
+    #[serde(default)]
+    no_speech_prob: Option<f64>,
+}
+
+impl VerboseTranscriptionResponse {
+    pub(crate) fn into_transcription(self) -> Transcription {
+        let confidence = segment_confidence(&self.segments);
+        let segments = self
+            .segments
+            .into_iter()
+            .map(|s| Segment {
+                start: s.start,
+                end: s.end,
+                text: s.text.trim().to_string(),
+            })
+            .collect();
+
+        Transcription {
+            text: self.text.trim().to_string(),
+            segments: Some(segments),
+            confidence,
+        }
+    }
+}
+
+/// Duration-weighted mean over segments of `exp(avg_logprob) * (1 - no_speech_prob)`,
+/// or `None` when the response carries no scores
+fn segment_confidence(segments: &[VerboseSegment]) -> Option<f64> {
+    let mut total = 0.0;
+    let mut weights = 0.0;
+    for segment in segments {
+        let (Some(logprob), Some(no_speech)) = (segment.avg_logprob, segment.no_speech_prob) else {
+            continue;
+        };
+        let weight = (segment.end - segment.start).max(0.01);
+        total += logprob.exp().min(1.0) * (1.0 - no_speech) * weight;
+        weights += weight;
+    }
+    (weights > 0.0).then(|| total / weights)
+}
+
+/// Parse a successful response body, tolerating what OpenAI-compatible servers
+/// do differently: plain-text bodies, `text` missing next to `segments`, and
+/// segments without timings. HTML (a proxy or login page) is still an error.
+pub(crate) fn parse_response(body: &str) -> Result<VerboseTranscriptionResponse> {
+    let trimmed = body.trim();
+    if trimmed.starts_with('<') {
+        anyhow::bail!("Expected a transcription but the server returned markup: {trimmed:.200}");
+    }
+    if !trimmed.starts_with('{') {
+        return Ok(VerboseTranscriptionResponse {
+            text: trimmed.to_string(),
+            segments: Vec::new(),
+        });
+    }
+
+    let mut response: VerboseTranscriptionResponse =
+        serde_json::from_str(trimmed).context("Failed to parse transcription response")?;
+    if response.text.trim().is_empty() {
+        response.text = response
+            .segments
+            .iter()
+            .map(|segment| segment.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+    Ok(response)
 }
 
 #[derive(Debug, Deserialize)]
 struct ErrorResponse {
-    error: ErrorDetail,
+    error: ErrorBody,
+}
+
+/// OpenAI sends an object; some compatible servers send a bare string
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ErrorBody {
+    Detail(ErrorDetail),
+    Message(String),
 }
 
 #[derive(Debug, Deserialize)]
 struct ErrorDetail {
     message: String,
     r#type: Option<String>,
-    code: Option<String>,
+    /// A string from OpenAI, a number from some compatible servers
+    code: Option<serde_json::Value>,
 }
 
 pub struct OpenAIProvider {
@@ -30,13 +145,24 @@ pub struct OpenAIProvider {
     api_key: String,
     endpoint: String,
     model: String,
+    task: Task,
+    initial_prompt: Option<String>,
+    auth_style: AuthStyle,
+    timeout: Duration,
+    max_attempts: u32,
 }
 
 impl OpenAIProvider {
+    /// API key from `OPENAI_API_KEY`, used when the config has none
+    pub fn api_key_from_env() -> Option<String> {
+        std::env::var(OPENAI_API_KEY_ENV)
+            .ok()
+            .filter(|key| !key.is_empty())
+    }
+
     pub fn new(api_key: String, endpoint: Option<String>, model: String) -> Result<Self> {
         let client = reqwest::Client::new();
-        let endpoint = endpoint
-            .unwrap_or_else(|| "https://api.openai.com/v1/audio/transcriptions".to_string());
+        let endpoint = endpoint.unwrap_or_else(|| OPENAI_ENDPOINT.to_string());
 
         info!("Initialized OpenAI provider with endpoint: {}", endpoint);
 
@@ -45,8 +171,166 @@ impl OpenAIProvider {
             api_key,
             endpoint,
             model,
+            task: Task::Transcribe,
+            initial_prompt: None,
+            auth_style: AuthStyle::Bearer,
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
         })
     }
+
+    /// Per-request timeout and how many times transient failures are attempted
+    pub fn with_retry_policy(mut self, timeout: Duration, max_attempts: u32) -> Self {
+        self.timeout = timeout;
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_initial_prompt(mut self, prompt: Option<String>) -> Self {
+        self.initial_prompt = prompt;
+        self
+    }
+
+    pub fn with_task(mut self, task: Task) -> Self {
+        self.task = task;
+        self
+    }
+
+    pub fn with_auth_style(mut self, auth_style: AuthStyle) -> Self {
+        self.auth_style = auth_style;
+        self
+    }
+
+    /// Header carrying the API key, in the configured style
+    fn auth_header(&self) -> Option<(&'static str, String)> {
+        match self.auth_style {
+            AuthStyle::Bearer => Some(("Authorization", format!("Bearer {}", self.api_key))),
+            AuthStyle::ApiKeyHeader => Some(("api-key", self.api_key.clone())),
+            AuthStyle::None => None,
+        }
+    }
+
+    fn build_form(
+        &self,
+        audio_data: Vec<u8>,
+        filename: &str,
+        language: &str,
+        model: &str,
+        response_format: &str,
+    ) -> Result<Form> {
+        let audio_part = Part::bytes(audio_data)
+            .file_name(filename.to_string())
+            .mime_str(mime_type(Path::new(filename)))
+            .context("Failed to set MIME type")?;
+
+        let mut form = Form::new()
+            .part("file", audio_part)
+            .text("model", model.to_string());
+
+        if let Some(language) = self.language_field(language) {
+            form = form.text("language", language.to_string());
+        }
+
+        if let Some(prompt) = &self.initial_prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+
+        Ok(form.text("response_format", response_format.to_string()))
+    }
+
+    /// Value for the `language` form field; omitted for auto-detection and for
+    /// the translations endpoint, which always outputs English
+    fn language_field<'a>(&self, language: &'a str) -> Option<&'a str> {
+        if self.task == Task::Translate || is_auto_language(language) {
+            None
+        } else {
+            Some(language)
+        }
+    }
+
+    /// POST the audio with retries and return the successful response body
+    async fn send_request(
+        &self,
+        audio_path: &Path,
+        language: &str,
+        model: Option<&str>,
+        response_format: &str,
+    ) -> Result<String> {
+        info!("Transcribing audio file via OpenAI API: {:?}", audio_path);
+
+        let audio_data = tokio::fs::read(audio_path)
+            .await
+            .context("Failed to read audio file")?;
+
+        // The API infers the format from the name, so keep the real extension
+        let filename = match audio_path.file_name().and_then(|n| n.to_str()) {
+            Some(name) => name.to_string(),
+            None => {
+                let extension = audio_path.extension().and_then(|e| e.to_str());
+                format!("audio.{}", extension.unwrap_or("wav"))
+            }
+        };
+
+        let model = model.unwrap_or(&self.model);
+        debug!(
+            "Sending request to OpenAI API with model: {}, language: {}",
+            model, language
+        );
+
+        send_with_retries(
+            "OpenAI API",
+            self.max_attempts,
+            || {
+                // Multipart forms are consumed on send, so rebuild one per attempt
+                let form = self.build_form(
+                    audio_data.clone(),
+                    &filename,
+                    language,
+                    model,
+                    response_format,
+                )?;
+                let mut request = self.client.post(self.request_endpoint());
+                if let Some((name, value)) = self.auth_header() {
+                    request = request.header(name, value);
+                }
+                Ok(request.timeout(self.timeout).multipart(form))
+            },
+            parse_error,
+        )
+        .await
+    }
+
+    /// Endpoint for the configured task; translations live next to transcriptions,
+    /// and any query string (Azure's `api-version`) is kept
+    fn request_endpoint(&self) -> String {
+        match self.task {
+            Task::Transcribe => self.endpoint.clone(),
+            Task::Translate => self
+                .endpoint
+                .replace("/audio/transcriptions", "/audio/translations"),
+        }
+    }
+}
+
+/// Content type for an upload, from its file extension. Covers every format
+/// the transcription endpoints accept; anything else is sent as opaque bytes
+/// and left to the API to reject.
+pub(crate) fn mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+
+    match extension.as_deref() {
+        Some("wav") => "audio/wav",
+        Some("mp3" | "mpga" | "mpeg") => "audio/mpeg",
+        Some("ogg" | "oga" | "opus") => "audio/ogg",
+        Some("flac") => "audio/flac",
+        Some("m4a") => "audio/mp4",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "audio/webm",
+        _ => "application/octet-stream",
+    }
 }
 
 impl TranscriptionProvider for OpenAIProvider {
@@ -55,91 +339,149 @@ impl TranscriptionProvider for OpenAIProvider {
     }
 
     fn is_available(&self) -> bool {
-        !self.api_key.is_empty()
+        self.auth_style == AuthStyle::None || !self.api_key.is_empty()
+    }
+
+    fn model(&self) -> Option<&str> {
+        Some(&self.model)
+    }
+
+    fn max_upload_bytes(&self) -> Option<u64> {
+        Some(MAX_UPLOAD_BYTES)
     }
 
     fn transcribe<'a>(
         &'a self,
         audio_path: &'a Path,
         language: &'a str,
+        model: Option<&'a str>,
     ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>> {
         Box::pin(async move {
-            info!("Transcribing audio file via OpenAI API: {:?}", audio_path);
+            let response_text = self
+                .send_request(audio_path, language, model, "json")
+                .await?;
+
+            let text = parse_response(&response_text)?.text.trim().to_string();
+            info!("Transcription complete: {} chars", text.len());
+            debug!("Raw transcription: {}", text);
+
+            Ok(text)
+        })
+    }
+
+    fn transcribe_with_segments<'a>(
+        &'a self,
+        audio_path: &'a Path,
+        language: &'a str,
+        model: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = Result<Transcription>> + Send + 'a>> {
+        Box::pin(async move {
+            let response_text = self
+                .send_request(audio_path, language, model, "verbose_json")
+                .await?;
 
-            let audio_data = tokio::fs::read(audio_path)
-                .await
-                .context("Failed to read audio file")?;
+            let transcription = parse_response(&response_text)?.into_transcription();
+            info!(
+                "Transcription complete: {} chars, {} segments",
+                transcription.text.len(),
+                transcription.segments.as_ref().map_or(0, Vec::len)
+            );
 
-            let filename = audio_path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("audio.wav");
+            Ok(transcription)
+        })
+    }
+}
 
-            let audio_part = Part::bytes(audio_data)
-                .file_name(filename.to_string())
-                .mime_str("audio/wav")
-                .context("Failed to set MIME type")?;
+/// Message from an error body in OpenAI's format, or the bare string some
+/// compatible servers send
+fn parse_error(body: &str) -> Option<String> {
+    match serde_json::from_str::<ErrorResponse>(body).ok()?.error {
+        ErrorBody::Detail(detail) => Some(format!(
+            "{} (type: {:?}, code: {:?})",
+            detail.message,
+            detail.r#type,
+            detail.code.map(|code| code.to_string())
+        )),
+        ErrorBody::Message(message) => Some(message),
+    }
+}
 
-            let mut form = Form::new()
-                .part("file", audio_part)
-                .text("model", self.model.clone());
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            if !language.is_empty() && language != "auto" {
-                form = form.text("language", language.to_string());
-            }
+    #[test]
+    fn test_segment_confidence() {
+        let response: VerboseTranscriptionResponse = serde_json::from_str(
+            r#"{"text": "hi", "segments": [
+                {"start": 0.0, "end": 1.0, "text": "hi", "avg_logprob": 0.0, "no_speech_prob": 0.0},
+                {"start": 1.0, "end": 4.0, "text": " you", "avg_logprob": -0.7, "no_speech_prob": 0.9}
+            ]}"#,
+        )
+        .unwrap();
+        let confidence = segment_confidence(&response.segments).unwrap();
+        // 1.0 over one second and ~0.05 over three seconds
+        assert!(confidence > 0.25 && confidence < 0.3, "{confidence}");
 
-            form = form.text("response_format", "json");
+        let response: VerboseTranscriptionResponse = serde_json::from_str(
+            r#"{"text": "hi", "segments": [{"start": 0, "end": 1, "text": "hi"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(segment_confidence(&response.segments), None);
+    }
 
-            debug!(
-                "Sending request to OpenAI API with model: {}, language: {}",
-                self.model, language
-            );
+    #[test]
+    fn test_mime_type_from_extension() {
+        assert_eq!(mime_type(Path::new("/tmp/rec.wav")), "audio/wav");
+        assert_eq!(mime_type(Path::new("/tmp/rec.MP3")), "audio/mpeg");
+        assert_eq!(mime_type(Path::new("/tmp/rec.ogg")), "audio/ogg");
+        assert_eq!(mime_type(Path::new("/tmp/rec.m4a")), "audio/mp4");
+        assert_eq!(mime_type(Path::new("/tmp/rec")), "application/octet-stream");
+    }
 
-            let response = self
-                .client
-                .post(&self.endpoint)
-                .header("Authorization", format!("Bearer {}", self.api_key))
-                .multipart(form)
-                .send()
-                .await
-                .context("Failed to send request to OpenAI API")?;
-
-            let status = response.status();
-            let response_text = response
-                .text()
-                .await
-                .context("Failed to read response body")?;
-
-            if !status.is_success() {
-                error!(
-                    "OpenAI API request failed with status {}: {}",
-                    status, response_text
-                );
-
-                if let Ok(error_response) = serde_json::from_str::<ErrorResponse>(&response_text) {
-                    return Err(anyhow::anyhow!(
-                        "OpenAI API error: {} (type: {:?}, code: {:?})",
-                        error_response.error.message,
-                        error_response.error.r#type,
-                        error_response.error.code
-                    ));
-                }
+    #[test]
+    fn test_language_field() {
+        let provider =
+            OpenAIProvider::new("key".to_string(), None, "whisper-1".to_string()).unwrap();
+        assert_eq!(provider.language_field("de"), Some("de"));
+        assert_eq!(provider.language_field("auto"), None);
+        assert_eq!(provider.language_field(""), None);
 
-                return Err(anyhow::anyhow!(
-                    "OpenAI API request failed with status {}: {}",
-                    status,
-                    response_text
-                ));
-            }
+        let provider = provider.with_task(Task::Translate);
+        assert_eq!(provider.language_field("de"), None);
+    }
 
-            let transcription: TranscriptionResponse = serde_json::from_str(&response_text)
-                .context("Failed to parse transcription response")?;
+    #[test]
+    fn test_auth_header_styles() {
+        let provider =
+            OpenAIProvider::new("key".to_string(), None, "whisper-1".to_string()).unwrap();
+        assert_eq!(
+            provider.auth_header(),
+            Some(("Authorization", "Bearer key".to_string()))
+        );
 
-            let text = transcription.text.trim().to_string();
-            info!("Transcription complete: {} chars", text.len());
-            debug!("Raw transcription: {}", text);
+        let provider = provider.with_auth_style(AuthStyle::ApiKeyHeader);
+        assert_eq!(provider.auth_header(), Some(("api-key", "key".to_string())));
 
-            Ok(text)
-        })
+        let provider = OpenAIProvider::new(String::new(), None, "whisper-1".to_string())
+            .unwrap()
+            .with_auth_style(AuthStyle::None);
+        assert_eq!(provider.auth_header(), None);
+        assert!(provider.is_available());
+    }
+
+    #[test]
+    fn test_parse_response_tolerates_compatible_servers() {
+        assert_eq!(parse_response(r#"{"text": " hi "}"#).unwrap().text, " hi ");
+        assert_eq!(parse_response("hello there\n").unwrap().text, "hello there");
+        assert_eq!(parse_response("").unwrap().text, "");
+
+        let response =
+            parse_response(r#"{"segments": [{"text": " one"}, {"text": "two "}]}"#).unwrap();
+        assert_eq!(response.text, "one two");
+        assert_eq!(response.segments[0].start, 0.0);
+
+        assert!(parse_response("<html>Bad gateway</html>").is_err());
+        assert!(parse_response("{not json").is_err());
     }
 }
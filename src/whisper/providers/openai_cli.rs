@@ -1,20 +1,29 @@
 use anyhow::{Context, Result};
+use std::ffi::OsString;
 use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::process::Command;
+use tokio::process::Command as AsyncCommand;
 use tracing::{error, info};
 use which::which;
 
-use crate::whisper::provider::TranscriptionProvider;
+use crate::whisper::provider::{is_auto_language, Task, TranscriptionProvider};
 
 pub struct OpenAIWhisperCliProvider {
     command_path: PathBuf,
     model: String,
+    output_dir: PathBuf,
+    task: Task,
+    initial_prompt: Option<String>,
 }
 
 impl OpenAIWhisperCliProvider {
-    pub fn new(command_path: Option<String>, model: String) -> Result<Self> {
+    pub fn new(
+        command_path: Option<String>,
+        model: String,
+        output_dir: Option<PathBuf>,
+    ) -> Result<Self> {
         let command_path = if let Some(path) = command_path {
             let custom_path = PathBuf::from(path);
             if custom_path.exists() {
@@ -51,8 +60,56 @@ impl OpenAIWhisperCliProvider {
         Ok(Self {
             command_path,
             model,
+            output_dir: output_dir.unwrap_or_else(std::env::temp_dir),
+            task: Task::Transcribe,
+            initial_prompt: None,
         })
     }
+
+    pub fn with_initial_prompt(mut self, prompt: Option<String>) -> Self {
+        self.initial_prompt = prompt;
+        self
+    }
+
+    pub fn with_task(mut self, task: Task) -> Self {
+        self.task = task;
+        self
+    }
+
+    /// Command-line arguments for transcribing `audio_path`. `--language` is
+    /// left out for "auto" so whisper detects the spoken language itself.
+    fn build_args(&self, audio_path: &Path, language: &str, model: Option<&str>) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![
+            audio_path.into(),
+            "--model".into(),
+            model.unwrap_or(&self.model).into(),
+        ];
+
+        if !is_auto_language(language) {
+            args.push("--language".into());
+            args.push(language.into());
+        }
+
+        let task = match self.task {
+            Task::Transcribe => "transcribe",
+            Task::Translate => "translate",
+        };
+        args.extend([
+            "--task".into(),
+            task.into(),
+            "--output_format".into(),
+            "txt".into(),
+            "--output_dir".into(),
+            self.output_dir.as_os_str().into(),
+        ]);
+
+        if let Some(prompt) = &self.initial_prompt {
+            args.push("--initial_prompt".into());
+            args.push(prompt.into());
+        }
+
+        args
+    }
 }
 
 impl TranscriptionProvider for OpenAIWhisperCliProvider {
@@ -64,30 +121,25 @@ impl TranscriptionProvider for OpenAIWhisperCliProvider {
         self.command_path.exists()
     }
 
+    fn model(&self) -> Option<&str> {
+        Some(&self.model)
+    }
+
     fn transcribe<'a>(
         &'a self,
         audio_path: &'a Path,
         language: &'a str,
+        model: Option<&'a str>,
     ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>> {
-        let audio_path = audio_path.to_path_buf();
-        let language = language.to_string();
-        let command_path = self.command_path.clone();
-        let model = self.model.clone();
-
         Box::pin(async move {
             info!("Using OpenAI Whisper CLI to transcribe: {:?}", audio_path);
 
-            let output = Command::new(&command_path)
-                .arg(&audio_path)
-                .arg("--model")
-                .arg(&model)
-                .arg("--language")
-                .arg(&language)
-                .arg("--output_format")
-                .arg("txt")
-                .arg("--output_dir")
-                .arg("/tmp")
+            // Killed if the transcription timeout drops this future
+            let output = AsyncCommand::new(&self.command_path)
+                .args(self.build_args(audio_path, language, model))
+                .kill_on_drop(true)
                 .output()
+                .await
                 .context("Failed to execute whisper command")?;
 
             if !output.status.success() {
@@ -102,11 +154,12 @@ impl TranscriptionProvider for OpenAIWhisperCliProvider {
                 .to_str()
                 .context("Invalid audio filename")?;
 
-            let output_path = PathBuf::from(format!("/tmp/{audio_stem}.txt"));
-            let transcription = std::fs::read_to_string(&output_path)
+            let output_path = self.output_dir.join(format!("{audio_stem}.txt"));
+            let transcription = tokio::fs::read_to_string(&output_path)
+                .await
                 .context("Failed to read transcription output")?;
 
-            let _ = std::fs::remove_file(&output_path);
+            let _ = tokio::fs::remove_file(&output_path).await;
 
             let transcription = transcription.trim().to_string();
             info!("Transcription complete: {} chars", transcription.len());
@@ -115,3 +168,38 @@ impl TranscriptionProvider for OpenAIWhisperCliProvider {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider() -> OpenAIWhisperCliProvider {
+        OpenAIWhisperCliProvider {
+            command_path: PathBuf::from("whisper"),
+            model: "base".to_string(),
+            output_dir: PathBuf::from("/tmp"),
+            task: Task::Transcribe,
+            initial_prompt: None,
+        }
+    }
+
+    #[test]
+    fn test_build_args_language() {
+        let args = provider().build_args(Path::new("a.wav"), "de", None);
+        let pos = args.iter().position(|a| a == "--language").unwrap();
+        assert_eq!(args[pos + 1], "de");
+
+        let args = provider().build_args(Path::new("a.wav"), "auto", None);
+        assert!(!args.iter().any(|a| a == "--language"));
+    }
+
+    #[test]
+    fn test_build_args_model_override() {
+        let args = provider().build_args(Path::new("a.wav"), "en", None);
+        let pos = args.iter().position(|a| a == "--model").unwrap();
+        assert_eq!(args[pos + 1], "base");
+
+        let args = provider().build_args(Path::new("a.wav"), "en", Some("large-v3"));
+        assert_eq!(args[pos + 1], "large-v3");
+    }
+}
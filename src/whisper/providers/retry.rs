@@ -0,0 +1,152 @@
+use anyhow::{Context, Result};
+use reqwest::{RequestBuilder, StatusCode};
+use std::time::Duration;
+use tracing::{error, warn};
+
+/// Send the request `build` makes until it succeeds or `max_attempts` is
+/// used up, and return the successful response body. Connection failures,
+/// timeouts, rate limits and 5xx responses are retried with exponential
+/// backoff. A final error response is described with `parse_error` when it
+/// recognizes the body, otherwise with the raw body.
+pub(crate) async fn send_with_retries<B, E>(
+    service: &str,
+    max_attempts: u32,
+    mut build: B,
+    parse_error: E,
+) -> Result<String>
+where
+    B: FnMut() -> Result<RequestBuilder>,
+    E: Fn(&str) -> Option<String>,
+{
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+
+        let response = match build()?.send().await {
+            Ok(response) => response,
+            Err(e) if attempt < max_attempts && is_retryable_error(&e) => {
+                backoff(service, attempt, max_attempts, &e.to_string()).await;
+                continue;
+            }
+            Err(e) => {
+                return Err(anyhow::Error::new(e).context(format!(
+                    "Failed to send request to {service} after {attempt} attempt(s)"
+                )));
+            }
+        };
+
+        let status = response.status();
+        let response_text = response
+            .text()
+            .await
+            .context("Failed to read response body")?;
+
+        if status.is_success() {
+            return Ok(response_text);
+        }
+
+        if attempt < max_attempts && is_retryable_status(status) {
+            backoff(service, attempt, max_attempts, &format!("HTTP {status}")).await;
+            continue;
+        }
+
+        error!(
+            "{} request failed with status {}: {}",
+            service, status, response_text
+        );
+
+        return Err(match parse_error(&response_text) {
+            Some(detail) => anyhow::anyhow!("{service} error after {attempt} attempt(s): {detail}"),
+            None => anyhow::anyhow!(
+                "{service} request failed with status {status} after {attempt} attempt(s): {response_text}"
+            ),
+        });
+    }
+}
+
+/// Exponential backoff before the next attempt: 500ms, 1s, 2s, ...
+async fn backoff(service: &str, attempt: u32, max_attempts: u32, reason: &str) {
+    let delay = Duration::from_millis(500 * 2u64.pow(attempt - 1));
+    warn!(
+        "{} attempt {}/{} failed ({}), retrying in {}ms",
+        service,
+        attempt,
+        max_attempts,
+        reason,
+        delay.as_millis()
+    );
+    tokio::time::sleep(delay).await;
+}
+
+/// Connection failures and timeouts are worth retrying; malformed requests are not
+fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout() || error.is_request()
+}
+
+/// Rate limits and server-side failures are transient, 4xx client errors are not
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable_status(StatusCode::UNAUTHORIZED));
+    }
+
+    /// Answers requests with `responses` in order, as (status line, body)
+    async fn serve(responses: Vec<(&'static str, &'static str)>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            for (status, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0; 4096];
+                let _ = socket.read(&mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retries() {
+        let client = reqwest::Client::new();
+
+        let url = serve(vec![("503 Service Unavailable", ""), ("200 OK", "done")]).await;
+        let body = send_with_retries("Test API", 2, || Ok(client.get(&url)), |_| None).await;
+        assert_eq!(body.unwrap(), "done");
+
+        let url = serve(vec![("400 Bad Request", "bad model")]).await;
+        let err = send_with_retries(
+            "Test API",
+            3,
+            || Ok(client.get(&url)),
+            |body| Some(format!("parsed {body}")),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Test API error after 1 attempt(s): parsed bad model"
+        );
+    }
+}
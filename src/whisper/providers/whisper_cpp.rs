@@ -1,17 +1,138 @@
 use anyhow::{Context, Result};
+use regex::Regex;
+use std::ffi::OsString;
 use std::future::Future;
+use std::net::TcpListener;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tracing::{error, info, warn};
 use which::which;
 
-use crate::whisper::provider::TranscriptionProvider;
+use crate::normalizer::OutputStyle;
+use crate::whisper::provider::{
+    is_auto_language, Segment, Task, Transcription, TranscriptionProvider, AUTO_LANGUAGE,
+};
+use crate::whisper::providers::WhisperCppServerProvider;
+
+/// How long a freshly spawned `whisper-server` gets to load its model
+const SERVER_STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
 
 pub struct WhisperCppProvider {
     command_path: PathBuf,
-    model_path: Option<String>,
-    model: String,
+    /// Resolved ggml model file, checked to exist when the provider is built
+    model_file: PathBuf,
+    task: Task,
+    initial_prompt: Option<String>,
+    /// `whisper-server` holding the model in memory, when `keep_model_loaded` is on
+    server: Option<ResidentServer>,
+}
+
+/// A `whisper-server` spawned with the configured model and killed with the
+/// provider. Transcribing through it skips the model load the CLI does per run.
+struct ResidentServer {
+    child: Mutex<Child>,
+    client: WhisperCppServerProvider,
+    port: u16,
+    spawned_at: Instant,
+    ready: AtomicBool,
+}
+
+impl ResidentServer {
+    fn spawn(
+        server_path: &Path,
+        model_file: &Path,
+        client: WhisperCppServerProvider,
+        port: u16,
+    ) -> Result<Self> {
+        let child = Command::new(server_path)
+            .arg("-m")
+            .arg(model_file)
+            .args(["--host", "127.0.0.1", "--port", &port.to_string()])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to start {server_path:?}"))?;
+        info!(
+            "Started whisper-server (pid {}) on port {} to keep {:?} loaded",
+            child.id(),
+            port,
+            model_file
+        );
+
+        Ok(Self {
+            child: Mutex::new(child),
+            client,
+            port,
+            spawned_at: Instant::now(),
+            ready: AtomicBool::new(false),
+        })
+    }
+
+    /// Wait until the server accepts connections, which happens once the model is loaded
+    async fn wait_ready(&self) -> Result<()> {
+        if self.ready.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        while self.spawned_at.elapsed() < SERVER_STARTUP_TIMEOUT {
+            if let Ok(Some(status)) = self.child.lock().unwrap().try_wait() {
+                return Err(anyhow::anyhow!("whisper-server exited with {status}"));
+            }
+            if tokio::net::TcpStream::connect(("127.0.0.1", self.port))
+                .await
+                .is_ok()
+            {
+                self.ready.store(true, Ordering::Relaxed);
+                info!(
+                    "whisper-server loaded the model in {}ms, later recordings skip this",
+                    self.spawned_at.elapsed().as_millis()
+                );
+                return Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        Err(anyhow::anyhow!(
+            "whisper-server did not start within {}s",
+            SERVER_STARTUP_TIMEOUT.as_secs()
+        ))
+    }
+
+    async fn transcribe(&self, audio_path: &Path, language: &str) -> Result<Transcription> {
+        self.wait_ready().await?;
+        self.client
+            .transcribe_with_segments(audio_path, language, None)
+            .await
+    }
+}
+
+impl Drop for ResidentServer {
+    fn drop(&mut self) {
+        if let Ok(child) = self.child.get_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// `whisper-server` is built alongside `whisper-cli`, so look next to it first
+fn find_server_binary(command_path: &Path) -> Option<PathBuf> {
+    let sibling = command_path.with_file_name("whisper-server");
+    if sibling.is_file() {
+        return Some(sibling);
+    }
+    which("whisper-server").ok()
+}
+
+/// A port the OS reports as free on loopback
+fn free_port() -> Result<u16> {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).context("Failed to find a free port")?;
+    Ok(listener.local_addr()?.port())
 }
 
 impl WhisperCppProvider {
@@ -40,12 +161,188 @@ impl WhisperCppProvider {
 
         info!("Found whisper.cpp at: {:?}", command_path);
 
+        let model_file = resolve_model_file(&command_path, &model, model_path.as_deref())?;
+        info!("Using whisper.cpp model: {:?}", model_file);
+
         Ok(Self {
             command_path,
-            model_path,
-            model,
+            model_file,
+            task: Task::Transcribe,
+            initial_prompt: None,
+            server: None,
         })
     }
+
+    /// Keep the model loaded in a `whisper-server` instead of reloading it per
+    /// recording, trading its memory for latency. Call after `with_task` and
+    /// `with_initial_prompt`, which the server is set up with. Falls back to the
+    /// CLI when no `whisper-server` binary is found.
+    pub fn with_resident_server(mut self, enabled: bool) -> Self {
+        if !enabled {
+            return self;
+        }
+
+        let Some(server_path) = find_server_binary(&self.command_path) else {
+            warn!("keep_model_loaded is on but whisper-server was not found, using whisper-cli per recording");
+            return self;
+        };
+
+        let server = free_port().and_then(|port| {
+            let client = WhisperCppServerProvider::new(Some(format!("http://127.0.0.1:{port}")))?
+                .with_task(self.task)
+                .with_initial_prompt(self.initial_prompt.clone())
+                .with_timeout(Duration::from_secs(600));
+            ResidentServer::spawn(&server_path, &self.model_file, client, port)
+        });
+        match server {
+            Ok(server) => self.server = Some(server),
+            Err(e) => warn!("Could not keep the whisper.cpp model loaded, using whisper-cli per recording: {e:#}"),
+        }
+        self
+    }
+
+    pub fn with_initial_prompt(mut self, prompt: Option<String>) -> Self {
+        self.initial_prompt = prompt;
+        self
+    }
+
+    pub fn with_task(mut self, task: Task) -> Self {
+        self.task = task;
+        self
+    }
+
+    /// Command-line arguments for transcribing `audio_path`. whisper.cpp
+    /// expects an explicit `-l auto` to detect the spoken language.
+    fn build_args(
+        &self,
+        audio_path: &Path,
+        model_file: &Path,
+        language: &str,
+        timestamps: bool,
+    ) -> Vec<OsString> {
+        let language = if is_auto_language(language) {
+            AUTO_LANGUAGE
+        } else {
+            language
+        };
+
+        let mut args: Vec<OsString> = vec![
+            "-f".into(),
+            audio_path.into(),
+            "-m".into(),
+            model_file.into(),
+            "-l".into(),
+            language.into(),
+            "-np".into(),
+        ];
+
+        if !timestamps {
+            args.push("-nt".into());
+        }
+
+        if self.task == Task::Translate {
+            args.push("-tr".into());
+        }
+
+        if let Some(prompt) = &self.initial_prompt {
+            args.push("--prompt".into());
+            args.push(prompt.into());
+        }
+
+        args
+    }
+
+    /// Transcribe through the resident server when there is one and no model
+    /// override is asked for, which it can't serve. `None` means use the CLI.
+    async fn transcribe_resident(
+        &self,
+        audio_path: &Path,
+        language: &str,
+        model: Option<&str>,
+    ) -> Option<Transcription> {
+        let server = self.server.as_ref().filter(|_| model.is_none())?;
+        let started = Instant::now();
+        match server.transcribe(audio_path, language).await {
+            Ok(transcription) => {
+                info!(
+                    "whisper.cpp transcription took {}ms with the model kept loaded",
+                    started.elapsed().as_millis()
+                );
+                Some(transcription)
+            }
+            Err(e) => {
+                warn!("Resident whisper-server failed, falling back to whisper-cli: {e:#}");
+                None
+            }
+        }
+    }
+
+    /// Run whisper.cpp and return its stdout. With `timestamps` the output keeps
+    /// the `[start --> end]` prefix on each line so segments can be parsed.
+    /// A `model` override is looked up the same way as the configured model.
+    async fn run(
+        &self,
+        audio_path: &Path,
+        language: &str,
+        model: Option<&str>,
+        timestamps: bool,
+    ) -> Result<String> {
+        info!("Using whisper.cpp to transcribe: {:?}", audio_path);
+        warn!("whisper.cpp integration is experimental - consider using OpenAI whisper");
+
+        let model_file = match model {
+            Some(model) => resolve_model_file(&self.command_path, model, None)?,
+            None => self.model_file.clone(),
+        };
+
+        let started = Instant::now();
+        // Killed if the transcription timeout drops this future
+        let output = tokio::process::Command::new(&self.command_path)
+            .args(self.build_args(audio_path, &model_file, language, timestamps))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await
+            .context("Failed to execute whisper.cpp command")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            error!("Whisper.cpp failed: {}", stderr);
+
+            warn!("Trying fallback whisper.cpp command");
+            let mut cmd = tokio::process::Command::new(&self.command_path);
+            cmd.arg("-f")
+                .arg(audio_path)
+                .arg("-m")
+                .arg(&model_file)
+                .kill_on_drop(true);
+
+            if self.task == Task::Translate {
+                cmd.arg("-tr");
+            }
+
+            let output = cmd
+                .output()
+                .await
+                .context("Failed to execute fallback whisper.cpp command")?;
+
+            if !output.status.success() {
+                return Err(anyhow::anyhow!("Whisper.cpp transcription failed"));
+            }
+
+            let transcription = String::from_utf8_lossy(&output.stdout);
+            return Ok(transcription.trim().to_string());
+        }
+
+        info!(
+            "whisper.cpp transcription took {}ms including the model load",
+            started.elapsed().as_millis()
+        );
+        let transcription = String::from_utf8_lossy(&output.stdout);
+        Ok(transcription.trim().to_string())
+    }
 }
 
 impl TranscriptionProvider for WhisperCppProvider {
@@ -54,78 +351,252 @@ impl TranscriptionProvider for WhisperCppProvider {
     }
 
     fn is_available(&self) -> bool {
-        self.command_path.exists()
+        self.command_path.exists() && self.model_file.exists()
+    }
+
+    fn output_style(&self) -> OutputStyle {
+        OutputStyle::Timestamped
     }
 
     fn transcribe<'a>(
         &'a self,
         audio_path: &'a Path,
         language: &'a str,
+        model: Option<&'a str>,
     ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>> {
-        let audio_path = audio_path.to_path_buf();
-        let language = language.to_string();
-        let command_path = self.command_path.clone();
-        let model = self.model.clone();
-        let model_path = self.model_path.clone();
-
         Box::pin(async move {
-            info!("Using whisper.cpp to transcribe: {:?}", audio_path);
-            warn!("whisper.cpp integration is experimental - consider using OpenAI whisper");
+            if let Some(transcription) = self.transcribe_resident(audio_path, language, model).await
+            {
+                return Ok(transcription.text);
+            }
 
-            let model_arg = if let Some(mp) = &model_path {
-                info!("Using custom model path: {}", mp);
-                mp.clone()
-            } else {
-                format!("models/ggml-{model}.bin")
-            };
+            let transcription = self.run(audio_path, language, model, false).await?;
+            info!("Transcription complete: {} chars", transcription.len());
 
-            let mut cmd = Command::new(&command_path);
-            cmd.arg("-f")
-                .arg(&audio_path)
-                .arg("-m")
-                .arg(&model_arg)
-                .arg("-l")
-                .arg(&language)
-                .arg("-nt")
-                .arg("-np")
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .stdin(Stdio::null());
+            Ok(transcription)
+        })
+    }
 
-            let output = cmd
-                .output()
-                .context("Failed to execute whisper.cpp command")?;
+    fn transcribe_with_segments<'a>(
+        &'a self,
+        audio_path: &'a Path,
+        language: &'a str,
+        model: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = Result<Transcription>> + Send + 'a>> {
+        Box::pin(async move {
+            if let Some(transcription) = self.transcribe_resident(audio_path, language, model).await
+            {
+                return Ok(transcription);
+            }
 
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                error!("Whisper.cpp failed: {}", stderr);
+            let raw = self.run(audio_path, language, model, true).await?;
+            let segments = parse_segments(&raw);
+            info!(
+                "Transcription complete: {} chars, {} segments",
+                raw.len(),
+                segments.len()
+            );
 
-                warn!("Trying fallback whisper.cpp command");
-                let mut cmd = Command::new(&command_path);
-                cmd.arg("-f").arg(&audio_path);
+            // Timestamps in the raw text are stripped later by WhisperCppNormalizer
+            Ok(Transcription {
+                text: raw,
+                segments: Some(segments),
+                confidence: None,
+            })
+        })
+    }
+}
 
-                if let Some(mp) = &model_path {
-                    cmd.arg("-m").arg(mp);
-                }
+/// Find the ggml model file for `model`. An explicit `model_path` must exist as
+/// given; otherwise `models/ggml-{model}.bin` is looked up next to the binary,
+/// in the whisper.cpp checkout the binary was built in, and in the working directory.
+fn resolve_model_file(
+    command_path: &Path,
+    model: &str,
+    model_path: Option<&str>,
+) -> Result<PathBuf> {
+    if let Some(path) = model_path {
+        let path = PathBuf::from(path);
+        if path.is_file() {
+            return Ok(path);
+        }
+        return Err(anyhow::anyhow!(
+            "whisper.cpp model not found at {:?} (from model_path). Check the path, or download a model with whisper.cpp's models/download-ggml-model.sh",
+            path
+        ));
+    }
 
-                let output = cmd
-                    .output()
-                    .context("Failed to execute fallback whisper.cpp command")?;
+    let file_name = format!("ggml-{model}.bin");
+    let candidates = model_candidates(command_path, &file_name);
 
-                if !output.status.success() {
-                    return Err(anyhow::anyhow!("Whisper.cpp transcription failed"));
-                }
+    if let Some(found) = candidates.iter().find(|path| path.is_file()) {
+        return Ok(found.clone());
+    }
 
-                let transcription = String::from_utf8_lossy(&output.stdout);
-                return Ok(transcription.trim().to_string());
-            }
+    let searched = candidates
+        .iter()
+        .map(|path| format!("  {}", path.display()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Err(anyhow::anyhow!(
+        "whisper.cpp model '{}' not found. Looked in:\n{}\nDownload it with `./models/download-ggml-model.sh {}` in your whisper.cpp checkout, or set model_path in the [whisper] config",
+        file_name,
+        searched,
+        model
+    ))
+}
 
-            let transcription = String::from_utf8_lossy(&output.stdout);
-            let transcription = transcription.trim().to_string();
+fn model_candidates(command_path: &Path, file_name: &str) -> Vec<PathBuf> {
+    // whisper.cpp builds land in build/bin/, two levels below models/
+    let binary = std::fs::canonicalize(command_path).unwrap_or_else(|_| command_path.to_path_buf());
+    let mut candidates: Vec<PathBuf> = binary
+        .ancestors()
+        .skip(1)
+        .take(3)
+        .map(|dir| dir.join("models").join(file_name))
+        .collect();
+    candidates.push(Path::new("models").join(file_name));
+    candidates
+}
 
-            info!("Transcription complete: {} chars", transcription.len());
+/// Parse `[00:00:01.000 --> 00:00:03.500] text` lines from whisper.cpp into segments
+pub fn parse_segments(raw_output: &str) -> Vec<Segment> {
+    let line_regex = Regex::new(
+        r"^\[(\d{2}):(\d{2}):(\d{2})[:.](\d{3})\s*-->\s*(\d{2}):(\d{2}):(\d{2})[:.](\d{3})\]\s*(.*)$",
+    )
+    .expect("valid segment regex");
 
-            Ok(transcription)
+    let seconds = |caps: &regex::Captures, first: usize| -> f64 {
+        let part = |i: usize| caps[first + i].parse::<u64>().unwrap_or(0);
+        let millis = ((part(0) * 60 + part(1)) * 60 + part(2)) * 1000 + part(3);
+        millis as f64 / 1000.0
+    };
+
+    raw_output
+        .lines()
+        .filter_map(|line| line_regex.captures(line.trim()))
+        .map(|caps| Segment {
+            start: seconds(&caps, 1),
+            end: seconds(&caps, 5),
+            text: caps[9].trim().to_string(),
         })
+        .filter(|segment| !segment.text.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_args_language() {
+        let provider = WhisperCppProvider {
+            command_path: PathBuf::from("whisper-cli"),
+            model_file: PathBuf::from("models/ggml-base.bin"),
+            task: Task::Transcribe,
+            initial_prompt: None,
+            server: None,
+        };
+
+        let args = provider.build_args(Path::new("a.wav"), &provider.model_file, "", false);
+        let pos = args.iter().position(|a| a == "-l").unwrap();
+        assert_eq!(args[pos + 1], "auto");
+
+        let args = provider.build_args(Path::new("a.wav"), &provider.model_file, "fr", false);
+        let pos = args.iter().position(|a| a == "-l").unwrap();
+        assert_eq!(args[pos + 1], "fr");
+        assert!(args.iter().any(|a| a == "-nt"));
+    }
+
+    #[test]
+    fn test_resolve_model_file() {
+        let dir = std::env::temp_dir().join(format!("chezwizper_models_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("build/bin")).unwrap();
+        std::fs::create_dir_all(dir.join("models")).unwrap();
+        std::fs::write(dir.join("models/ggml-tiny.bin"), b"").unwrap();
+        let binary = dir.join("build/bin/whisper-cli");
+
+        let found = resolve_model_file(&binary, "tiny", None).unwrap();
+        assert_eq!(found, dir.join("models/ggml-tiny.bin"));
+
+        let err = resolve_model_file(&binary, "small", None).unwrap_err();
+        assert!(err.to_string().contains("download-ggml-model.sh small"));
+
+        let missing = dir.join("nope.bin");
+        assert!(resolve_model_file(&binary, "tiny", missing.to_str()).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_find_server_binary_next_to_cli() {
+        let dir = std::env::temp_dir().join(format!("chezwizper_server_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cli = dir.join("whisper-cli");
+        assert_ne!(find_server_binary(&cli), Some(dir.join("whisper-server")));
+
+        std::fs::write(dir.join("whisper-server"), b"").unwrap();
+        assert_eq!(find_server_binary(&cli), Some(dir.join("whisper-server")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_run_does_not_block_runtime() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // A whisper-cli stand-in that takes a while to answer
+        let dir = std::env::temp_dir().join(format!("chezwizper_slow_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let command_path = dir.join("whisper-cli");
+        std::fs::write(&command_path, "#!/bin/sh\nsleep 0.5\necho hello\n").unwrap();
+        std::fs::set_permissions(&command_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let provider = WhisperCppProvider {
+            command_path,
+            model_file: dir.join("ggml-base.bin"),
+            task: Task::Transcribe,
+            initial_prompt: None,
+            server: None,
+        };
+
+        let started = Instant::now();
+        let ticker = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            started.elapsed()
+        };
+        let (text, ticked_after) =
+            tokio::join!(provider.run(Path::new("a.wav"), "en", None, false), ticker);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(text.unwrap(), "hello");
+        assert!(
+            ticked_after < Duration::from_millis(400),
+            "{ticked_after:?}"
+        );
+    }
+
+    #[test]
+    fn test_parse_segments() {
+        let raw = "[00:00:00.000 --> 00:00:03.280]   This is me talking\n\
+                   [00:01:03.280 --> 00:01:05.000]  And more text\n\
+                   whisper_print_timings: total time = 100 ms";
+
+        let segments = parse_segments(raw);
+        assert_eq!(
+            segments,
+            vec![
+                Segment {
+                    start: 0.0,
+                    end: 3.28,
+                    text: "This is me talking".to_string()
+                },
+                Segment {
+                    start: 63.28,
+                    end: 65.0,
+                    text: "And more text".to_string()
+                },
+            ]
+        );
     }
 }
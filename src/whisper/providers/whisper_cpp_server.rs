@@ -0,0 +1,238 @@
+use anyhow::{Context, Result};
+use reqwest::multipart::{Form, Part};
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::time::Duration;
+use tracing::{debug, error, info};
+
+use crate::whisper::provider::{
+    is_auto_language, Task, Transcription, TranscriptionProvider, AUTO_LANGUAGE,
+};
+use crate::whisper::providers::openai_api::{mime_type, parse_response, DEFAULT_TIMEOUT_SECS};
+
+/// Where `whisper-server` listens when started without `--host`/`--port`
+pub const WHISPER_SERVER_ENDPOINT: &str = "http://127.0.0.1:8080/inference";
+
+/// Sends recordings to a running whisper.cpp `whisper-server`, which keeps
+/// its model loaded between requests instead of reloading it per recording
+pub struct WhisperCppServerProvider {
+    client: reqwest::Client,
+    endpoint: String,
+    task: Task,
+    initial_prompt: Option<String>,
+    timeout: Duration,
+}
+
+impl WhisperCppServerProvider {
+    /// `endpoint` may be the server's base URL; `/inference` is added when it has no path
+    pub fn new(endpoint: Option<String>) -> Result<Self> {
+        let endpoint = inference_url(endpoint.as_deref().unwrap_or(WHISPER_SERVER_ENDPOINT))?;
+        info!("Initialized whisper.cpp server provider at: {}", endpoint);
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            endpoint,
+            task: Task::Transcribe,
+            initial_prompt: None,
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+        })
+    }
+
+    pub fn with_task(mut self, task: Task) -> Self {
+        self.task = task;
+        self
+    }
+
+    pub fn with_initial_prompt(mut self, prompt: Option<String>) -> Self {
+        self.initial_prompt = prompt;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn build_form(
+        &self,
+        audio_data: Vec<u8>,
+        audio_path: &Path,
+        language: &str,
+        response_format: &str,
+    ) -> Result<Form> {
+        let filename = audio_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("audio.wav");
+        let audio_part = Part::bytes(audio_data)
+            .file_name(filename.to_string())
+            .mime_str(mime_type(audio_path))
+            .context("Failed to set MIME type")?;
+
+        // The server has no implicit detection, it wants an explicit "auto"
+        let language = if is_auto_language(language) {
+            AUTO_LANGUAGE
+        } else {
+            language
+        };
+
+        let mut form = Form::new()
+            .part("file", audio_part)
+            .text("language", language.to_string())
+            .text("temperature", "0.0")
+            .text("response_format", response_format.to_string());
+
+        if self.task == Task::Translate {
+            form = form.text("translate", "true");
+        }
+        if let Some(prompt) = &self.initial_prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+
+        Ok(form)
+    }
+
+    /// POST the audio and return the successful response body. The server is
+    /// local, so failures are reported straight away rather than retried.
+    async fn send_request(
+        &self,
+        audio_path: &Path,
+        language: &str,
+        model: Option<&str>,
+        response_format: &str,
+    ) -> Result<String> {
+        info!(
+            "Transcribing audio file via whisper.cpp server: {:?}",
+            audio_path
+        );
+        if let Some(model) = model {
+            debug!(
+                "whisper.cpp server uses the model it was started with, ignoring {}",
+                model
+            );
+        }
+
+        let audio_data = tokio::fs::read(audio_path)
+            .await
+            .context("Failed to read audio file")?;
+        let form = self.build_form(audio_data, audio_path, language, response_format)?;
+
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .timeout(self.timeout)
+            .multipart(form)
+            .send()
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to reach whisper.cpp server at {}, is whisper-server running?",
+                    self.endpoint
+                )
+            })?;
+
+        let status = response.status();
+        let response_text = response
+            .text()
+            .await
+            .context("Failed to read response body")?;
+
+        if !status.is_success() {
+            error!(
+                "whisper.cpp server request failed with status {}: {}",
+                status, response_text
+            );
+            return Err(anyhow::anyhow!(
+                "whisper.cpp server request failed with status {}: {}",
+                status,
+                response_text.trim()
+            ));
+        }
+
+        Ok(response_text)
+    }
+}
+
+/// Append `/inference` to a bare server URL, leave explicit paths alone
+fn inference_url(endpoint: &str) -> Result<String> {
+    let mut url = reqwest::Url::parse(endpoint)
+        .with_context(|| format!("Invalid whisper.cpp server URL: {endpoint}"))?;
+    if url.path() == "/" {
+        url.set_path("/inference");
+    }
+    Ok(url.to_string())
+}
+
+impl TranscriptionProvider for WhisperCppServerProvider {
+    fn name(&self) -> &'static str {
+        "whisper.cpp server"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio_path: &'a Path,
+        language: &'a str,
+        model: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>> {
+        Box::pin(async move {
+            let response_text = self
+                .send_request(audio_path, language, model, "json")
+                .await?;
+
+            let text = parse_response(&response_text)?.text.trim().to_string();
+            info!("Transcription complete: {} chars", text.len());
+            debug!("Raw transcription: {}", text);
+
+            Ok(text)
+        })
+    }
+
+    fn transcribe_with_segments<'a>(
+        &'a self,
+        audio_path: &'a Path,
+        language: &'a str,
+        model: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = Result<Transcription>> + Send + 'a>> {
+        Box::pin(async move {
+            let response_text = self
+                .send_request(audio_path, language, model, "verbose_json")
+                .await?;
+
+            let transcription = parse_response(&response_text)?.into_transcription();
+            info!(
+                "Transcription complete: {} chars, {} segments",
+                transcription.text.len(),
+                transcription.segments.as_ref().map_or(0, Vec::len)
+            );
+
+            Ok(transcription)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inference_url() {
+        assert_eq!(
+            inference_url("http://gpu-box:8080").unwrap(),
+            "http://gpu-box:8080/inference"
+        );
+        assert_eq!(
+            inference_url("http://127.0.0.1:8080/inference").unwrap(),
+            "http://127.0.0.1:8080/inference"
+        );
+        assert_eq!(
+            inference_url("https://proxy.lan/whisper/inference").unwrap(),
+            "https://proxy.lan/whisper/inference"
+        );
+        assert!(inference_url("not a url").is_err());
+    }
+}
@@ -0,0 +1,45 @@
+//! Smoke test for the transcribe_file binary against the real Groq API.
+//!
+//! Run with `GROQ_API_KEY=... cargo test --features groq-smoke-test --test transcribe_file_smoke`.
+#![cfg(feature = "groq-smoke-test")]
+
+use std::process::Command;
+
+#[test]
+fn transcribe_file_with_groq() {
+    assert!(
+        std::env::var("GROQ_API_KEY").is_ok(),
+        "GROQ_API_KEY must be set for the groq-smoke-test feature"
+    );
+
+    // One second of a 440 Hz tone, enough for a request to go through end to end
+    let path = std::env::temp_dir().join(format!("chezwizper_smoke_{}.wav", std::process::id()));
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 16000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for i in 0..16000 {
+        let t = i as f32 / 16000.0;
+        let sample = (t * 440.0 * 2.0 * std::f32::consts::PI).sin() * 0.3;
+        writer
+            .write_sample((sample * i16::MAX as f32) as i16)
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_transcribe_file"))
+        .args(["--provider", "groq", "--model", "whisper-large-v3-turbo"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(
+        output.status.success(),
+        "transcribe_file failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}